@@ -3,10 +3,10 @@ use std::fmt::Formatter;
 
 use json::object;
 use serde::de::Error;
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
-#[derive(Deserialize, Eq, PartialEq, Debug, Copy, Clone)]
+#[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Copy, Clone)]
 pub enum HandAction {
     Fold,
     Check,
@@ -1,8 +1,45 @@
-use clap::Parser;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::{Parser, Subcommand, ValueEnum};
+
+use crate::payout::PayoutStructure;
+use crate::player_components::DEFAULT_START_MONEY;
+use crate::table::{
+    ButtonRule, CashGameConfig, GameVariant, LevelSchedule, RebuyConfig, StopConditions, Table,
+    TableConfig, DEFAULT_MONTE_CARLO_SAMPLES,
+};
+
+/// How the connected bots are organized into games
+#[derive(ValueEnum, Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TournamentFormat {
+    /// Everyone plays at one table until one player is left
+    SingleTable,
+    /// Qualifying tables where the top finishers advance until a final table
+    Bracket,
+    /// One tournament over several tables that are balanced and broken as players bust
+    MultiTable,
+}
+
+/// Commands that run instead of hosting a game
+#[derive(Subcommand, Clone, Debug, Eq, PartialEq)]
+pub enum Command {
+    /// Plays a replay file back without any bots and checks it finishes with the recorded results
+    Replay {
+        /// The replay file written by --replay-file
+        file: PathBuf,
+        /// Stops once this hand is dealt and prints the table instead of checking the results if passed
+        #[arg(long)]
+        stop_at_hand: Option<i32>,
+    },
+}
 
 #[derive(Parser, Debug)]
 #[command(author, version, about, long_about = None)]
 pub struct BotArgs {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
     /// The port the server to listen on
     #[arg(short, long, default_value_t = 10100)]
     pub port: i32,
@@ -26,13 +63,347 @@ pub struct BotArgs {
     /// Override to disable all logging if passed
     #[arg(short = 'l', long)]
     pub disable_logging: bool,
+
+    /// How the connected bots are organized into games
+    #[arg(long, value_enum, default_value_t = TournamentFormat::SingleTable)]
+    pub tournament_format: TournamentFormat,
+
+    /// The most players that may sit at one table of a multi-table tournament
+    #[arg(long, default_value_t = Table::MAX_PLAYERS)]
+    pub max_table_size: usize,
+
+    /// How many players from each qualifying table advance in a bracket tournament
+    #[arg(long, default_value_t = 3)]
+    pub advance_per_table: usize,
+
+    /// Plays a cash game of this many hands instead of a tournament if passed
+    #[arg(long)]
+    pub cash_game_hands: Option<i32>,
+
+    /// The stack every cash game player is topped back up to before each hand
+    #[arg(long, default_value_t = DEFAULT_START_MONEY)]
+    pub cash_game_stack: i32,
+
+    /// The fixed ante of a cash game
+    #[arg(long, default_value_t = 1)]
+    pub cash_game_ante: i32,
+
+    /// Lets players that bust in this many first hands of a single table tournament rebuy if passed
+    #[arg(long)]
+    pub rebuy_hands: Option<i32>,
+
+    /// Chips every player still alive gets once the rebuy period ends
+    #[arg(long, default_value_t = 0)]
+    pub add_on_chips: i32,
+
+    /// Plays a single table bounty tournament where every entrant carries this bounty if passed
+    #[arg(long)]
+    pub bounty: Option<i32>,
+
+    /// The percent of the prize pool paid to each place of a single table tournament, such as 50,30,20
+    #[arg(long, value_delimiter = ',')]
+    pub payouts: Vec<f64>,
+
+    /// The total amount paid out by the payouts
+    #[arg(long, default_value_t = 100.0)]
+    pub prize_pool: f64,
+
+    /// Also writes the results to this file if passed
+    #[arg(long)]
+    pub results_file: Option<PathBuf>,
+
+    /// A json file with the blind and ante levels of a single table tournament, see schedules/ for examples
+    #[arg(long)]
+    pub level_schedule: Option<PathBuf>,
+
+    /// Stops a single table game after this many hands if passed
+    #[arg(long)]
+    pub max_hands: Option<i32>,
+
+    /// Stops a single table game after the first hand that ends once this many seconds have passed if passed
+    #[arg(long)]
+    pub max_duration_seconds: Option<f64>,
+
+    /// Stops a single table game instead of moving past this level of the schedule if passed
+    #[arg(long)]
+    pub max_level: Option<usize>,
+
+    /// How the button and blinds of a single table move after players are knocked out
+    #[arg(long, value_enum, default_value_t = ButtonRule::NextAlive)]
+    pub button_rule: ButtonRule,
+
+    /// The poker game played at a single table
+    #[arg(long, value_enum, default_value_t = GameVariant::TexasHoldem)]
+    pub game_variant: GameVariant,
+
+    /// How many times a single table deals the rest of the board when everyone is all in before it is finished
+    #[arg(long, default_value_t = 1)]
+    pub run_it_times: usize,
+
+    /// Lists each player's chips adjusted for their luck in all in hands with the single table results
+    #[arg(long)]
+    pub all_in_adjusted: bool,
+
+    /// How many random boards each all in hand's equity is averaged over when there are too many boards to check each one
+    #[arg(long, default_value_t = DEFAULT_MONTE_CARLO_SAMPLES)]
+    pub all_in_samples: usize,
+
+    /// Also writes each player's stats from a single table game to this json file if passed
+    #[arg(long)]
+    pub stats_file: Option<PathBuf>,
+
+    /// Appends every hand of a single table game to this file in the PokerStars hand history format if passed
+    #[arg(long)]
+    pub hand_history_file: Option<PathBuf>,
+
+    /// Writes the seed, settings and every action of a single table game to this file so it can be replayed if passed
+    #[arg(long)]
+    pub replay_file: Option<PathBuf>,
+
+    /// Shuffles every deck of a single table game from this seed instead of a random one if passed
+    #[arg(long)]
+    pub seed: Option<u64>,
+
+    /// Saves a single table game to this file at the start of every hand, and journals every action next to it, so it can be resumed if passed
+    #[arg(long)]
+    pub checkpoint_file: Option<PathBuf>,
+
+    /// Resumes the game saved in the checkpoint file and its journal instead of starting a new one, bots are seated by identity
+    #[arg(long)]
+    pub resume: bool,
+
+    /// Writes the games, hands, actions, board cards and showdowns of a single table to this SQLite database if passed
+    #[arg(long)]
+    pub database_file: Option<PathBuf>,
+}
+
+impl BotArgs {
+    fn get_payouts(&self) -> Result<PayoutStructure, String> {
+        PayoutStructure::new(self.prize_pool, self.payouts.clone())
+    }
+
+    /// Gets the settings for a single table game
+    pub fn get_table_config(&self) -> TableConfig {
+        TableConfig {
+            cash_game: self.cash_game_hands.map(|hand_count| CashGameConfig {
+                stack_size: self.cash_game_stack,
+                ante: self.cash_game_ante,
+                hand_count,
+            }),
+            rebuy: self.rebuy_hands.map(|period_hands| RebuyConfig {
+                period_hands,
+                add_on_chips: self.add_on_chips,
+            }),
+            bounty: self.bounty,
+            payouts: self.get_payouts().ok(),
+            level_schedule: self
+                .level_schedule
+                .as_ref()
+                .and_then(|path| LevelSchedule::from_file(path).ok()),
+            stop_conditions: StopConditions {
+                max_hands: self.max_hands,
+                max_duration: self.max_duration_seconds.map(Duration::from_secs_f64),
+                max_level: self.max_level,
+            },
+            button_rule: self.button_rule,
+            variant: self.game_variant,
+            run_it_times: self.run_it_times,
+            all_in_adjusted: self.all_in_adjusted,
+            all_in_samples: self.all_in_samples,
+            hand_history_file: self.hand_history_file.clone(),
+            seed: self.seed,
+            database_file: self.database_file.clone(),
+            ..TableConfig::default()
+        }
+    }
 }
 
-// Validation function to ensure the sum of call-bot and random-bot is less than 23
+// Validation function to ensure the sum of call-bot and random-bot is less than 23 for a single table,
+// multi-table tournaments instead need table settings that let the field shrink
 pub fn validate_bot_args(args: &BotArgs) -> Result<(), String> {
-    let sum = args.n_call_bots + args.n_random_bots + args.n_fail_bots;
-    if sum >= 23 {
-        Err("The sum of all bots must be less than 23".to_string())
+    if args.cash_game_hands.is_some() {
+        validate_cash_game_args(args)?;
+    }
+    if args.rebuy_hands.is_some() {
+        validate_rebuy_args(args)?;
+    }
+    if args.bounty.is_some() {
+        validate_bounty_args(args)?;
+    }
+    if !args.payouts.is_empty() {
+        validate_payout_args(args)?;
+    }
+    if let Some(path) = &args.level_schedule {
+        if args.tournament_format != TournamentFormat::SingleTable || args.cash_game_hands.is_some()
+        {
+            return Err(
+                "Level schedules can only be used in a single table tournament".to_string(),
+            );
+        }
+        LevelSchedule::from_file(path)?;
+    }
+    if args.button_rule != ButtonRule::NextAlive
+        && args.tournament_format != TournamentFormat::SingleTable
+    {
+        return Err("Button rules can only be used at a single table".to_string());
+    }
+    if args.game_variant != GameVariant::TexasHoldem
+        && args.tournament_format != TournamentFormat::SingleTable
+    {
+        return Err("Game variants can only be played at a single table".to_string());
+    }
+    if args.run_it_times == 0 {
+        return Err("The board must be run at least once".to_string());
+    }
+    if args.run_it_times > 1 && args.tournament_format != TournamentFormat::SingleTable {
+        return Err("The board can only be run more than once at a single table".to_string());
+    }
+    if args.all_in_adjusted && args.tournament_format != TournamentFormat::SingleTable {
+        return Err("All in adjusted results are only kept at a single table".to_string());
+    }
+    if args.all_in_samples == 0 {
+        return Err("All in equity needs at least one sampled board".to_string());
+    }
+    if args.stats_file.is_some() && args.tournament_format != TournamentFormat::SingleTable {
+        return Err("Player stats are only written for a single table".to_string());
+    }
+    if args.hand_history_file.is_some() && args.tournament_format != TournamentFormat::SingleTable {
+        return Err("Hand histories are only written for a single table".to_string());
+    }
+    if args.replay_file.is_some() || args.seed.is_some() {
+        validate_replay_args(args)?;
+    }
+    if args.checkpoint_file.is_some() && args.tournament_format != TournamentFormat::SingleTable {
+        return Err("Checkpoints can only be written for a single table".to_string());
+    }
+    if args.database_file.is_some() && args.tournament_format != TournamentFormat::SingleTable {
+        return Err("Databases are only written for a single table".to_string());
+    }
+    if args.resume && args.checkpoint_file.is_none() {
+        return Err("Resuming a game needs the checkpoint file to resume from".to_string());
+    }
+    validate_stop_condition_args(args)?;
+    match args.tournament_format {
+        TournamentFormat::SingleTable => {
+            let sum = args.n_call_bots + args.n_random_bots + args.n_fail_bots;
+            if sum >= 23 {
+                Err("The sum of all bots must be less than 23".to_string())
+            } else if sum > args.game_variant.get_max_players() {
+                Err(format!(
+                    "The sum of all bots must be at most {} for this game variant",
+                    args.game_variant.get_max_players()
+                ))
+            } else {
+                Ok(())
+            }
+        }
+        TournamentFormat::Bracket => {
+            if !(2..=Table::MAX_PLAYERS).contains(&args.max_table_size) {
+                Err(format!(
+                    "The max table size must be between 2 and {}",
+                    Table::MAX_PLAYERS
+                ))
+            } else if args.advance_per_table == 0
+                || args.advance_per_table * 2 >= args.max_table_size
+            {
+                Err("At least one, but less than half, of each table must advance".to_string())
+            } else {
+                Ok(())
+            }
+        }
+        TournamentFormat::MultiTable => {
+            if !(3..=Table::MAX_PLAYERS).contains(&args.max_table_size) {
+                Err(format!(
+                    "The max table size must be between 3 and {}",
+                    Table::MAX_PLAYERS
+                ))
+            } else {
+                Ok(())
+            }
+        }
+    }
+}
+
+// Cash games are only played at a single table, and need a stack that can cover the ante
+fn validate_cash_game_args(args: &BotArgs) -> Result<(), String> {
+    if args.tournament_format != TournamentFormat::SingleTable {
+        Err("Cash games can only be played at a single table".to_string())
+    } else if args.cash_game_hands.unwrap_or_default() < 1 {
+        Err("A cash game must play at least one hand".to_string())
+    } else if args.cash_game_ante < 1 || args.cash_game_stack <= args.cash_game_ante {
+        Err("The cash game ante must be at least 1 and less than the stack".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+// Rebuys are only offered in a single table freezeout
+fn validate_rebuy_args(args: &BotArgs) -> Result<(), String> {
+    if args.tournament_format != TournamentFormat::SingleTable || args.cash_game_hands.is_some() {
+        Err("Rebuys can only be used in a single table tournament".to_string())
+    } else if args.rebuy_hands.unwrap_or_default() < 1 {
+        Err("The rebuy period must be at least one hand".to_string())
+    } else if args.add_on_chips < 0 {
+        Err("The add-on can't be negative".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+// Bounties are only collected in a single table tournament
+fn validate_bounty_args(args: &BotArgs) -> Result<(), String> {
+    if args.tournament_format != TournamentFormat::SingleTable || args.cash_game_hands.is_some() {
+        Err("Bounties can only be used in a single table tournament".to_string())
+    } else if args.bounty.unwrap_or_default() < 1 {
+        Err("The bounty must be at least 1".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+// Prizes are only paid by finishing place in a single table tournament
+fn validate_payout_args(args: &BotArgs) -> Result<(), String> {
+    if args.tournament_format != TournamentFormat::SingleTable || args.cash_game_hands.is_some() {
+        Err("Payouts can only be used in a single table tournament".to_string())
+    } else {
+        args.get_payouts().map(|_| ())
+    }
+}
+
+// Replays are only recorded at a single table, and a game that depends on the clock can't be played back
+fn validate_replay_args(args: &BotArgs) -> Result<(), String> {
+    let has_timed_level = args
+        .level_schedule
+        .as_ref()
+        .and_then(|path| LevelSchedule::from_file(path).ok())
+        .is_some_and(|schedule| schedule.levels.iter().any(|level| level.seconds.is_some()));
+    if args.tournament_format != TournamentFormat::SingleTable {
+        Err("Replays and seeds can only be used at a single table".to_string())
+    } else if args.replay_file.is_some() && (args.max_duration_seconds.is_some() || has_timed_level)
+    {
+        Err("Games with a time limit or timed levels can't be replayed".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+// Limits are only checked by a single table, and each one must allow at least some play
+fn validate_stop_condition_args(args: &BotArgs) -> Result<(), String> {
+    let has_limit =
+        args.max_hands.is_some() || args.max_duration_seconds.is_some() || args.max_level.is_some();
+    if has_limit && args.tournament_format != TournamentFormat::SingleTable {
+        Err("Stop conditions can only be used at a single table".to_string())
+    } else if args.max_hands.is_some_and(|x| x < 1) {
+        Err("The hand limit must be at least one hand".to_string())
+    } else if args
+        .max_duration_seconds
+        .is_some_and(|x| !x.is_finite() || x <= 0.0)
+    {
+        Err("The time limit must be a positive number of seconds".to_string())
+    } else if args.max_level.is_some() && args.level_schedule.is_none() {
+        Err("A level cap needs a level schedule".to_string())
+    } else if args.max_level == Some(0) {
+        Err("The level cap must be at least level 1".to_string())
     } else {
         Ok(())
     }
@@ -42,7 +413,10 @@ pub fn validate_bot_args(args: &BotArgs) -> Result<(), String> {
 mod tests {
     use clap::Parser;
 
-    use crate::args::{validate_bot_args, BotArgs};
+    use std::time::Duration;
+
+    use crate::args::{validate_bot_args, BotArgs, Command, TournamentFormat};
+    use crate::table::{ButtonRule, GameVariant, StopConditions};
 
     #[test]
     fn test_defaults() {
@@ -88,4 +462,504 @@ mod tests {
         // The sum should be valid and less than 23
         assert!(validate_bot_args(&args).is_err());
     }
+
+    #[test]
+    fn test_bracket_args() {
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--tournament-format",
+            "bracket",
+            "--n-call-bots",
+            "40",
+        ]);
+        assert_eq!(args.tournament_format, TournamentFormat::Bracket);
+        assert_eq!(args.max_table_size, 23);
+        assert_eq!(args.advance_per_table, 3);
+        // Brackets aren't limited to one table's worth of bots
+        assert!(validate_bot_args(&args).is_ok());
+
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--tournament-format",
+            "bracket",
+            "--max-table-size",
+            "6",
+            "--advance-per-table",
+            "3",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--tournament-format",
+            "bracket",
+            "--max-table-size",
+            "24",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_multi_table_args() {
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--tournament-format",
+            "multi-table",
+            "--n-call-bots",
+            "40",
+            "--max-table-size",
+            "9",
+        ]);
+        assert_eq!(args.tournament_format, TournamentFormat::MultiTable);
+        assert!(validate_bot_args(&args).is_ok());
+
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--tournament-format",
+            "multi-table",
+            "--max-table-size",
+            "2",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_cash_game_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert!(args.cash_game_hands.is_none());
+        assert!(args.get_table_config().cash_game.is_none());
+
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--cash-game-hands",
+            "200",
+            "--cash-game-stack",
+            "100",
+            "--cash-game-ante",
+            "2",
+        ]);
+        assert!(validate_bot_args(&args).is_ok());
+        let cash_game = args.get_table_config().cash_game.unwrap();
+        assert_eq!(cash_game.hand_count, 200);
+        assert_eq!(cash_game.stack_size, 100);
+        assert_eq!(cash_game.ante, 2);
+
+        let args = BotArgs::parse_from(vec!["test", "--cash-game-hands", "0"]);
+        assert!(validate_bot_args(&args).is_err());
+
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--cash-game-hands",
+            "10",
+            "--cash-game-stack",
+            "5",
+            "--cash-game-ante",
+            "5",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--cash-game-hands",
+            "10",
+            "--tournament-format",
+            "bracket",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_rebuy_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert!(args.get_table_config().rebuy.is_none());
+
+        let args =
+            BotArgs::parse_from(vec!["test", "--rebuy-hands", "50", "--add-on-chips", "300"]);
+        assert!(validate_bot_args(&args).is_ok());
+        let rebuy = args.get_table_config().rebuy.unwrap();
+        assert_eq!(rebuy.period_hands, 50);
+        assert_eq!(rebuy.add_on_chips, 300);
+
+        let args = BotArgs::parse_from(vec!["test", "--rebuy-hands", "0"]);
+        assert!(validate_bot_args(&args).is_err());
+
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--rebuy-hands",
+            "10",
+            "--cash-game-hands",
+            "10",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--rebuy-hands",
+            "10",
+            "--tournament-format",
+            "multi-table",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_bounty_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert!(args.get_table_config().bounty.is_none());
+
+        let args = BotArgs::parse_from(vec!["test", "--bounty", "25", "--rebuy-hands", "10"]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert_eq!(args.get_table_config().bounty, Some(25));
+
+        let args = BotArgs::parse_from(vec!["test", "--bounty", "0"]);
+        assert!(validate_bot_args(&args).is_err());
+
+        let args = BotArgs::parse_from(vec!["test", "--bounty", "5", "--cash-game-hands", "10"]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_payout_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert!(args.get_table_config().payouts.is_none());
+        assert!(args.results_file.is_none());
+
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--payouts",
+            "50,30,20",
+            "--prize-pool",
+            "1000",
+            "--results-file",
+            "results.txt",
+        ]);
+        assert!(validate_bot_args(&args).is_ok());
+        let payouts = args.get_table_config().payouts.unwrap();
+        assert_eq!(payouts.get_prizes(), vec![500.0, 300.0, 200.0]);
+        assert_eq!(args.results_file.unwrap().to_str(), Some("results.txt"));
+
+        let args = BotArgs::parse_from(vec!["test", "--payouts", "60,50"]);
+        assert!(validate_bot_args(&args).is_err());
+
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--payouts",
+            "100",
+            "--tournament-format",
+            "bracket",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_level_schedule_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert!(args.get_table_config().level_schedule.is_none());
+
+        let schedule = concat!(env!("CARGO_MANIFEST_DIR"), "/schedules/turbo.json");
+        let args = BotArgs::parse_from(vec!["test", "--level-schedule", schedule]);
+        assert!(validate_bot_args(&args).is_ok());
+        let level_schedule = args.get_table_config().level_schedule.unwrap();
+        assert_eq!(level_schedule.levels[0].big_blind, 10);
+
+        let args = BotArgs::parse_from(vec!["test", "--level-schedule", "does_not_exist.json"]);
+        assert!(validate_bot_args(&args).is_err());
+
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--level-schedule",
+            schedule,
+            "--cash-game-hands",
+            "10",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_stop_condition_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert_eq!(
+            args.get_table_config().stop_conditions,
+            StopConditions::default()
+        );
+
+        let schedule = concat!(env!("CARGO_MANIFEST_DIR"), "/schedules/turbo.json");
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--max-hands",
+            "100",
+            "--max-duration-seconds",
+            "1.5",
+            "--level-schedule",
+            schedule,
+            "--max-level",
+            "3",
+        ]);
+        assert!(validate_bot_args(&args).is_ok());
+        let stop_conditions = args.get_table_config().stop_conditions;
+        assert_eq!(stop_conditions.max_hands, Some(100));
+        assert_eq!(
+            stop_conditions.max_duration,
+            Some(Duration::from_millis(1500))
+        );
+        assert_eq!(stop_conditions.max_level, Some(3));
+
+        let args = BotArgs::parse_from(vec!["test", "--max-hands", "0"]);
+        assert!(validate_bot_args(&args).is_err());
+        let args = BotArgs::parse_from(vec!["test", "--max-duration-seconds=-1"]);
+        assert!(validate_bot_args(&args).is_err());
+        let args = BotArgs::parse_from(vec!["test", "--max-level", "2"]);
+        assert!(validate_bot_args(&args).is_err());
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--max-hands",
+            "10",
+            "--tournament-format",
+            "bracket",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_button_rule_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert_eq!(args.get_table_config().button_rule, ButtonRule::NextAlive);
+        let args = BotArgs::parse_from(vec!["test", "--button-rule", "dead-button"]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert_eq!(args.get_table_config().button_rule, ButtonRule::DeadButton);
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--button-rule",
+            "moving-button",
+            "--tournament-format",
+            "multi-table",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_run_it_times_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert_eq!(args.get_table_config().run_it_times, 1);
+        let args = BotArgs::parse_from(vec!["test", "--run-it-times", "2"]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert_eq!(args.get_table_config().run_it_times, 2);
+        let args = BotArgs::parse_from(vec!["test", "--run-it-times", "0"]);
+        assert!(validate_bot_args(&args).is_err());
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--run-it-times",
+            "3",
+            "--tournament-format",
+            "multi-table",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_all_in_adjusted_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert!(!args.get_table_config().all_in_adjusted);
+        let args = BotArgs::parse_from(vec!["test", "--all-in-adjusted"]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert!(args.get_table_config().all_in_adjusted);
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--all-in-adjusted",
+            "--tournament-format",
+            "bracket",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+        let args =
+            BotArgs::parse_from(vec!["test", "--all-in-adjusted", "--all-in-samples", "500"]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert_eq!(args.get_table_config().all_in_samples, 500);
+        let args = BotArgs::parse_from(vec!["test", "--all-in-adjusted", "--all-in-samples", "0"]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_stats_file_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert!(args.stats_file.is_none());
+        let args = BotArgs::parse_from(vec!["test", "--stats-file", "stats.json"]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert_eq!(args.stats_file.unwrap().to_str(), Some("stats.json"));
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--stats-file",
+            "stats.json",
+            "--tournament-format",
+            "multi-table",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_hand_history_file_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert!(args.get_table_config().hand_history_file.is_none());
+        let args = BotArgs::parse_from(vec!["test", "--hand-history-file", "hands.txt"]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert_eq!(
+            args.get_table_config().hand_history_file.unwrap().to_str(),
+            Some("hands.txt")
+        );
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--hand-history-file",
+            "hands.txt",
+            "--tournament-format",
+            "bracket",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_replay_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert!(args.command.is_none());
+        assert!(args.replay_file.is_none());
+        assert!(args.get_table_config().seed.is_none());
+        let args = BotArgs::parse_from(vec!["test", "--replay-file", "replay.json", "--seed", "7"]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert_eq!(
+            args.replay_file.as_ref().unwrap().to_str(),
+            Some("replay.json")
+        );
+        assert_eq!(args.get_table_config().seed, Some(7));
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--seed",
+            "7",
+            "--tournament-format",
+            "multi-table",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--replay-file",
+            "replay.json",
+            "--max-duration-seconds",
+            "10",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+        let args =
+            BotArgs::parse_from(vec!["test", "replay", "replay.json", "--stop-at-hand", "3"]);
+        assert_eq!(
+            args.command,
+            Some(Command::Replay {
+                file: "replay.json".into(),
+                stop_at_hand: Some(3)
+            })
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert!(args.checkpoint_file.is_none());
+        assert!(!args.resume);
+        let args = BotArgs::parse_from(vec!["test", "--checkpoint-file", "game.json", "--resume"]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert_eq!(args.checkpoint_file.unwrap().to_str(), Some("game.json"));
+        let args = BotArgs::parse_from(vec!["test", "--resume"]);
+        assert!(validate_bot_args(&args).is_err());
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--checkpoint-file",
+            "game.json",
+            "--tournament-format",
+            "bracket",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_database_file_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert!(args.get_table_config().database_file.is_none());
+        let args = BotArgs::parse_from(vec!["test", "--database-file", "games.db"]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert_eq!(
+            args.get_table_config().database_file.unwrap().to_str(),
+            Some("games.db")
+        );
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--database-file",
+            "games.db",
+            "--tournament-format",
+            "multi-table",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
+
+    #[test]
+    fn test_game_variant_args() {
+        let args = BotArgs::parse_from(vec!["test"]);
+        assert_eq!(args.get_table_config().variant, GameVariant::TexasHoldem);
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--game-variant",
+            "pot-limit-omaha",
+            "--n-call-bots",
+            "11",
+        ]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert_eq!(args.get_table_config().variant, GameVariant::PotLimitOmaha);
+        let args = BotArgs::parse_from(vec!["test", "--game-variant", "omaha-hi-lo"]);
+        assert_eq!(args.get_table_config().variant, GameVariant::OmahaHiLo);
+        let args = BotArgs::parse_from(vec!["test", "--game-variant", "short-deck-holdem"]);
+        assert_eq!(
+            args.get_table_config().variant,
+            GameVariant::ShortDeckHoldem
+        );
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--game-variant",
+            "kuhn-poker",
+            "--n-call-bots",
+            "2",
+        ]);
+        assert!(validate_bot_args(&args).is_ok());
+        assert_eq!(args.get_table_config().variant, GameVariant::KuhnPoker);
+        let args = BotArgs::parse_from(vec!["test", "--game-variant", "leduc-holdem"]);
+        assert_eq!(args.get_table_config().variant, GameVariant::LeducHoldem);
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--game-variant",
+            "seven-card-stud",
+            "--n-call-bots",
+            "8",
+        ]);
+        assert_eq!(args.get_table_config().variant, GameVariant::SevenCardStud);
+        assert!(validate_bot_args(&args).is_err());
+        // A three card deck can only deal to three players
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--game-variant",
+            "kuhn-poker",
+            "--n-call-bots",
+            "4",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+        // Four cards each for twelve players and the board is more than one deck
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--game-variant",
+            "pot-limit-omaha",
+            "--n-call-bots",
+            "12",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+        let args = BotArgs::parse_from(vec![
+            "test",
+            "--game-variant",
+            "pot-limit-omaha",
+            "--tournament-format",
+            "bracket",
+        ]);
+        assert!(validate_bot_args(&args).is_err());
+    }
 }
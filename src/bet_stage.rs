@@ -1,21 +1,36 @@
-use crate::bet_stage::BetStage::{Flop, PreFlop, River, Turn};
+use std::fmt;
+use std::fmt::Formatter;
 
-#[derive(Eq, PartialEq, Debug, Copy, Clone)]
+use serde::{Deserialize, Serialize};
+
+/// A round of betting, which rounds a hand has and in what order is decided by the game variant
+#[derive(Deserialize, Serialize, Eq, PartialEq, Debug, Copy, Clone)]
 pub enum BetStage {
     PreFlop,
     Flop,
     Turn,
     River,
+    ThirdStreet,
+    FourthStreet,
+    FifthStreet,
+    SixthStreet,
+    SeventhStreet,
 }
 
-impl BetStage {
-    pub fn next_stage(&mut self) {
-        match self {
-            PreFlop => *self = Flop,
-            Flop => *self = Turn,
-            Turn => *self = River,
-            River => *self = PreFlop,
-        }
+impl fmt::Display for BetStage {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            BetStage::PreFlop => "pre flop",
+            BetStage::Flop => "flop",
+            BetStage::Turn => "turn",
+            BetStage::River => "river",
+            BetStage::ThirdStreet => "third street",
+            BetStage::FourthStreet => "fourth street",
+            BetStage::FifthStreet => "fifth street",
+            BetStage::SixthStreet => "sixth street",
+            BetStage::SeventhStreet => "seventh street",
+        };
+        write!(f, "{name}")
     }
 }
 
@@ -24,15 +39,9 @@ mod tests {
     use crate::bet_stage::BetStage;
 
     #[test]
-    fn check_next_stage() {
-        let mut bet_stage = BetStage::PreFlop;
-        bet_stage.next_stage();
-        assert_eq!(bet_stage, BetStage::Flop);
-        bet_stage.next_stage();
-        assert_eq!(bet_stage, BetStage::Turn);
-        bet_stage.next_stage();
-        assert_eq!(bet_stage, BetStage::River);
-        bet_stage.next_stage();
-        assert_eq!(bet_stage, BetStage::PreFlop);
+    fn check_stage_names() {
+        assert_eq!(BetStage::PreFlop.to_string(), "pre flop");
+        assert_eq!(BetStage::River.to_string(), "river");
+        assert_eq!(BetStage::FourthStreet.to_string(), "fourth street");
     }
 }
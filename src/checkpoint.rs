@@ -0,0 +1,266 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::actions::HandAction;
+use crate::journal::JournalEntry;
+use crate::table::{Table, TableAction};
+
+/// Everything a single table game needs to carry on after a restart, written at the start of every hand.
+/// The actions themselves are only kept in the journal, so a checkpoint stays the same size as the game goes on.
+/// The table is borrowed while writing and owned once read back.
+#[derive(Deserialize, Serialize)]
+pub struct Checkpoint<T> {
+    /// The identity of the bot sitting in each seat
+    pub identities: Vec<String>,
+    /// How many actions had been applied to the table when it was saved
+    pub action_count: usize,
+    pub table: T,
+}
+
+impl Checkpoint<&Table> {
+    /// Writes the checkpoint next to the file first and then moves it over, so a crash while writing
+    /// never leaves a broken checkpoint behind
+    pub fn write_to_file(&self, path: &Path) -> Result<(), String> {
+        let contents = serde_json::to_string(self)
+            .map_err(|error| format!("Couldn't serialize the checkpoint: {error}"))?;
+        let temp_path = path.with_extension("tmp");
+        std::fs::write(&temp_path, contents)
+            .and_then(|_| std::fs::rename(&temp_path, path))
+            .map_err(|error| format!("Couldn't write the checkpoint {}: {error}", path.display()))
+    }
+}
+
+impl Checkpoint<Table> {
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| format!("Couldn't read the checkpoint {}: {error}", path.display()))?;
+        serde_json::from_str(&contents)
+            .map_err(|error| format!("Couldn't parse the checkpoint: {error}"))
+    }
+
+    /// Applies the actions journaled after the checkpoint was written and returns every action of the game.
+    /// The entries from before the checkpoint are only read back, so a replay of the whole game can be written
+    pub fn apply_journal(&mut self, entries: &[JournalEntry]) -> Result<Vec<HandAction>, String> {
+        let mut actions = vec![];
+        for entry in entries {
+            if entry.index != actions.len() {
+                return Err(format!(
+                    "The journal should have action {} next, but it has action {}",
+                    actions.len(),
+                    entry.index
+                ));
+            }
+            let TableAction::TakePlayerAction(seat, action) = entry.action else {
+                return Err(format!(
+                    "Only player actions are journaled, not {}",
+                    entry.action
+                ));
+            };
+            if entry.index >= self.action_count {
+                self.apply_entry(entry.index, seat, action)?;
+            }
+            actions.push(action);
+        }
+        if actions.len() < self.action_count {
+            return Err(format!(
+                "The journal ends at action {}, but the checkpoint was saved after action {}",
+                actions.len(),
+                self.action_count
+            ));
+        }
+        self.action_count = actions.len();
+        Ok(actions)
+    }
+
+    fn apply_entry(&mut self, index: usize, seat: i8, action: HandAction) -> Result<(), String> {
+        if seat as usize != self.table.get_current_player_index() || self.table.is_game_over() {
+            return Err(format!(
+                "Action {index} was taken by seat {seat}, but it isn't their turn"
+            ));
+        }
+        // A failed action voided its hand when the game was played, so it does here too
+        self.table
+            .take_action(action)
+            .or_else(|_| self.table.void_hand())
+            .map_err(|error| format!("Couldn't void the hand at action {index}: {error}"))
+    }
+}
+
+/// Finds the connection for each seat by matching the identity of the bot that sat there to the identities that
+/// connected, bots with the same identity are seated in the order they connected. A seat is None when its bot
+/// didn't reconnect, and a bot that didn't sit at the table isn't seated.
+pub fn match_seats(seat_identities: &[String], connected: &[String]) -> Vec<Option<usize>> {
+    let mut is_seated = vec![false; connected.len()];
+    seat_identities
+        .iter()
+        .map(|identity| {
+            let index = (0..connected.len())
+                .find(|&index| !is_seated[index] && connected[index] == *identity)?;
+            is_seated[index] = true;
+            Some(index)
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actions::HandAction;
+    use crate::checkpoint::{match_seats, Checkpoint};
+    use crate::journal::JournalEntry;
+    use crate::table::{Table, TableAction, TableConfig};
+
+    fn to_strings(identities: &[&str]) -> Vec<String> {
+        identities.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_match_seats() {
+        let seats = to_strings(&["alice", "bob", "127.0.0.1", "127.0.0.1"]);
+        // Everyone reconnected in a different order
+        let connected = to_strings(&["127.0.0.1", "bob", "127.0.0.1", "alice"]);
+        assert_eq!(
+            match_seats(&seats, &connected),
+            vec![Some(3), Some(1), Some(0), Some(2)]
+        );
+        // Bob didn't come back and a stranger can't take their seat
+        let connected = to_strings(&["carol", "alice", "127.0.0.1"]);
+        assert_eq!(
+            match_seats(&seats, &connected),
+            vec![Some(1), None, Some(2), None]
+        );
+    }
+
+    #[test]
+    fn test_checkpoint_file() {
+        let mut table = Table::with_config(3, TableConfig::default()).unwrap();
+        let actions = vec![HandAction::Call, HandAction::Raise(10), HandAction::Fold];
+        for action in &actions {
+            table.take_action(*action).unwrap();
+        }
+        let path = std::env::temp_dir().join("bot_arena_test_checkpoint.json");
+        let checkpoint = Checkpoint {
+            identities: vec!["a".to_string(), "b".to_string(), "c".to_string()],
+            action_count: actions.len(),
+            table: &table,
+        };
+        checkpoint.write_to_file(&path).unwrap();
+        let read_checkpoint = Checkpoint::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read_checkpoint.identities, checkpoint.identities);
+        assert_eq!(read_checkpoint.action_count, 3);
+        assert_eq!(
+            read_checkpoint.table.get_state_string_for_current_player(),
+            table.get_state_string_for_current_player()
+        );
+        assert!(Checkpoint::from_file(&path).is_err());
+    }
+
+    #[test]
+    fn test_apply_journal() {
+        let config = TableConfig {
+            seed: Some(3),
+            ..TableConfig::default()
+        };
+        let mut table = Table::with_config(3, config.clone()).unwrap();
+        let mut checkpoint = Checkpoint {
+            identities: vec![],
+            action_count: 1,
+            table: Table::with_config(3, config).unwrap(),
+        };
+        checkpoint.table.take_action(HandAction::Call).unwrap();
+        // The first entry was taken before the checkpoint, the rest were journaled after it
+        let actions = [HandAction::Call, HandAction::Raise(10), HandAction::Call];
+        let mut entries = vec![];
+        for action in actions {
+            entries.push(JournalEntry {
+                index: entries.len(),
+                action: TableAction::TakePlayerAction(
+                    table.get_current_player_index() as i8,
+                    action,
+                ),
+            });
+            table.take_action(action).unwrap();
+        }
+        assert_eq!(checkpoint.apply_journal(&entries).unwrap(), actions);
+        assert_eq!(checkpoint.action_count, 3);
+        assert_eq!(
+            checkpoint.table.get_state_string_for_current_player(),
+            table.get_state_string_for_current_player()
+        );
+        // An entry out of turn or after a gap means the journal doesn't follow the checkpoint
+        let wrong_seat = (table.get_current_player_index() + 1) % 3;
+        entries.push(JournalEntry {
+            index: 3,
+            action: TableAction::TakePlayerAction(wrong_seat as i8, HandAction::Fold),
+        });
+        assert!(checkpoint.apply_journal(&entries).is_err());
+        entries[3].index = 5;
+        assert!(checkpoint.apply_journal(&entries).is_err());
+        // The journal has to reach the checkpoint
+        assert!(checkpoint.apply_journal(&entries[..1]).is_err());
+    }
+
+    #[test]
+    fn test_apply_journal_with_database() {
+        let path = std::env::temp_dir().join("bot_arena_test_checkpoint.db");
+        let _ = std::fs::remove_file(&path);
+        let config = TableConfig {
+            seed: Some(5),
+            database_file: Some(path.clone()),
+            ..TableConfig::default()
+        };
+        let mut table = Table::with_config(3, config).unwrap();
+        // The checkpoint is written once the hand is dealt
+        let saved = serde_json::to_string(&Checkpoint {
+            identities: vec![],
+            action_count: 0,
+            table: &table,
+        })
+        .unwrap();
+        let mut entries = vec![];
+        for action in [HandAction::Call, HandAction::Raise(10)] {
+            entries.push(JournalEntry {
+                index: entries.len(),
+                action: TableAction::TakePlayerAction(
+                    table.get_current_player_index() as i8,
+                    action,
+                ),
+            });
+            table.take_action(action).unwrap();
+        }
+        // The crashed run wrote the end of the hand and the next deal but never journaled them
+        table.take_action(HandAction::Fold).unwrap();
+        table.take_action(HandAction::Fold).unwrap();
+        drop(table);
+        let mut checkpoint: Checkpoint<Table> = serde_json::from_str(&saved).unwrap();
+        checkpoint.apply_journal(&entries).unwrap();
+        checkpoint.table.take_action(HandAction::Call).unwrap();
+        drop(checkpoint);
+        let connection = rusqlite::Connection::open(&path).unwrap();
+        let hands: i64 = connection
+            .query_row("SELECT COUNT(*) FROM hands", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(hands, 1);
+        let mut statement = connection
+            .prepare("SELECT sequence, action FROM actions ORDER BY sequence")
+            .unwrap();
+        let actions: Vec<(i64, String)> = statement
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))
+            .unwrap()
+            .map(|row| row.unwrap())
+            .collect();
+        let expected = ["deal", "call", "raise", "call"];
+        assert_eq!(
+            actions,
+            expected
+                .iter()
+                .enumerate()
+                .map(|(sequence, action)| (sequence as i64, action.to_string()))
+                .collect::<Vec<_>>()
+        );
+        drop(statement);
+        drop(connection);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -1,97 +1,273 @@
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 use std::time::Duration;
 
 use futures_util::{SinkExt, StreamExt};
+use tokio::net::TcpStream;
 use tokio::time::timeout;
 use tokio_tungstenite::tungstenite::Message;
+use tokio_tungstenite::WebSocketStream;
 
 use crate::actions::HandAction;
+use crate::checkpoint::{match_seats, Checkpoint};
+use crate::journal::{Journal, JournalEntry};
 use crate::server::Server;
-use crate::table::Table;
+use crate::table::{Table, TableAction, TableConfig};
 
 pub struct Engine {
     pub table: Table,
     pub server: Server,
     pub read_timeout: Duration,
+    /// Every action applied to the table so far, used to write a replay of the game
+    pub actions: Vec<HandAction>,
+    /// The identity of the bot sitting in each seat
+    identities: Vec<String>,
+    /// The connection playing each seat, None when the bot in that seat didn't reconnect after a restart
+    seat_connections: Vec<Option<usize>>,
+    /// Checkpoints the game to this file at the start of every hand if set, and journals every action next to it
+    pub checkpoint_file: Option<PathBuf>,
+    /// Every action applied this game, None until the game starts or without a checkpoint file
+    journal: Option<Journal>,
 }
 
 impl Engine {
-    pub async fn new(server: Server, read_timeout: Duration) -> Result<Engine, String> {
+    pub async fn new(
+        server: Server,
+        read_timeout: Duration,
+        config: TableConfig,
+    ) -> Result<Engine, String> {
         if server.connections.is_empty() {
             return Err("No connections established.".to_string());
         }
+        if server.connections.len() > config.variant.get_max_players() {
+            return Err(format!(
+                "Too many connections, {:?} can deal into at most {} players.",
+                config.variant,
+                config.variant.get_max_players()
+            ));
+        }
 
         let engine = Engine {
-            table: Table::new(server.connections.len()),
+            table: Table::with_config(server.connections.len(), config)
+                .map_err(|error| error.to_string())?,
+            identities: server.identities.clone(),
+            seat_connections: (0..server.connections.len()).map(Some).collect(),
             server,
             read_timeout,
+            actions: vec![],
+            checkpoint_file: None,
+            journal: None,
         };
 
         Ok(engine)
     }
 
-    pub async fn play_game(&mut self) {
-        while !self.table.is_game_over() {
-            let input = self.get_client_input().await;
-            self.table.take_action(input);
+    /// Carries on a game from a checkpoint with the journal applied, each bot is seated where it was sitting
+    /// before the restart. Seats whose bot didn't reconnect fold every hand.
+    pub async fn resume(
+        server: Server,
+        read_timeout: Duration,
+        checkpoint: Checkpoint<Table>,
+        actions: Vec<HandAction>,
+    ) -> Result<Engine, String> {
+        let seat_connections = match_seats(&checkpoint.identities, &server.identities);
+        if seat_connections.iter().all(Option::is_none) {
+            return Err("None of the bots from the checkpoint reconnected.".to_string());
         }
-        let results = format!("Game is over:\n{}", self.table.get_results());
-        println!("{}", results);
-        for (index, connection) in &mut self.server.connections.iter_mut().enumerate() {
-            let send_result = connection.send(Message::Text(results.clone())).await;
-            if send_result.is_err() {
-                warn!("Couldn't send the results to subscriber {index}")
+        for (seat, identity) in checkpoint.identities.iter().enumerate() {
+            if seat_connections[seat].is_none() {
+                warn!(
+                    "The bot {identity} in seat {seat} didn't reconnect, it will fold every hand."
+                );
             }
         }
+        for (index, identity) in server.identities.iter().enumerate() {
+            if !seat_connections.contains(&Some(index)) {
+                warn!(
+                    "The bot {identity} wasn't seated at the table, it will only get the results."
+                );
+            }
+        }
+        let engine = Engine {
+            table: checkpoint.table,
+            server,
+            read_timeout,
+            actions,
+            identities: checkpoint.identities,
+            seat_connections,
+            checkpoint_file: None,
+            journal: None,
+        };
+
+        Ok(engine)
+    }
+
+    /// Plays until the game is over or an admin stops it by pressing ctrl-c twice, then returns the results.
+    /// A single ctrl-c only warns, so pressing it by accident doesn't end the game
+    pub async fn play_game(&mut self) -> String {
+        let stop_requested = Arc::new(AtomicBool::new(false));
+        let stop_listener = {
+            let stop_requested = stop_requested.clone();
+            tokio::spawn(async move {
+                if tokio::signal::ctrl_c().await.is_err() {
+                    return;
+                }
+                warn!("Press ctrl-c again to end the game early and send the results.");
+                if tokio::signal::ctrl_c().await.is_ok() {
+                    warn!("Stop requested, the game will end after the current action.");
+                    stop_requested.store(true, Ordering::SeqCst);
+                }
+            })
+        };
+        self.open_journal();
+        self.write_checkpoint();
+        while !self.table.is_game_over() && !stop_requested.load(Ordering::SeqCst) {
+            let input = self.get_client_input().await;
+            let seat = self.table.get_current_player_index();
+            let hand_number = self.table.get_hand_number();
+            if let Err(error) = self.table.take_action(input) {
+                error!("Seat {seat} couldn't take the action {input}, voiding hand {hand_number}: {error}");
+                if let Err(error) = self.table.void_hand() {
+                    error!("Couldn't void hand {hand_number}, the game can't carry on: {error}");
+                    break;
+                }
+            }
+            self.actions.push(input);
+            // The action has to be saved before the next state goes out, a new hand is also checkpointed
+            self.write_journal_entry(TableAction::TakePlayerAction(seat as i8, input));
+            if self.table.get_hand_number() != hand_number {
+                self.write_checkpoint();
+            }
+        }
+        stop_listener.abort();
+        let results = if self.table.is_game_over() {
+            format!("Game is over:\n{}", self.table.get_results())
+        } else {
+            format!("Game was stopped early:\n{}", self.table.get_results())
+        };
+        println!("{}", results);
+        send_results(&mut self.server.connections, &results).await;
+        results
     }
 
     pub async fn get_client_input(&mut self) -> HandAction {
         let current_index = self.table.get_current_player_index();
-        let connection = match self.server.connections.get_mut(current_index) {
+        let connection = match self
+            .seat_connections
+            .get(current_index)
+            .copied()
+            .flatten()
+            .and_then(|index| self.server.connections.get_mut(index))
+        {
             Some(conn) => conn,
             None => {
                 warn!("No connection found for index {current_index}. Will return fold.");
                 return HandAction::Fold;
             }
         };
-
         let state = self.table.get_state_string_for_current_player();
-        let result = connection.send(Message::Text(state)).await;
-        match result {
-            Ok(_) => {
-                debug!("Ok send to player {current_index}");
-            }
-            Err(error) => {
-                warn!("Couldn't write to user at index {current_index} because {error}, will take a fold action.");
-                return HandAction::Fold;
-            }
+        get_connection_input(connection, state, self.read_timeout, current_index).await
+    }
+
+    /// Starts the journal next to the checkpoint file if there is one, a resumed game carries on its journal.
+    /// Failures are only logged so the game goes on
+    fn open_journal(&mut self) {
+        let Some(path) = &self.checkpoint_file else {
+            return;
+        };
+        let path = Journal::get_path(path);
+        let journal = if self.actions.is_empty() {
+            Journal::create(&path)
+        } else {
+            Journal::open(&path)
+        };
+        self.journal = journal.map_err(|error| warn!("{error}")).ok();
+    }
+
+    /// Writes the game to the checkpoint file if there is one, failures are only logged so the game goes on
+    fn write_checkpoint(&mut self) {
+        let Some(path) = &self.checkpoint_file else {
+            return;
+        };
+        let checkpoint = Checkpoint {
+            identities: self.identities.clone(),
+            action_count: self.actions.len(),
+            table: &self.table,
+        };
+        if let Err(error) = checkpoint.write_to_file(path) {
+            warn!("{error}");
+        }
+    }
+
+    /// Appends the last applied action to the journal if there is one, failures are only logged so the game goes on
+    fn write_journal_entry(&mut self, action: TableAction) {
+        let Some(journal) = &mut self.journal else {
+            return;
+        };
+        let entry = JournalEntry {
+            index: self.actions.len() - 1,
+            action,
+        };
+        if let Err(error) = journal.append(&entry) {
+            warn!("{error}");
         }
+    }
+}
+
+/// Sends the results to every connection, failures are only logged since the game is over anyway
+pub async fn send_results(connections: &mut [WebSocketStream<TcpStream>], results: &str) {
+    for (index, connection) in connections.iter_mut().enumerate() {
+        let send_result = connection.send(Message::Text(results.to_string())).await;
+        if send_result.is_err() {
+            warn!("Couldn't send the results to subscriber {index}")
+        }
+    }
+}
 
-        let read_future = connection.next();
-        let timeout = timeout(self.read_timeout, read_future).await;
-
-        match timeout {
-            Ok(result) => match result {
-                None => HandAction::Fold,
-                Some(result) => match result {
-                    Ok(message) => {
-                        let message_string = message
-                            .into_text()
-                            .unwrap_or("Couldn't parse string".to_string());
-                        HandAction::parse_hand_action(message_string.as_str()).unwrap_or_else(|_| {
-                            warn!("Invalid hand action from client at {current_index}. Will return fold. Given string \"{message_string}\"");
-                            HandAction::Fold
-                        })
-                    }
-                    Err(error) => {
-                        warn!("Couldn't parse the message due to error: {error}");
+/// Sends the state to the connection and waits for its action, any failure is treated as a fold
+pub async fn get_connection_input(
+    connection: &mut WebSocketStream<TcpStream>,
+    state: String,
+    read_timeout: Duration,
+    current_index: usize,
+) -> HandAction {
+    let result = connection.send(Message::Text(state)).await;
+    match result {
+        Ok(_) => {
+            debug!("Ok send to player {current_index}");
+        }
+        Err(error) => {
+            warn!("Couldn't write to user at index {current_index} because {error}, will take a fold action.");
+            return HandAction::Fold;
+        }
+    }
+
+    let read_future = connection.next();
+    let timeout = timeout(read_timeout, read_future).await;
+
+    match timeout {
+        Ok(result) => match result {
+            None => HandAction::Fold,
+            Some(result) => match result {
+                Ok(message) => {
+                    let message_string = message
+                        .into_text()
+                        .unwrap_or("Couldn't parse string".to_string());
+                    HandAction::parse_hand_action(message_string.as_str()).unwrap_or_else(|_| {
+                        warn!("Invalid hand action from client at {current_index}. Will return fold. Given string \"{message_string}\"");
                         HandAction::Fold
-                    }
-                },
+                    })
+                }
+                Err(error) => {
+                    warn!("Couldn't parse the message due to error: {error}");
+                    HandAction::Fold
+                }
             },
-            Err(error) => {
-                warn!("Had a timeout: {error}");
-                HandAction::Fold
-            }
+        },
+        Err(error) => {
+            warn!("Had a timeout: {error}");
+            HandAction::Fold
         }
     }
 }
@@ -103,6 +279,7 @@ mod tests {
     use url::Url;
 
     use crate::server::Server;
+    use crate::table::TableConfig;
 
     use super::Engine;
 
@@ -117,6 +294,7 @@ mod tests {
         let result = Engine::new(
             Server::from_tcp_listener(tcp_connection, server_wait_duration).await,
             Duration::from_nanos(1),
+            TableConfig::default(),
         )
         .await;
         // This should be an error as no one connected
@@ -136,6 +314,7 @@ mod tests {
             Engine::new(
                 Server::from_tcp_listener(tcp_connection, server_wait_duration).await,
                 Duration::from_nanos(1),
+                TableConfig::default(),
             )
             .await
         });
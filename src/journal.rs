@@ -0,0 +1,151 @@
+use std::fs::{File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+use crate::table::TableAction;
+
+/// One action applied to the table, numbered by its place among every action of the game
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct JournalEntry {
+    pub index: usize,
+    pub action: TableAction,
+}
+
+/// Every action applied to the table this game, one json line each. Each line is on disk before the next
+/// state is sent, so a game can be restored to the exact action it was on after a crash.
+pub struct Journal {
+    file: File,
+}
+
+impl Journal {
+    /// The journal is kept next to the checkpoint it follows
+    pub fn get_path(checkpoint_file: &Path) -> PathBuf {
+        checkpoint_file.with_extension("journal")
+    }
+
+    /// Starts an empty journal, replacing any old one
+    pub fn create(path: &Path) -> Result<Self, String> {
+        let file = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .truncate(true)
+            .open(path)
+            .map_err(|error| format!("Couldn't create the journal {}: {error}", path.display()))?;
+        Ok(Journal { file })
+    }
+
+    /// Opens a journal to carry on writing it after a restart. A partly written last line is cut off first,
+    /// otherwise the next entry would be appended to it
+    pub fn open(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| format!("Couldn't read the journal {}: {error}", path.display()))?;
+        let length = contents.rfind('\n').map_or(0, |index| index + 1);
+        let file = OpenOptions::new()
+            .append(true)
+            .open(path)
+            .and_then(|file| file.set_len(length as u64).map(|_| file))
+            .map_err(|error| format!("Couldn't open the journal {}: {error}", path.display()))?;
+        Ok(Journal { file })
+    }
+
+    /// Appends the entry and waits until it is on disk
+    pub fn append(&mut self, entry: &JournalEntry) -> Result<(), String> {
+        let line = serde_json::to_string(entry)
+            .map_err(|error| format!("Couldn't serialize the journal entry: {error}"))?;
+        writeln!(self.file, "{line}")
+            .and_then(|_| self.file.sync_data())
+            .map_err(|error| format!("Couldn't write to the journal: {error}"))
+    }
+
+    /// Reads every entry of a journal, a missing journal has none. The last line is skipped if it was only
+    /// partly written when the server went down.
+    pub fn read(path: &Path) -> Result<Vec<JournalEntry>, String> {
+        if !path.exists() {
+            return Ok(vec![]);
+        }
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| format!("Couldn't read the journal {}: {error}", path.display()))?;
+        let lines: Vec<_> = contents.lines().collect();
+        let mut entries = vec![];
+        for (number, line) in lines.iter().enumerate() {
+            match serde_json::from_str(line) {
+                Ok(entry) => entries.push(entry),
+                Err(_) if number + 1 == lines.len() && !contents.ends_with('\n') => {
+                    warn!("Skipping the partly written last line of the journal: {line}");
+                }
+                Err(error) => {
+                    return Err(format!(
+                        "Couldn't parse line {} of the journal: {error}",
+                        number + 1
+                    ))
+                }
+            }
+        }
+        Ok(entries)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::actions::HandAction;
+    use crate::journal::{Journal, JournalEntry};
+    use crate::table::TableAction;
+
+    #[test]
+    fn test_journal_path() {
+        assert_eq!(
+            Journal::get_path(Path::new("games/game.json")),
+            Path::new("games/game.journal")
+        );
+    }
+
+    #[test]
+    fn test_journal_round_trip() {
+        let path = std::env::temp_dir().join("bot_arena_test_journal.journal");
+        let entries = vec![
+            JournalEntry {
+                index: 4,
+                action: TableAction::TakePlayerAction(1, HandAction::Raise(20)),
+            },
+            JournalEntry {
+                index: 5,
+                action: TableAction::TakePlayerAction(2, HandAction::Fold),
+            },
+        ];
+        let mut journal = Journal::create(&path).unwrap();
+        for entry in &entries {
+            journal.append(entry).unwrap();
+        }
+        assert_eq!(Journal::read(&path).unwrap(), entries);
+        // Creating the journal again empties it
+        Journal::create(&path).unwrap();
+        assert!(Journal::read(&path).unwrap().is_empty());
+        std::fs::remove_file(&path).unwrap();
+        assert!(Journal::read(&path).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_journal_partly_written_line() {
+        let path = std::env::temp_dir().join("bot_arena_test_torn_journal.journal");
+        let entry = JournalEntry {
+            index: 0,
+            action: TableAction::TakePlayerAction(0, HandAction::Call),
+        };
+        let line = serde_json::to_string(&entry).unwrap();
+        // The last line was cut off by the crash, so it is skipped
+        std::fs::write(&path, format!("{line}\n{}", &line[..10])).unwrap();
+        assert_eq!(Journal::read(&path).unwrap(), vec![entry.clone()]);
+        // Carrying on the journal drops the cut off line before writing the next entry
+        let mut journal = Journal::open(&path).unwrap();
+        journal.append(&entry).unwrap();
+        assert_eq!(Journal::read(&path).unwrap(), vec![entry.clone(), entry]);
+        // A broken line before the end means the journal can't be trusted
+        std::fs::write(&path, format!("{}\n{line}\n", &line[..10])).unwrap();
+        assert!(Journal::read(&path).is_err());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
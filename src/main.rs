@@ -2,32 +2,48 @@ extern crate core;
 #[macro_use]
 extern crate log;
 
+use std::path::Path;
 use std::time::Duration;
 
 use clap::Parser;
 use env_logger::Env;
+use json::stringify_pretty;
 
-use crate::args::{validate_bot_args, BotArgs};
+use crate::args::{validate_bot_args, BotArgs, Command, TournamentFormat};
+use crate::checkpoint::Checkpoint;
 use crate::engine::Engine;
 use crate::example_bots::{
     subscribe_and_take_call_action, subscribe_and_take_fold_via_incorrect_api_usage,
     subscribe_and_take_random_action,
 };
+use crate::journal::Journal;
+use crate::replay::Replay;
 use crate::server::Server;
+use crate::tournament::bracket::BracketTournament;
+use crate::tournament::multi_table::MultiTableTournament;
 
 mod actions;
 mod args;
 mod bet_stage;
+mod checkpoint;
 mod engine;
 mod example_bots;
 mod global_immutables;
+mod journal;
 mod log_setup;
+mod payout;
 mod player_components;
+mod replay;
 mod server;
 mod table;
+mod tournament;
 
 const ERROR_CODE_NO_SUBS: i32 = 1;
 const ERROR_CODE_BAD_INPUT: i32 = 2;
+const ERROR_CODE_RESULTS_FILE: i32 = 3;
+const ERROR_CODE_STATS_FILE: i32 = 4;
+const ERROR_CODE_REPLAY: i32 = 5;
+const ERROR_CODE_CHECKPOINT: i32 = 6;
 
 #[tokio::main]
 async fn main() -> Result<(), i32> {
@@ -49,6 +65,27 @@ async fn main_result(args: BotArgs) -> Result<(), i32> {
         ERROR_CODE_BAD_INPUT
     })?;
 
+    if let Some(Command::Replay { file, stop_at_hand }) = &args.command {
+        return play_replay(file, *stop_at_hand);
+    }
+
+    // Read the checkpoint and the journal after it before waiting on any bots so a bad file fails right away
+    let checkpoint = match (&args.checkpoint_file, args.resume) {
+        (Some(path), true) => Some(
+            Checkpoint::from_file(path)
+                .and_then(|mut checkpoint| {
+                    let entries = Journal::read(&Journal::get_path(path))?;
+                    let actions = checkpoint.apply_journal(&entries)?;
+                    Ok((checkpoint, actions))
+                })
+                .map_err(|error| {
+                    error!("Couldn't resume the game due to the following error: {error}");
+                    ERROR_CODE_CHECKPOINT
+                })?,
+        ),
+        _ => None,
+    };
+
     // Start any test bots
     let mut bot_futures = vec![];
     for id in 0..args.n_call_bots {
@@ -70,27 +107,94 @@ async fn main_result(args: BotArgs) -> Result<(), i32> {
         bot_futures.push(result);
     }
 
-    // Start the engine
-    let engine_future = Engine::new(
-        Server::from_server_url(
-            format!("0.0.0.0:{}", args.port).as_str(),
-            Duration::from_nanos((args.server_connection_time_seconds * 1e9) as u64),
-        )
-        .await,
-        Duration::from_secs(1),
-    );
-
-    // Wait for the engine to finish accepting connections
-    let mut engine = engine_future.await.map_err(|error| {
-        let error_string = format!("Couldn't init server due to the following error: {}", error);
-        error!("{error_string}");
-        ERROR_CODE_NO_SUBS
-    })?;
-    // Play the game
-    engine.play_game().await;
-    info!("Game is over now!");
-    // Game is now over after the await, shutdown the server (drop it)
-    drop(engine);
+    let server = Server::from_server_url(
+        format!("0.0.0.0:{}", args.port).as_str(),
+        Duration::from_nanos((args.server_connection_time_seconds * 1e9) as u64),
+    )
+    .await;
+    let read_timeout = Duration::from_secs(1);
+
+    let results = match args.tournament_format {
+        TournamentFormat::SingleTable => {
+            // Start the engine once the server is done accepting connections
+            let engine = match checkpoint {
+                Some((checkpoint, actions)) => {
+                    Engine::resume(server, read_timeout, checkpoint, actions).await
+                }
+                None => Engine::new(server, read_timeout, args.get_table_config()).await,
+            };
+            let mut engine = engine.map_err(|error| {
+                let error_string =
+                    format!("Couldn't init server due to the following error: {}", error);
+                error!("{error_string}");
+                ERROR_CODE_NO_SUBS
+            })?;
+            engine.checkpoint_file = args.checkpoint_file.clone();
+            // Play the game
+            let results = engine.play_game().await;
+            info!("Game is over now!");
+            if let Some(stats_file) = &args.stats_file {
+                let stats = stringify_pretty(engine.table.get_player_stats_json(), 4);
+                std::fs::write(stats_file, stats).map_err(|error| {
+                    error!("Couldn't write the stats file due to the following error: {error}");
+                    ERROR_CODE_STATS_FILE
+                })?;
+            }
+            if let Some(replay_file) = &args.replay_file {
+                Replay::new(&engine.table, engine.actions.clone())
+                    .write_to_file(replay_file)
+                    .map_err(|error| {
+                        error!(
+                            "Couldn't write the replay file due to the following error: {error}"
+                        );
+                        ERROR_CODE_REPLAY
+                    })?;
+            }
+            // Game is now over after the await, shutdown the server (drop it)
+            drop(engine);
+            results
+        }
+        TournamentFormat::Bracket => {
+            let mut server = server;
+            let mut bracket = BracketTournament::new(
+                server.connections.len(),
+                args.max_table_size,
+                args.advance_per_table,
+            )
+            .map_err(|error| {
+                error!("Couldn't start the bracket due to the following error: {error}");
+                ERROR_CODE_NO_SUBS
+            })?;
+            // Play every stage
+            let results = bracket.play(&mut server.connections, read_timeout).await;
+            info!("Tournament is over now!");
+            // Tournament is now over, shutdown the server (drop it)
+            drop(server);
+            results
+        }
+        TournamentFormat::MultiTable => {
+            let mut server = server;
+            let mut tournament =
+                MultiTableTournament::new(server.connections.len(), args.max_table_size).map_err(
+                    |error| {
+                        error!("Couldn't start the tournament due to the following error: {error}");
+                        ERROR_CODE_NO_SUBS
+                    },
+                )?;
+            // Play hand for hand until one player is left
+            let results = tournament.play(&mut server.connections, read_timeout).await;
+            info!("Tournament is over now!");
+            // Tournament is now over, shutdown the server (drop it)
+            drop(server);
+            results
+        }
+    };
+    if let Some(results_file) = &args.results_file {
+        std::fs::write(results_file, results).map_err(|error| {
+            error!("Couldn't write the results file due to the following error: {error}");
+            ERROR_CODE_RESULTS_FILE
+        })?;
+    }
     // Join any testing bots now
     for (index, bot_future) in bot_futures.into_iter().enumerate() {
         info!("Waiting for bot at index {index}");
@@ -106,26 +210,57 @@ async fn main_result(args: BotArgs) -> Result<(), i32> {
     Ok(())
 }
 
+/// Plays a replay file back, printing the table at the requested hand or the matching results
+fn play_replay(file: &Path, stop_at_hand: Option<i32>) -> Result<(), i32> {
+    let table = Replay::from_file(file)
+        .and_then(|replay| replay.play(stop_at_hand))
+        .map_err(|error| {
+            error!("Couldn't replay the game due to the following error: {error}");
+            ERROR_CODE_REPLAY
+        })?;
+    match stop_at_hand {
+        Some(hand) => println!(
+            "Stopped at hand {hand}:\n{}",
+            table.get_state_string_for_current_player()
+        ),
+        None => println!(
+            "Replay finished with the recorded results:\n{}",
+            table.get_results()
+        ),
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use std::time::Duration;
 
-    use crate::args::BotArgs;
+    use clap::Parser;
+
+    use crate::args::{BotArgs, TournamentFormat};
+    use crate::checkpoint::Checkpoint;
     use crate::example_bots::subscribe_and_take_fold_via_incorrect_api_usage;
-    use crate::{main_result, ERROR_CODE_NO_SUBS};
+    use crate::journal::{Journal, JournalEntry};
+    use crate::replay::Replay;
+    use crate::table::{Table, TableAction};
+    use crate::{
+        main_result, play_replay, ERROR_CODE_CHECKPOINT, ERROR_CODE_NO_SUBS, ERROR_CODE_REPLAY,
+    };
+
+    /// The default args on the given port with logging turned off, tests set the args they need on top
+    fn get_test_args(port: i32, server_connection_time_seconds: f64) -> BotArgs {
+        BotArgs {
+            port,
+            server_connection_time_seconds,
+            disable_logging: true,
+            ..BotArgs::parse_from(["test"])
+        }
+    }
 
     #[tokio::test]
     async fn check_main_no_subs() {
         // Since there are no subs this should be an error
-        let main_result = main_result(BotArgs {
-            port: 10100,
-            server_connection_time_seconds: 0.0002,
-            n_call_bots: 0,
-            n_random_bots: 0,
-            n_fail_bots: 0,
-            disable_logging: true,
-        })
-        .await;
+        let main_result = main_result(get_test_args(10100, 0.0002)).await;
         assert!(main_result.is_err());
         assert_eq!(main_result.err().unwrap(), ERROR_CODE_NO_SUBS);
     }
@@ -134,17 +269,10 @@ mod tests {
     async fn check_main_with_subs() {
         const PORT_TEST_NUMBER: i32 = 10101;
 
-        let main_result = tokio::task::spawn(async move {
-            main_result(BotArgs {
-                port: PORT_TEST_NUMBER,
-                server_connection_time_seconds: 10.0,
-                n_call_bots: 0,
-                n_random_bots: 0,
-                n_fail_bots: 0,
-                disable_logging: true,
-            })
-            .await
-        });
+        let main_result =
+            tokio::task::spawn(
+                async move { main_result(get_test_args(PORT_TEST_NUMBER, 10.0)).await },
+            );
 
         let mut handles = vec![];
 
@@ -173,12 +301,10 @@ mod tests {
 
         let main_result = tokio::task::spawn(async move {
             main_result(BotArgs {
-                port: PORT_TEST_NUMBER,
-                server_connection_time_seconds: 10.0,
                 n_call_bots: 7,
                 n_random_bots: 7,
                 n_fail_bots: 7,
-                disable_logging: true,
+                ..get_test_args(PORT_TEST_NUMBER, 10.0)
             })
             .await
         });
@@ -195,12 +321,10 @@ mod tests {
 
         let main_result = tokio::task::spawn(async move {
             main_result(BotArgs {
-                port: PORT_TEST_NUMBER,
-                server_connection_time_seconds: 10.0,
                 n_call_bots: 7,
                 n_random_bots: 7,
                 n_fail_bots: 37,
-                disable_logging: true,
+                ..get_test_args(PORT_TEST_NUMBER, 10.0)
             })
             .await
         });
@@ -208,4 +332,148 @@ mod tests {
         let result = main_result.await.expect("Main result ended ok");
         assert!(result.is_err());
     }
+
+    #[tokio::test]
+    async fn check_main_with_bracket() {
+        const PORT_TEST_NUMBER: i32 = 10112;
+
+        let main_result = tokio::task::spawn(async move {
+            main_result(BotArgs {
+                n_call_bots: 8,
+                tournament_format: TournamentFormat::Bracket,
+                max_table_size: 4,
+                advance_per_table: 1,
+                ..get_test_args(PORT_TEST_NUMBER, 2.0)
+            })
+            .await
+        });
+
+        let result = main_result.await.expect("Main result ended ok");
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn check_main_with_multi_table() {
+        const PORT_TEST_NUMBER: i32 = 10113;
+
+        let main_result = tokio::task::spawn(async move {
+            main_result(BotArgs {
+                n_call_bots: 4,
+                n_random_bots: 4,
+                tournament_format: TournamentFormat::MultiTable,
+                max_table_size: 3,
+                ..get_test_args(PORT_TEST_NUMBER, 2.0)
+            })
+            .await
+        });
+
+        let result = main_result.await.expect("Main result ended ok");
+        assert!(result.is_ok());
+    }
+
+    #[tokio::test]
+    async fn check_main_with_payouts_and_results_file() {
+        const PORT_TEST_NUMBER: i32 = 10114;
+        let results_file = std::env::temp_dir().join("bot_arena_check_main_results.txt");
+        let _ = std::fs::remove_file(&results_file);
+        let stats_file = std::env::temp_dir().join("bot_arena_check_main_stats.json");
+        let _ = std::fs::remove_file(&stats_file);
+        let replay_file = std::env::temp_dir().join("bot_arena_check_main_replay.json");
+        let _ = std::fs::remove_file(&replay_file);
+
+        let result = main_result(BotArgs {
+            n_call_bots: 3,
+            payouts: vec![70.0, 30.0],
+            prize_pool: 1000.0,
+            results_file: Some(results_file.clone()),
+            stats_file: Some(stats_file.clone()),
+            replay_file: Some(replay_file.clone()),
+            ..get_test_args(PORT_TEST_NUMBER, 2.0)
+        })
+        .await;
+        assert!(result.is_ok());
+        let results = std::fs::read_to_string(&results_file).unwrap();
+        assert!(results.starts_with("Game is over:"));
+        assert!(results.contains("Prize:    700.00"));
+        assert!(results.contains("Prize:    300.00"));
+        assert!(results.contains("Player Stats:"));
+        std::fs::remove_file(&results_file).unwrap();
+        let stats = json::parse(&std::fs::read_to_string(&stats_file).unwrap()).unwrap();
+        assert_eq!(stats.len(), 3);
+        assert!(stats[0]["hands"].as_usize().unwrap() > 0);
+        std::fs::remove_file(&stats_file).unwrap();
+        // The game plays back to the same results without any bots
+        assert!(play_replay(&replay_file, None).is_ok());
+        assert!(play_replay(&replay_file, Some(2)).is_ok());
+        std::fs::remove_file(&replay_file).unwrap();
+        assert_eq!(play_replay(&replay_file, None), Err(ERROR_CODE_REPLAY));
+    }
+
+    fn get_checkpoint_args(
+        port: i32,
+        checkpoint_file: &std::path::Path,
+        replay_file: &std::path::Path,
+        resume: bool,
+    ) -> BotArgs {
+        BotArgs {
+            n_call_bots: 3,
+            replay_file: Some(replay_file.to_path_buf()),
+            checkpoint_file: Some(checkpoint_file.to_path_buf()),
+            resume,
+            ..get_test_args(port, 2.0)
+        }
+    }
+
+    #[tokio::test]
+    async fn check_main_resumes_from_checkpoint() {
+        const PORT_TEST_NUMBER: i32 = 10115;
+        let checkpoint_file = std::env::temp_dir().join("bot_arena_check_main_checkpoint.json");
+        let _ = std::fs::remove_file(&checkpoint_file);
+        let replay_file = std::env::temp_dir().join("bot_arena_check_main_resume_replay.json");
+        // Resuming without a checkpoint fails before waiting on any bots
+        let args = get_checkpoint_args(PORT_TEST_NUMBER, &checkpoint_file, &replay_file, true);
+        assert_eq!(main_result(args).await, Err(ERROR_CODE_CHECKPOINT));
+
+        let args = get_checkpoint_args(PORT_TEST_NUMBER, &checkpoint_file, &replay_file, false);
+        assert!(main_result(args).await.is_ok());
+        let checkpoint = Checkpoint::from_file(&checkpoint_file).unwrap();
+        assert!(checkpoint.table.is_game_over());
+        assert_eq!(checkpoint.identities, vec!["127.0.0.1"; 3]);
+        // Pretend the server crashed during the third hand, the last checkpoint is from when it was dealt
+        // and the journal has every action up to two actions into the hand
+        let replay = Replay::from_file(&replay_file).unwrap();
+        let mut table = Table::with_config(replay.player_count, replay.config.clone()).unwrap();
+        let mut journal = Journal::create(&Journal::get_path(&checkpoint_file)).unwrap();
+        let mut journal_action = |table: &mut Table, index: usize| {
+            let seat = table.get_current_player_index() as i8;
+            table.take_action(replay.actions[index]).unwrap();
+            let action = TableAction::TakePlayerAction(seat, replay.actions[index]);
+            journal.append(&JournalEntry { index, action }).unwrap();
+        };
+        let mut action_count = 0;
+        while table.get_hand_number() < 3 {
+            journal_action(&mut table, action_count);
+            action_count += 1;
+        }
+        Checkpoint {
+            identities: checkpoint.identities,
+            action_count,
+            table: &table,
+        }
+        .write_to_file(&checkpoint_file)
+        .unwrap();
+        for index in action_count..action_count + 2 {
+            journal_action(&mut table, index);
+        }
+        assert_eq!(table.get_hand_number(), 3);
+        let args = get_checkpoint_args(PORT_TEST_NUMBER + 1, &checkpoint_file, &replay_file, true);
+        assert!(main_result(args).await.is_ok());
+        // The bots always call, so the resumed game finishes just like the first one
+        let resumed_replay = Replay::from_file(&replay_file).unwrap();
+        assert_eq!(resumed_replay.results, replay.results);
+        assert!(play_replay(&replay_file, None).is_ok());
+        std::fs::remove_file(&checkpoint_file).unwrap();
+        std::fs::remove_file(Journal::get_path(&checkpoint_file)).unwrap();
+        std::fs::remove_file(&replay_file).unwrap();
+    }
 }
@@ -0,0 +1,216 @@
+use std::collections::HashMap;
+
+use rand::{Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
+
+/// How a prize pool is split between the top finishers
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PayoutStructure {
+    /// The total amount paid out
+    prize_pool: f64,
+    /// The percent of the prize pool paid to each place, best place first
+    percentages: Vec<f64>,
+}
+
+impl PayoutStructure {
+    /// Makes a payout structure, the percentages must be positive and add up to at most 100
+    pub fn new(prize_pool: f64, percentages: Vec<f64>) -> Result<Self, String> {
+        if prize_pool <= 0.0 {
+            return Err("The prize pool must be positive".to_string());
+        }
+        if percentages.is_empty() || percentages.iter().any(|x| *x <= 0.0) {
+            return Err(
+                "At least one place must be paid, and every paid place must get a positive percent"
+                    .to_string(),
+            );
+        }
+        // Leave a little room for percentages like 33.33 that can't add up exactly
+        if percentages.iter().sum::<f64>() > 100.0 + 1e-6 {
+            return Err("The payout percentages add up to more than 100".to_string());
+        }
+        Ok(PayoutStructure {
+            prize_pool,
+            percentages,
+        })
+    }
+
+    /// Gets the prize for each paid place, best place first
+    pub fn get_prizes(&self) -> Vec<f64> {
+        self.percentages
+            .iter()
+            .map(|percent| self.prize_pool * percent / 100.0)
+            .collect()
+    }
+
+    /// Gets the prize for a place, 0 INDEXED, places past the paid places get nothing
+    pub fn get_prize(&self, place: usize) -> f64 {
+        self.get_prizes().get(place).copied().unwrap_or_default()
+    }
+}
+
+/// The most players with chips whose equities are worked out exactly, the exact model looks at every set of
+/// finishers so its cost doubles with each player
+const MAX_EXACT_ICM_PLAYERS: usize = 10;
+
+/// How many finishing orders are drawn to estimate the equities of larger fields
+const ICM_SAMPLES: usize = 50_000;
+
+/// Gets the Independent Chip Model equity of each stack for the given prizes (best place first).
+/// A player finishes first with a chance proportional to their stack, and the same is repeated
+/// for each later place among the players that are left. Fields with more than
+/// MAX_EXACT_ICM_PLAYERS players with chips are estimated from sampled finishing orders instead.
+pub fn get_icm_equities(stacks: &[i32], prizes: &[f64]) -> Vec<f64> {
+    let total_chips: i64 = stacks.iter().map(|x| *x as i64).sum();
+    if total_chips <= 0 {
+        // Nobody has chips, so everyone is equally likely to take each place
+        let prize_total: f64 = prizes.iter().take(stacks.len()).sum();
+        return vec![prize_total / stacks.len() as f64; stacks.len()];
+    }
+    // Busted stacks never take a place, so only the players with chips are ranked
+    let seats: Vec<usize> = (0..stacks.len()).filter(|i| stacks[*i] > 0).collect();
+    let live_stacks: Vec<i32> = seats.iter().map(|i| stacks[*i]).collect();
+    let live_equities = if live_stacks.len() <= MAX_EXACT_ICM_PLAYERS {
+        get_exact_icm_equities(&live_stacks, prizes)
+    } else {
+        get_sampled_icm_equities(&live_stacks, prizes, ICM_SAMPLES)
+    };
+    let mut equities = vec![0.0; stacks.len()];
+    for (seat, equity) in seats.into_iter().zip(live_equities) {
+        equities[seat] = equity;
+    }
+    equities
+}
+
+/// Works out the equities by following the chance of every set of players taking the paid places,
+/// every stack must be positive
+fn get_exact_icm_equities(stacks: &[i32], prizes: &[f64]) -> Vec<f64> {
+    let mut equities = vec![0.0; stacks.len()];
+    let total_chips: i64 = stacks.iter().map(|x| *x as i64).sum();
+    // The chance that exactly the players in each set took the places paid so far, in any order
+    let mut finished_sets: HashMap<u32, f64> = HashMap::from([(0, 1.0)]);
+    for prize in prizes.iter().take(stacks.len()) {
+        let mut next_finished_sets = HashMap::new();
+        for (finished, probability) in finished_sets {
+            let chips_left = total_chips
+                - (0..stacks.len())
+                    .filter(|i| finished & (1 << i) != 0)
+                    .map(|i| stacks[i] as i64)
+                    .sum::<i64>();
+            for (i, stack) in stacks.iter().enumerate() {
+                if finished & (1 << i) != 0 {
+                    continue;
+                }
+                let place_probability = probability * *stack as f64 / chips_left as f64;
+                equities[i] += place_probability * prize;
+                *next_finished_sets.entry(finished | (1 << i)).or_insert(0.0) += place_probability;
+            }
+        }
+        finished_sets = next_finished_sets;
+    }
+    equities
+}
+
+/// Estimates the equities from finishing orders drawn with the same chances as the exact model, every stack must be
+/// positive. Each player gets a finishing time drawn from an exponential distribution whose rate is their stack, the
+/// soonest finishes first, which is the same as picking each place in proportion to the stacks left.
+/// The draws use a fixed seed so the same stacks always get the same equities
+fn get_sampled_icm_equities(stacks: &[i32], prizes: &[f64], samples: usize) -> Vec<f64> {
+    let mut rng = ChaCha12Rng::seed_from_u64(0);
+    let mut equities = vec![0.0; stacks.len()];
+    let mut finish_order: Vec<(f64, usize)> = Vec::with_capacity(stacks.len());
+    for _ in 0..samples {
+        finish_order.clear();
+        for (i, stack) in stacks.iter().enumerate() {
+            // 1 - a number in [0, 1) is never 0, so the log is finite
+            let time = -(1.0 - rng.gen::<f64>()).ln() / *stack as f64;
+            finish_order.push((time, i));
+        }
+        finish_order.sort_by(|left, right| left.0.total_cmp(&right.0));
+        for ((_, i), prize) in finish_order.iter().zip(prizes) {
+            equities[*i] += prize;
+        }
+    }
+    equities
+        .into_iter()
+        .map(|equity| equity / samples as f64)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::payout::{
+        get_exact_icm_equities, get_icm_equities, get_sampled_icm_equities, PayoutStructure,
+    };
+
+    fn assert_close(left: f64, right: f64) {
+        assert!((left - right).abs() < 1e-6, "{left} != {right}");
+    }
+
+    #[test]
+    fn test_bad_payouts() {
+        assert!(PayoutStructure::new(0.0, vec![100.0]).is_err());
+        assert!(PayoutStructure::new(100.0, vec![]).is_err());
+        assert!(PayoutStructure::new(100.0, vec![60.0, 50.0]).is_err());
+        assert!(PayoutStructure::new(100.0, vec![60.0, 0.0]).is_err());
+        assert!(PayoutStructure::new(100.0, vec![33.34, 33.33, 33.33]).is_ok());
+    }
+
+    #[test]
+    fn test_prizes() {
+        let payouts = PayoutStructure::new(1000.0, vec![50.0, 30.0, 20.0]).unwrap();
+        assert_eq!(payouts.get_prizes(), vec![500.0, 300.0, 200.0]);
+        assert_eq!(payouts.get_prize(1), 300.0);
+        assert_eq!(payouts.get_prize(3), 0.0);
+    }
+
+    #[test]
+    fn test_icm_heads_up() {
+        // Heads up, the chance of first place is the share of the chips
+        let equities = get_icm_equities(&[300, 100], &[70.0, 30.0]);
+        assert_close(equities[0], 0.75 * 70.0 + 0.25 * 30.0);
+        assert_close(equities[1], 0.25 * 70.0 + 0.75 * 30.0);
+    }
+
+    #[test]
+    fn test_icm_three_players() {
+        let equities = get_icm_equities(&[5000, 3000, 2000], &[50.0, 30.0, 20.0]);
+        // Known values for this spot
+        assert_close(equities[0], 38.392857143);
+        assert_close(equities[1], 32.75);
+        assert_close(equities[2], 28.857142857);
+        assert_close(equities.iter().sum(), 100.0);
+    }
+
+    #[test]
+    fn test_icm_equal_stacks_and_fewer_prizes() {
+        let equities = get_icm_equities(&[100, 100, 100, 100], &[60.0, 40.0]);
+        for equity in &equities {
+            assert_close(*equity, 25.0);
+        }
+        // Busted stacks never take a place
+        let equities = get_icm_equities(&[100, 0, 100], &[60.0, 40.0]);
+        assert_close(equities[0], 50.0);
+        assert_close(equities[1], 0.0);
+        assert_close(equities[2], 50.0);
+    }
+
+    #[test]
+    fn test_icm_large_field() {
+        let stacks: Vec<i32> = (1..=22).map(|x| x * 100).collect();
+        let prizes = [40.0, 25.0, 15.0, 10.0, 6.0, 4.0];
+        let equities = get_icm_equities(&stacks, &prizes);
+        assert_close(equities.iter().sum(), 100.0);
+        // Bigger stacks are worth more, but less than their share of the chips
+        assert!(equities.windows(2).all(|x| x[0] < x[1]));
+        let chip_share = 2200.0 / stacks.iter().sum::<i32>() as f64;
+        assert!(equities[21] < chip_share * 100.0);
+        // The estimate is close to the exact model where both can be worked out
+        let stacks = [900, 700, 500, 300, 100, 50, 800, 600, 400, 200];
+        let exact = get_exact_icm_equities(&stacks, &prizes);
+        let sampled = get_sampled_icm_equities(&stacks, &prizes, 50_000);
+        for (exact, sampled) in exact.iter().zip(&sampled) {
+            assert!((exact - sampled).abs() < 0.5, "{exact} != {sampled}");
+        }
+    }
+}
@@ -1,33 +1,130 @@
 use std::cmp::Ordering;
 use std::fmt;
 use std::fmt::Formatter;
+use std::ops::Deref;
 
 use json::{array, object, JsonValue};
-use poker::Card;
+use poker::{Card, Rank, Suit};
+use serde::{Deserialize, Serialize};
 
 use crate::player_components::PlayerState::{Active, Folded};
+use crate::table::TableError;
 
 pub const DEFAULT_START_MONEY: i32 = 500;
 
-#[derive(Copy, Clone)]
+/// The most hole cards any game variant deals to one player
+pub const MAX_HOLE_CARDS: usize = 7;
+
+/// A player's private cards, how many depends on the game variant
+/// Saved as the card strings, such as `As`
+#[derive(Copy, Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+#[serde(into = "Vec<String>", try_from = "Vec<String>")]
+pub struct HoleCards {
+    cards: [Card; MAX_HOLE_CARDS],
+    count: usize,
+}
+
+impl HoleCards {
+    pub fn new(cards: &[Card]) -> Self {
+        if cards.len() > MAX_HOLE_CARDS {
+            panic!("Too many hole cards!")
+        }
+        // The unused slots are never read, any card can fill them
+        let mut hole_cards = [Card::new(Rank::Two, Suit::Clubs); MAX_HOLE_CARDS];
+        hole_cards[..cards.len()].copy_from_slice(cards);
+        HoleCards {
+            cards: hole_cards,
+            count: cards.len(),
+        }
+    }
+
+    /// Gets the card strings as a json array
+    pub fn as_json(&self) -> JsonValue {
+        self.iter()
+            .map(|card| card.to_string())
+            .collect::<Vec<_>>()
+            .into()
+    }
+}
+
+impl Deref for HoleCards {
+    type Target = [Card];
+
+    fn deref(&self) -> &[Card] {
+        &self.cards[..self.count]
+    }
+}
+
+impl<'a> IntoIterator for &'a HoleCards {
+    type Item = &'a Card;
+    type IntoIter = std::slice::Iter<'a, Card>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.iter()
+    }
+}
+
+impl From<HoleCards> for Vec<String> {
+    fn from(hole_cards: HoleCards) -> Self {
+        hole_cards
+            .iter()
+            .map(|card| card.rank_suit_string())
+            .collect()
+    }
+}
+
+impl TryFrom<Vec<String>> for HoleCards {
+    type Error = String;
+
+    fn try_from(cards: Vec<String>) -> Result<Self, Self::Error> {
+        if cards.len() > MAX_HOLE_CARDS {
+            return Err(format!("{} hole cards is too many", cards.len()));
+        }
+        let cards = cards
+            .iter()
+            .map(|card| card.parse().map_err(|_| format!("{card} isn't a card")))
+            .collect::<Result<Vec<Card>, _>>()?;
+        Ok(HoleCards::new(&cards))
+    }
+}
+
+impl fmt::Display for HoleCards {
+    /// Gets the cards separated by spaces
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let cards: Vec<String> = self.iter().map(|card| card.to_string()).collect();
+        write!(f, "{}", cards.join(" "))
+    }
+}
+
+#[derive(Copy, Clone, Deserialize, Serialize)]
 pub enum PlayerState {
     Folded,
     Active(ActiveState),
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Deserialize, Serialize)]
 pub struct ActiveState {
-    pub hand: [Card; 2],
+    pub hand: HoleCards,
     pub current_bet: i32,
 }
 
-#[derive(Copy, Clone)]
+#[derive(Copy, Clone, Deserialize, Serialize)]
 pub struct Player {
     pub player_state: PlayerState,
     pub total_money: i32,
     pub death_hand_number: Option<i32>,
     id: i8,
     pub has_had_turn_this_round: bool,
+    /// Every chip the player has been given, their starting stack plus any rebuys
+    pub total_buy_in: i32,
+    /// How many times the player re-entered after busting
+    pub rebuy_count: i32,
+    /// How many players this player has knocked out
+    pub knockout_count: i32,
+    /// The bounties collected from knocking out other players
+    pub bounty_winnings: i32,
+    /// The money the player had when their last hand was dealt, before any forced bets
+    pub hand_starting_money: i32,
 }
 
 impl fmt::Display for PlayerState {
@@ -45,7 +142,7 @@ impl PlayerState {
                 object!(state_type: "folded", details: object! ())
             }
             Active(a) => {
-                object!(state_type: "active", details: object!(hand: array![a.hand[0].to_string(), a.hand[1].to_string()], bet: a.current_bet))
+                object!(state_type: "active", details: object!(hand: a.hand.as_json(), bet: a.current_bet))
             }
         }
     }
@@ -74,9 +171,7 @@ impl PlayerState {
             Folded => {
                 array!["None"]
             }
-            Active(active) => {
-                array![active.hand[0].to_string(), active.hand[1].to_string()]
-            }
+            Active(active) => active.hand.as_json(),
         }
     }
 
@@ -99,7 +194,8 @@ impl Eq for Player {}
 
 impl PartialEq<Self> for Player {
     fn eq(&self, other: &Self) -> bool {
-        self.death_hand_number == other.death_hand_number
+        // Players are only equal if neither ranks above the other
+        self.cmp(other) == Ordering::Equal
     }
 }
 
@@ -111,9 +207,11 @@ impl PartialOrd<Self> for Player {
 
 impl Ord for Player {
     fn cmp(&self, other: &Self) -> Ordering {
+        // Between otherwise equal players the one that needed fewer entries did better
+        let fewer_rebuys = other.rebuy_count.cmp(&self.rebuy_count);
         // If players are both alive return whichever has more money
         if self.is_alive() && other.is_alive() {
-            return self.total_money.cmp(&other.total_money);
+            return self.total_money.cmp(&other.total_money).then(fewer_rebuys);
         } else if self.is_alive() {
             // Only self is alive so it is greater
             return Ordering::Greater;
@@ -121,10 +219,13 @@ impl Ord for Player {
             // Compared to other hand that is alive while self is dead, so it is less than
             return Ordering::Less;
         }
-        // Both players are dead, prefer which round died on (money left doesn't matter now)
+        // Both players are dead, prefer which round died on (money left doesn't matter now),
+        // then whoever started the hand they busted on with more money
         self.death_hand_number
             .unwrap()
             .cmp(&other.death_hand_number.unwrap())
+            .then(self.hand_starting_money.cmp(&other.hand_starting_money))
+            .then(fewer_rebuys)
     }
 }
 
@@ -137,25 +238,31 @@ impl Player {
             death_hand_number: None,
             id,
             has_had_turn_this_round: false,
+            total_buy_in: DEFAULT_START_MONEY,
+            rebuy_count: 0,
+            knockout_count: 0,
+            bounty_winnings: 0,
+            hand_starting_money: DEFAULT_START_MONEY,
         }
     }
 
     /// Given the player new cards and ensures they're in an active state
-    pub fn deal(&mut self, cards: [Card; 2]) {
+    pub fn deal(&mut self, cards: &[Card]) {
         self.player_state = Active(ActiveState {
-            hand: cards,
+            hand: HoleCards::new(cards),
             current_bet: 0,
         });
         self.has_had_turn_this_round = false;
     }
 
-    /// Changes state to fold, and removes all bet money
-    pub fn fold(&mut self) {
+    /// Changes state to fold, and removes all bet money. A player that isn't in the hand can't fold
+    pub fn fold(&mut self) -> Result<(), TableError> {
         self.has_had_turn_this_round = true;
         if let Active(_) = &mut self.player_state {
             self.player_state = Folded;
+            Ok(())
         } else {
-            panic!("Folded on an inactive player!")
+            Err(TableError::InactivePlayer(self.id))
         }
     }
 
@@ -169,8 +276,32 @@ impl Player {
         self.id
     }
 
-    /// Increases the bet of the player, returns how much the player increased their money into the pot
-    pub fn bet(&mut self, bet: i32) -> i32 {
+    /// How many chips the player has won (or lost if negative) compared to what they bought in for
+    pub fn get_net_chips(&self) -> i32 {
+        self.total_money - self.total_buy_in
+    }
+
+    /// Re-enters a busted player with a fresh stack on top of whatever they had left
+    pub fn rebuy(&mut self, stack: i32) {
+        self.total_money += stack;
+        self.total_buy_in += stack;
+        self.rebuy_count += 1;
+    }
+
+    /// Gives the player the one time add-on at the end of a rebuy period
+    pub fn add_on(&mut self, chips: i32) {
+        self.total_money += chips;
+        self.total_buy_in += chips;
+    }
+
+    /// Changes the players id, used when they change seats
+    pub fn set_id(&mut self, id: i8) {
+        self.id = id;
+    }
+
+    /// Increases the bet of the player, returns how much the player increased their money into the pot.
+    /// A player that isn't in the hand can't bet
+    pub fn bet(&mut self, bet: i32) -> Result<i32, TableError> {
         self.has_had_turn_this_round = true;
         if let Active(a) = &mut self.player_state {
             if bet >= self.total_money {
@@ -178,15 +309,15 @@ impl Player {
                 let all_in_amount = self.total_money;
                 a.current_bet += all_in_amount;
                 self.total_money -= all_in_amount;
-                all_in_amount
+                Ok(all_in_amount)
             } else {
                 // Normal bet occurred
                 a.current_bet += bet;
                 self.total_money -= bet;
-                bet
+                Ok(bet)
             }
         } else {
-            panic!("Betting in a non-active state is illegal!")
+            Err(TableError::InactivePlayer(self.id))
         }
     }
 
@@ -207,7 +338,10 @@ mod tests {
     use rand::seq::SliceRandom;
     use rand::thread_rng;
 
-    use crate::player_components::{ActiveState, Player, PlayerState, DEFAULT_START_MONEY};
+    use crate::player_components::{
+        ActiveState, HoleCards, Player, PlayerState, DEFAULT_START_MONEY,
+    };
+    use crate::table::TableError;
 
     #[test]
     fn test_state_json_folded() {
@@ -220,7 +354,7 @@ mod tests {
     fn test_state_json_active() {
         let json = {
             let mut player = Player::new(0);
-            player.deal([
+            player.deal(&[
                 Card::new(Rank::Ace, Suit::Clubs),
                 Card::new(Rank::Ace, Suit::Hearts),
             ]);
@@ -241,7 +375,7 @@ mod tests {
         const BET_AMOUNT: i32 = DEFAULT_START_MONEY / 2;
         let mut player = Player::new(0);
         assert_eq!(player.total_money, DEFAULT_START_MONEY);
-        player.deal([
+        player.deal(&[
             Card::new(Rank::Ace, Suit::Clubs),
             Card::new(Rank::Ace, Suit::Hearts),
         ]);
@@ -252,10 +386,10 @@ mod tests {
             panic!("After deal player wasn't active")
         }
         // Add a bet now
-        player.bet(BET_AMOUNT);
+        player.bet(BET_AMOUNT).unwrap();
         assert!(player.has_had_turn_this_round);
         // Fold and check the player goes to back to folded
-        player.fold();
+        player.fold().unwrap();
         match player.player_state {
             PlayerState::Folded => {}
             _ => {
@@ -270,7 +404,7 @@ mod tests {
     fn test_player_dead() {
         let mut player = Player::new(0);
         assert_eq!(player.total_money, DEFAULT_START_MONEY);
-        player.deal([
+        player.deal(&[
             Card::new(Rank::Ace, Suit::Clubs),
             Card::new(Rank::Ace, Suit::Hearts),
         ]);
@@ -283,7 +417,7 @@ mod tests {
             panic!("After deal player wasn't active");
         }
         // Fold and check the player goes to back to inactive
-        player.fold();
+        player.fold().unwrap();
         // Indicate they are dead by setting the round they died
         player.death_hand_number = Some(0);
         // Check they are dead now
@@ -292,28 +426,27 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
     fn fold_twice() {
         let mut player = Player::new(0);
-        player.deal([
+        player.deal(&[
             Card::new(Rank::Ace, Suit::Clubs),
             Card::new(Rank::Ace, Suit::Hearts),
         ]);
-        player.fold();
-        player.fold();
+        player.fold().unwrap();
+        assert_eq!(player.fold(), Err(TableError::InactivePlayer(0)));
     }
 
     #[test]
     fn bet_check_normal() {
         let mut player = Player::new(0);
-        player.deal([
+        player.deal(&[
             Card::new(Rank::Ace, Suit::Clubs),
             Card::new(Rank::Ace, Suit::Hearts),
         ]);
         const BET_AMOUNT: i32 = 20;
-        assert_eq!(player.bet(BET_AMOUNT), BET_AMOUNT);
-        assert_eq!(player.bet(BET_AMOUNT), BET_AMOUNT);
-        assert_eq!(player.bet(BET_AMOUNT), BET_AMOUNT);
+        assert_eq!(player.bet(BET_AMOUNT).unwrap(), BET_AMOUNT);
+        assert_eq!(player.bet(BET_AMOUNT).unwrap(), BET_AMOUNT);
+        assert_eq!(player.bet(BET_AMOUNT).unwrap(), BET_AMOUNT);
         if let PlayerState::Active(a) = player.player_state {
             assert_eq!(a.current_bet, BET_AMOUNT * 3)
         } else {
@@ -324,18 +457,24 @@ mod tests {
     #[test]
     fn bet_all_in() {
         let mut player = Player::new(0);
-        player.deal([
+        player.deal(&[
             Card::new(Rank::Ace, Suit::Clubs),
             Card::new(Rank::Ace, Suit::Hearts),
         ]);
-        assert_eq!(player.bet(DEFAULT_START_MONEY + 3), DEFAULT_START_MONEY);
+        assert_eq!(
+            player.bet(DEFAULT_START_MONEY + 3).unwrap(),
+            DEFAULT_START_MONEY
+        );
         let mut player = Player::new(0);
-        player.deal([
+        player.deal(&[
             Card::new(Rank::Ace, Suit::Clubs),
             Card::new(Rank::Ace, Suit::Hearts),
         ]);
-        assert_eq!(player.bet(DEFAULT_START_MONEY), DEFAULT_START_MONEY);
-        assert_eq!(player.bet(DEFAULT_START_MONEY), 0);
+        assert_eq!(
+            player.bet(DEFAULT_START_MONEY).unwrap(),
+            DEFAULT_START_MONEY
+        );
+        assert_eq!(player.bet(DEFAULT_START_MONEY).unwrap(), 0);
         if let PlayerState::Active(a) = player.player_state {
             assert_eq!(a.current_bet, DEFAULT_START_MONEY);
         } else {
@@ -346,11 +485,14 @@ mod tests {
     #[test]
     fn bet_all_in_1_bet() {
         let mut player = Player::new(0);
-        player.deal([
+        player.deal(&[
             Card::new(Rank::Ace, Suit::Clubs),
             Card::new(Rank::Ace, Suit::Hearts),
         ]);
-        assert_eq!(player.bet(DEFAULT_START_MONEY), DEFAULT_START_MONEY);
+        assert_eq!(
+            player.bet(DEFAULT_START_MONEY).unwrap(),
+            DEFAULT_START_MONEY
+        );
         if let PlayerState::Active(a) = player.player_state {
             assert_eq!(a.current_bet, DEFAULT_START_MONEY);
         } else {
@@ -359,19 +501,21 @@ mod tests {
     }
 
     #[test]
-    #[should_panic]
     fn bet_inactive() {
         let mut player = Player::new(0);
-        player.bet(DEFAULT_START_MONEY);
+        assert_eq!(
+            player.bet(DEFAULT_START_MONEY),
+            Err(TableError::InactivePlayer(0))
+        );
     }
 
     #[test]
     fn test_player_state_string_active() {
         let state = PlayerState::Active(ActiveState {
-            hand: [
+            hand: HoleCards::new(&[
                 Card::new(Rank::Ace, Suit::Clubs),
                 Card::new(Rank::Ace, Suit::Hearts),
-            ],
+            ]),
             current_bet: 30,
         });
         let string_version = state.to_string();
@@ -399,11 +543,11 @@ mod tests {
     #[test]
     fn test_player_string() {
         let mut player = Player::new(0);
-        player.deal([
+        player.deal(&[
             Card::new(Rank::Ace, Suit::Clubs),
             Card::new(Rank::Ace, Suit::Hearts),
         ]);
-        player.bet(DEFAULT_START_MONEY);
+        player.bet(DEFAULT_START_MONEY).unwrap();
         let string_version = player.to_string();
         let json_parsed_string = json::parse(&string_version).unwrap().dump();
         assert_eq!(
@@ -421,11 +565,14 @@ mod tests {
     #[test]
     fn test_no_cards_in_secret() {
         let mut player = Player::new(0);
-        player.deal([
+        player.deal(&[
             Card::new(Rank::Ace, Suit::Clubs),
             Card::new(Rank::Ace, Suit::Hearts),
         ]);
-        assert_eq!(player.bet(DEFAULT_START_MONEY), DEFAULT_START_MONEY);
+        assert_eq!(
+            player.bet(DEFAULT_START_MONEY).unwrap(),
+            DEFAULT_START_MONEY
+        );
         let string_version = player.as_json_no_secret_data().to_string();
         let json_parsed_string = json::parse(&string_version).unwrap().dump();
         assert_eq!(
@@ -466,11 +613,52 @@ mod tests {
         assert!(player1 < player2);
         assert!(player2 > player1);
         assert!(player1 == player1);
+        // Alive players with different stacks aren't tied
+        assert!(player1 != player2);
         assert!(player_dead < player1);
         assert!(player1 > player_dead);
         assert!(player_dead == player_dead);
     }
 
+    #[test]
+    fn test_same_hand_death_order() {
+        let mut player1 = Player::new(0);
+        let mut player2 = Player::new(1);
+        player1.death_hand_number = Some(7);
+        player2.death_hand_number = Some(7);
+        // Same stack at the start of the hand is a real tie
+        assert!(player1 == player2);
+        player1.hand_starting_money = 40;
+        player2.hand_starting_money = 25;
+        assert!(player1 > player2);
+        assert!(player1 != player2);
+        // Busting on a later hand still matters more
+        player2.death_hand_number = Some(8);
+        assert!(player1 < player2);
+    }
+
+    #[test]
+    fn test_rebuy_player_order() {
+        let mut player1 = Player::new(0);
+        let mut player2 = Player::new(1);
+        player1.rebuy(DEFAULT_START_MONEY);
+        player1.total_money = 100;
+        player2.total_money = 100;
+        // Same chips, but player1 needed a second entry to get them
+        assert!(player1 < player2);
+        assert!(player1 != player2);
+        assert_eq!(player1.total_buy_in, DEFAULT_START_MONEY * 2);
+        // Same death hand, the rebuy still counts against player1
+        player1.death_hand_number = Some(4);
+        player2.death_hand_number = Some(4);
+        assert!(player1 < player2);
+        // Lasting longer still matters more than the rebuys used
+        player1.death_hand_number = Some(5);
+        assert!(player1 > player2);
+        player1.add_on(200);
+        assert_eq!(player1.total_buy_in, DEFAULT_START_MONEY * 2 + 200);
+    }
+
     #[test]
     fn test_secret_folded() {
         // Make a player that has folded their hand
@@ -0,0 +1,168 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::actions::HandAction;
+use crate::table::{Table, TableConfig};
+
+/// Everything needed to play a single table game again exactly as it was played
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct Replay {
+    /// How many players sat at the table
+    pub player_count: usize,
+    /// The config of the table, including the seed every deck was shuffled from
+    pub config: TableConfig,
+    /// Every action applied to the table, in order
+    pub actions: Vec<HandAction>,
+    /// The results the game finished with
+    pub results: String,
+}
+
+impl Replay {
+    /// Records a game from its table and the actions that were applied to it
+    pub fn new(table: &Table, actions: Vec<HandAction>) -> Self {
+        Replay {
+            player_count: table.get_player_count(),
+            config: table.get_config().clone(),
+            actions,
+            results: table.get_results(),
+        }
+    }
+
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| format!("Couldn't read the replay {}: {error}", path.display()))?;
+        serde_json::from_str(&contents)
+            .map_err(|error| format!("Couldn't parse the replay: {error}"))
+    }
+
+    pub fn write_to_file(&self, path: &Path) -> Result<(), String> {
+        let contents = serde_json::to_string(self)
+            .map_err(|error| format!("Couldn't serialize the replay: {error}"))?;
+        std::fs::write(path, contents)
+            .map_err(|error| format!("Couldn't write the replay {}: {error}", path.display()))
+    }
+
+    /// Plays the recorded actions back through a new table. With a hand to stop at the table is returned
+    /// as soon as that hand is dealt, otherwise every action is played and the results must match the recorded ones.
+    pub fn play(&self, stop_at_hand: Option<i32>) -> Result<Table, String> {
+        let mut config = self.config.clone();
+        // Playing the game back shouldn't add its hands to the hand history or database again
+        config.hand_history_file = None;
+        config.database_file = None;
+        let mut table =
+            Table::with_config(self.player_count, config).map_err(|error| error.to_string())?;
+        for (index, action) in self.actions.iter().enumerate() {
+            if stop_at_hand.is_some_and(|hand| table.get_hand_number() >= hand) {
+                return Ok(table);
+            }
+            if table.is_game_over() {
+                return Err(format!(
+                    "The game was over after {index} of the {} recorded actions",
+                    self.actions.len()
+                ));
+            }
+            // A failed action voided its hand when the game was played, so it does here too
+            table
+                .take_action(*action)
+                .or_else(|_| table.void_hand())
+                .map_err(|error| format!("Couldn't void the hand at action {index}: {error}"))?;
+        }
+        if let Some(hand) = stop_at_hand {
+            if table.get_hand_number() < hand {
+                return Err(format!(
+                    "The game ended on hand {} before hand {hand}",
+                    table.get_hand_number()
+                ));
+            }
+            return Ok(table);
+        }
+        let results = table.get_results();
+        if results != self.results {
+            return Err(format!(
+                "The results don't match, the recorded results were:\n{}\nbut the replay finished with:\n{results}",
+                self.results
+            ));
+        }
+        Ok(table)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::actions::HandAction;
+    use crate::replay::Replay;
+    use crate::table::{GameVariant, StopConditions, Table, TableConfig};
+
+    /// Plays a game with random actions and records it
+    fn record_game(config: TableConfig) -> Replay {
+        let mut table = Table::with_config(4, config).unwrap();
+        let mut actions = vec![];
+        while !table.is_game_over() {
+            let action = match thread_rng().gen_range(0..4) {
+                0 => HandAction::Fold,
+                1 => HandAction::Check,
+                2 => HandAction::Call,
+                _ => HandAction::Raise(thread_rng().gen_range(1..50)),
+            };
+            table.take_action(action).unwrap();
+            actions.push(action);
+        }
+        Replay::new(&table, actions)
+    }
+
+    #[test]
+    fn test_replay_matches() {
+        for variant in [GameVariant::TexasHoldem, GameVariant::SevenCardStud] {
+            let replay = record_game(TableConfig {
+                variant,
+                run_it_times: 2,
+                all_in_adjusted: true,
+                ..TableConfig::default()
+            });
+            assert!(replay.config.seed.is_some());
+            let table = replay.play(None).unwrap();
+            assert!(table.is_game_over());
+        }
+    }
+
+    #[test]
+    fn test_replay_stops_at_hand() {
+        let replay = record_game(TableConfig {
+            stop_conditions: StopConditions {
+                max_hands: Some(20),
+                ..StopConditions::default()
+            },
+            ..TableConfig::default()
+        });
+        let table = replay.play(Some(5)).unwrap();
+        assert_eq!(table.get_hand_number(), 5);
+        assert!(!table.is_game_over());
+        assert!(replay.play(Some(30)).is_err());
+    }
+
+    #[test]
+    fn test_replay_mismatch() {
+        let mut replay = record_game(TableConfig::default());
+        // A different deck order plays out differently
+        replay.config.seed = replay.config.seed.map(|seed| seed.wrapping_add(1));
+        assert!(replay.play(None).is_err());
+        let mut replay = record_game(TableConfig::default());
+        replay.results += "Extra line\n";
+        let error = replay.play(None).err().unwrap();
+        assert!(error.starts_with("The results don't match"));
+    }
+
+    #[test]
+    fn test_replay_file() {
+        let replay = record_game(TableConfig::default());
+        let path = std::env::temp_dir().join("bot_arena_test_replay.json");
+        replay.write_to_file(&path).unwrap();
+        let read_replay = Replay::from_file(&path).unwrap();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(read_replay, replay);
+        assert!(Replay::from_file(&path).is_err());
+    }
+}
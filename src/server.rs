@@ -4,10 +4,14 @@ use std::time::Duration;
 use log::info;
 use tokio::net::{TcpListener, TcpStream};
 use tokio::time::timeout;
+use tokio_tungstenite::tungstenite::handshake::server::{Request, Response};
 use tokio_tungstenite::WebSocketStream;
 
 pub struct Server {
     pub connections: Vec<WebSocketStream<TcpStream>>,
+    /// Who each connection is, the path a bot connected to such as `ws://host:port/my_bot`, or its ip address
+    /// without a path. Used to seat reconnecting bots where they were sitting before.
+    pub identities: Vec<String>,
 }
 
 impl Server {
@@ -23,6 +27,7 @@ impl Server {
         info!("Listening on: {}", server_address);
         info!("Will try to listen for: {:?}", wait_duration);
         let mut connections = vec![];
+        let mut identities = vec![];
 
         let mut addresses = HashSet::new();
 
@@ -39,9 +44,21 @@ impl Server {
                         .peer_addr()
                         .expect("connected streams should have a peer address");
 
-                    let ws_stream = tokio_tungstenite::accept_async(stream)
+                    // Remember the path the bot connected to, the handshake decides the error type
+                    let mut path = String::new();
+                    #[allow(clippy::result_large_err)]
+                    let read_path = |request: &Request, response: Response| {
+                        path = request.uri().path().trim_matches('/').to_string();
+                        Ok(response)
+                    };
+                    let ws_stream = tokio_tungstenite::accept_hdr_async(stream, read_path)
                         .await
                         .expect("Error during the websocket handshake occurred");
+                    let identity = if path.is_empty() {
+                        addr.ip().to_string()
+                    } else {
+                        path
+                    };
 
                     info!(
                         "New WebSocket connection from the following address: {}",
@@ -51,9 +68,10 @@ impl Server {
                         addresses.insert(addr.ip());
                         connections.push(ws_stream);
                         info!(
-                            "Added the following address to the list of listeners: {}",
+                            "Added the following address to the list of listeners: {}, as {identity}",
                             addr
                         );
+                        identities.push(identity);
                     } else {
                         warn!("Couldn't add the connection {addr} because there was already a connection from this host: {}", addr.ip());
                     }
@@ -68,7 +86,10 @@ impl Server {
                 }
             }
         }
-        Server { connections }
+        Server {
+            connections,
+            identities,
+        }
     }
     #[cfg(test)]
     pub async fn get_random_tcp_listener() -> TcpListener {
@@ -114,7 +135,9 @@ mod tests {
         let number_of_connections = 3;
         for i in 0..number_of_connections {
             info!("Trying to connect on iteration {i}");
-            let url = Url::parse(format!("ws://{}", ADDRESS).as_str()).unwrap();
+            // The first bot names itself, the others are known by their address
+            let path = if i == 0 { "/named_bot" } else { "" };
+            let url = Url::parse(format!("ws://{}{path}", ADDRESS).as_str()).unwrap();
             let _ = connect_async(url).await.unwrap();
             let sleep_duration = wait_duration / number_of_connections / 2;
             info!(
@@ -135,6 +158,10 @@ mod tests {
         let server = server_handle.await.unwrap();
 
         assert_eq!(server.connections.len(), number_of_connections as usize); // 3 connections should be accepted
+        assert_eq!(
+            server.identities,
+            vec!["named_bot", "127.0.0.1", "127.0.0.1"]
+        );
     }
 
     #[tokio::test]
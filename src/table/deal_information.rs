@@ -1,7 +1,9 @@
 use std::fmt;
 use std::fmt::Formatter;
 
-#[derive(Clone, Eq, PartialEq, Debug)]
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Deserialize, Eq, PartialEq, Debug, Serialize)]
 pub struct DealInformation {
     pub round_number: i32,
     pub dealer_button_index: usize,
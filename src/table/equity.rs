@@ -0,0 +1,296 @@
+use std::collections::HashSet;
+
+use poker::{Card, Evaluator};
+use rand::seq::SliceRandom;
+use rand::Rng;
+
+use crate::table::game_variant::{get_combinations, HandStrength};
+use crate::table::{GameVariant, LowHand};
+
+/// The most ways of finishing the board that are each checked, with more than this random boards are sampled
+pub const EXACT_BOARD_LIMIT: usize = 2000;
+/// How many random boards are sampled by default when there are too many to check each one
+pub const DEFAULT_MONTE_CARLO_SAMPLES: usize = 5000;
+
+/// A pot and the seats of the players still in the hand that can win it
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Pot {
+    pub amount: i32,
+    pub eligible_seats: Vec<usize>,
+}
+
+/// The cards that were dealt when everyone went all in
+pub struct KnownCards<'a> {
+    /// Each seat's hole cards, or None if it isn't in the hand
+    pub hands: &'a [Option<Vec<Card>>],
+    /// The board cards already shown
+    pub board: &'a [Card],
+    /// Any other cards already dealt, such as the hands of players that folded, they can't come on the board
+    pub dead_cards: &'a [Card],
+}
+
+/// Gets how many chips each seat would win on average if the rest of the board was dealt every possible way.
+/// Every way of finishing the board is checked when there are few enough of them, otherwise the average is over
+/// the given number of random boards from the given generator.
+pub fn get_expected_winnings(
+    evaluator: &Evaluator,
+    variant: GameVariant,
+    known: &KnownCards,
+    pots: &[Pot],
+    samples: usize,
+    rng: &mut impl Rng,
+) -> Vec<f64> {
+    let KnownCards {
+        hands,
+        board,
+        dead_cards,
+    } = *known;
+    let mut known_cards: HashSet<Card> = board.iter().chain(dead_cards).copied().collect();
+    for hand in hands.iter().flatten() {
+        known_cards.extend(hand);
+    }
+    let unseen_cards: Vec<Card> = variant
+        .generate_shuffled_deck(rng)
+        .into_iter()
+        .filter(|card| !known_cards.contains(card))
+        .collect();
+    let cards_needed = variant.get_board_card_count() - board.len();
+    let run_outs = if get_combination_count(unseen_cards.len(), cards_needed) <= EXACT_BOARD_LIMIT {
+        get_combinations(&unseen_cards, cards_needed)
+    } else {
+        (0..samples)
+            .map(|_| {
+                unseen_cards
+                    .choose_multiple(rng, cards_needed)
+                    .copied()
+                    .collect()
+            })
+            .collect()
+    };
+    let mut winnings = vec![0.0; hands.len()];
+    for run_out in &run_outs {
+        let mut full_board = board.to_vec();
+        full_board.extend(run_out);
+        add_winnings(evaluator, variant, hands, &full_board, pots, &mut winnings);
+    }
+    winnings
+        .into_iter()
+        .map(|total| total / run_outs.len() as f64)
+        .collect()
+}
+
+/// Adds what each seat wins on a finished board, splitting ties and high and low halves like the table does
+fn add_winnings(
+    evaluator: &Evaluator,
+    variant: GameVariant,
+    hands: &[Option<Vec<Card>>],
+    board: &[Card],
+    pots: &[Pot],
+    winnings: &mut [f64],
+) {
+    let strengths: Vec<Option<HandStrength>> = hands
+        .iter()
+        .map(|hand| {
+            hand.as_ref()
+                .map(|hand| variant.evaluate(evaluator, hand, board))
+        })
+        .collect();
+    let lows: Vec<Option<LowHand>> = hands
+        .iter()
+        .map(|hand| {
+            hand.as_ref()
+                .and_then(|hand| variant.evaluate_low(hand, board))
+        })
+        .collect();
+    for pot in pots {
+        let best_high = pot
+            .eligible_seats
+            .iter()
+            .map(|seat| strengths[*seat])
+            .max()
+            .flatten();
+        let high_winners: Vec<usize> = pot
+            .eligible_seats
+            .iter()
+            .copied()
+            .filter(|seat| strengths[*seat] == best_high)
+            .collect();
+        let best_low = pot
+            .eligible_seats
+            .iter()
+            .filter_map(|seat| lows[*seat])
+            .min();
+        let low_winners: Vec<usize> = pot
+            .eligible_seats
+            .iter()
+            .copied()
+            .filter(|seat| best_low.is_some() && lows[*seat] == best_low)
+            .collect();
+        // The odd chip of a split pot goes to the high hand
+        let low_amount = if low_winners.is_empty() {
+            0
+        } else {
+            pot.amount / 2
+        };
+        for (amount, winners) in [
+            (pot.amount - low_amount, high_winners),
+            (low_amount, low_winners),
+        ] {
+            for seat in &winners {
+                winnings[*seat] += amount as f64 / winners.len() as f64;
+            }
+        }
+    }
+}
+
+/// Counts the ways to pick `size` of `count` cards, stopping once it is clear there are too many to check
+fn get_combination_count(count: usize, size: usize) -> usize {
+    let mut combinations: usize = 1;
+    for picked in 0..size {
+        combinations = combinations * (count - picked) / (picked + 1);
+        if combinations > EXACT_BOARD_LIMIT {
+            return combinations;
+        }
+    }
+    combinations
+}
+
+#[cfg(test)]
+mod tests {
+    use poker::{cards, Card};
+    use rand::thread_rng;
+
+    use crate::global_immutables::SHARED_EVALUATOR;
+    use crate::table::equity::{
+        get_combination_count, get_expected_winnings, KnownCards, Pot, DEFAULT_MONTE_CARLO_SAMPLES,
+    };
+    use crate::table::GameVariant;
+
+    fn parse(cards: &str) -> Vec<Card> {
+        cards!(cards).try_collect().unwrap()
+    }
+
+    fn get_heads_up_winnings(hands: [&str; 2], board: &str, amount: i32) -> Vec<f64> {
+        get_expected_winnings(
+            &SHARED_EVALUATOR,
+            GameVariant::TexasHoldem,
+            &KnownCards {
+                hands: &[Some(parse(hands[0])), Some(parse(hands[1]))],
+                board: &parse(board),
+                dead_cards: &[],
+            },
+            &[Pot {
+                amount,
+                eligible_seats: vec![0, 1],
+            }],
+            DEFAULT_MONTE_CARLO_SAMPLES,
+            &mut thread_rng(),
+        )
+    }
+
+    #[test]
+    fn test_combination_count() {
+        assert_eq!(get_combination_count(45, 2), 990);
+        assert_eq!(get_combination_count(44, 1), 44);
+        assert_eq!(get_combination_count(10, 0), 1);
+        assert!(get_combination_count(48, 5) > super::EXACT_BOARD_LIMIT);
+    }
+
+    #[test]
+    fn test_exact_equity_on_the_flop() {
+        // Kings need one of the two kings left in 45 cards, which comes in 87 of the 990 turns and rivers,
+        // but in 4 of those an ace comes too
+        let winnings = get_heads_up_winnings(["As Ad", "Ks Kd"], "2c 7d 9h", 990);
+        assert!((winnings[0] - 907.0).abs() < 1e-6);
+        assert!((winnings[1] - 83.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_ties_split_the_pot() {
+        // The board plays for both players every time
+        let winnings = get_heads_up_winnings(["2c 3d", "2d 3c"], "Ts Js Qs Ks", 100);
+        assert_eq!(winnings, vec![50.0, 50.0]);
+    }
+
+    #[test]
+    fn test_monte_carlo_equity_before_the_flop() {
+        // Aces win about 82% of the time against kings
+        let winnings = get_heads_up_winnings(["As Ad", "Ks Kd"], "", 100);
+        assert!((winnings[0] - 82.0).abs() < 4.0, "{winnings:?}");
+        assert!((winnings[0] + winnings[1] - 100.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_side_pots() {
+        // Player 2 has the best hand but is only in the main pot, player 0 beats player 1 for the side pot
+        let winnings = get_expected_winnings(
+            &SHARED_EVALUATOR,
+            GameVariant::TexasHoldem,
+            &KnownCards {
+                hands: &[
+                    Some(parse("Qs Qd")),
+                    Some(parse("Js Jd")),
+                    Some(parse("As Ad")),
+                    None,
+                ],
+                board: &parse("2c 7d 9h 3s 4c"),
+                dead_cards: &[],
+            },
+            &[
+                Pot {
+                    amount: 30,
+                    eligible_seats: vec![0, 1, 2],
+                },
+                Pot {
+                    amount: 20,
+                    eligible_seats: vec![0, 1],
+                },
+            ],
+            DEFAULT_MONTE_CARLO_SAMPLES,
+            &mut thread_rng(),
+        );
+        assert_eq!(winnings, vec![20.0, 0.0, 30.0, 0.0]);
+    }
+
+    #[test]
+    fn test_hi_lo_equity() {
+        // Player 0 always has the best high and player 1 the best low on this river
+        let winnings = get_expected_winnings(
+            &SHARED_EVALUATOR,
+            GameVariant::OmahaHiLo,
+            &KnownCards {
+                hands: &[Some(parse("Kc Kd 9h 9c")), Some(parse("2d 4s Jc Jd"))],
+                board: &parse("Ah 3c 6d Ks Qs"),
+                dead_cards: &[],
+            },
+            &[Pot {
+                amount: 11,
+                eligible_seats: vec![0, 1],
+            }],
+            DEFAULT_MONTE_CARLO_SAMPLES,
+            &mut thread_rng(),
+        );
+        assert_eq!(winnings, vec![6.0, 5.0]);
+    }
+
+    #[test]
+    fn test_folded_cards_are_dead() {
+        // Both kings left were folded, so the aces always hold
+        let winnings = get_expected_winnings(
+            &SHARED_EVALUATOR,
+            GameVariant::TexasHoldem,
+            &KnownCards {
+                hands: &[Some(parse("As Ad")), Some(parse("Ks Kd")), None],
+                board: &parse("2c 7d 9h"),
+                dead_cards: &parse("Kc Kh"),
+            },
+            &[Pot {
+                amount: 100,
+                eligible_seats: vec![0, 1],
+            }],
+            DEFAULT_MONTE_CARLO_SAMPLES,
+            &mut thread_rng(),
+        );
+        assert_eq!(winnings, vec![100.0, 0.0, 0.0]);
+    }
+}
@@ -0,0 +1,745 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+use clap::ValueEnum;
+use poker::{Card, Eval, EvalClass, Evaluator, Rank, Suit};
+use rand::seq::SliceRandom;
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+use crate::bet_stage::BetStage;
+
+/// The poker game played at a table, it decides how many cards are dealt and how hands are made
+#[derive(ValueEnum, Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum GameVariant {
+    /// Two hole cards, hands are made from any five of the hole and board cards
+    #[default]
+    TexasHoldem,
+    /// Four hole cards, hands are made from exactly two hole cards and three board cards
+    PotLimitOmaha,
+    /// Omaha where each pot is split between the best high hand and the best eight or better low hand
+    OmahaHiLo,
+    /// Hold'em without the twos through fives, a flush beats a full house and A-6-7-8-9 is a straight
+    ShortDeckHoldem,
+    /// A jack, queen and king, one card each and a single round of fixed limit betting, the highest card wins
+    KuhnPoker,
+    /// Two jacks, queens and kings, one card each and one on the board after the first of two fixed limit
+    /// betting rounds, pairing the board beats a higher card
+    LeducHoldem,
+    /// Seven cards each, three face down and four face up, with no board and a bring-in from the lowest up card
+    SevenCardStud,
+}
+
+/// A round of betting and the cards dealt right before it
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct Street {
+    pub stage: BetStage,
+    /// Cards dealt face down to each player, only they can see them
+    pub down_cards: usize,
+    /// Cards dealt face up to each player, after any face down cards of the street
+    pub up_cards: usize,
+    /// Cards dealt face up to the table for everyone to use
+    pub board_cards: usize,
+}
+
+impl Street {
+    const fn new(stage: BetStage, down_cards: usize, up_cards: usize, board_cards: usize) -> Self {
+        Street {
+            stage,
+            down_cards,
+            up_cards,
+            board_cards,
+        }
+    }
+}
+
+const HOLDEM_STREETS: [Street; 4] = [
+    Street::new(BetStage::PreFlop, 2, 0, 0),
+    Street::new(BetStage::Flop, 0, 0, 3),
+    Street::new(BetStage::Turn, 0, 0, 1),
+    Street::new(BetStage::River, 0, 0, 1),
+];
+const OMAHA_STREETS: [Street; 4] = [
+    Street::new(BetStage::PreFlop, 4, 0, 0),
+    Street::new(BetStage::Flop, 0, 0, 3),
+    Street::new(BetStage::Turn, 0, 0, 1),
+    Street::new(BetStage::River, 0, 0, 1),
+];
+const KUHN_STREETS: [Street; 1] = [Street::new(BetStage::PreFlop, 1, 0, 0)];
+const LEDUC_STREETS: [Street; 2] = [
+    Street::new(BetStage::PreFlop, 1, 0, 0),
+    Street::new(BetStage::Flop, 0, 0, 1),
+];
+const STUD_STREETS: [Street; 5] = [
+    Street::new(BetStage::ThirdStreet, 2, 1, 0),
+    Street::new(BetStage::FourthStreet, 0, 1, 0),
+    Street::new(BetStage::FifthStreet, 0, 1, 0),
+    Street::new(BetStage::SixthStreet, 0, 1, 0),
+    Street::new(BetStage::SeventhStreet, 1, 0, 0),
+];
+
+/// The bet sizes of a fixed limit game, a raise is always one bet no matter the amount asked for
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct FixedLimit {
+    /// The size of a bet in each betting stage, starting with the first
+    pub bet_sizes: &'static [i32],
+    /// How many bets and raises can be made in a betting stage, raises past it are calls
+    pub max_bets: usize,
+}
+
+/// How strong a made hand is, only comparable to hands of the same game variant
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct HandStrength {
+    /// How the hand's category, such as a flush or full house, ranks in the game played
+    pub category_rank: u8,
+    /// The standard evaluation of the hand, it orders hands of the same category. It is the worst evaluation in
+    /// games with too few cards to make a five card hand
+    pub eval: Eval,
+    /// The rank of the hole card in games with too few cards to make a five card hand, None otherwise
+    pub high_card: Option<Rank>,
+}
+
+impl HandStrength {
+    /// Ranks a hand by the standard hand rankings
+    fn new(eval: Eval) -> Self {
+        let category_rank = match eval.class() {
+            EvalClass::HighCard { .. } => 0,
+            EvalClass::Pair { .. } => 1,
+            EvalClass::TwoPair { .. } => 2,
+            EvalClass::ThreeOfAKind { .. } => 3,
+            EvalClass::Straight { .. } => 4,
+            EvalClass::Flush { .. } => 5,
+            EvalClass::FullHouse { .. } => 6,
+            EvalClass::FourOfAKind { .. } => 7,
+            EvalClass::StraightFlush { .. } => 8,
+        };
+        HandStrength {
+            category_rank,
+            eval,
+            high_card: None,
+        }
+    }
+
+    /// Ranks a single hole card by its rank, or as a pair if it pairs a board card
+    fn new_single_card(hole_card: Card, board: &[Card]) -> Self {
+        let is_pair = board.iter().any(|card| card.rank() == hole_card.rank());
+        HandStrength {
+            category_rank: if is_pair { 1 } else { 0 },
+            eval: Eval::WORST,
+            high_card: Some(hole_card.rank()),
+        }
+    }
+
+    /// Ranks five cards by the short deck hand rankings
+    fn new_short_deck(evaluator: &Evaluator, cards: &[Card]) -> Self {
+        let mut ranks: Vec<Rank> = cards.iter().map(|card| card.rank()).collect();
+        ranks.sort_unstable();
+        // With the aces playing low A-6-7-8-9 is the lowest straight, so it gets the worst evaluation
+        if ranks == [Rank::Six, Rank::Seven, Rank::Eight, Rank::Nine, Rank::Ace] {
+            let is_flush = cards.iter().all(|card| card.suit() == cards[0].suit());
+            return HandStrength {
+                category_rank: if is_flush { 8 } else { 4 },
+                eval: Eval::WORST,
+                high_card: None,
+            };
+        }
+        let mut strength = HandStrength::new(evaluate(evaluator, cards));
+        // Flushes are harder to make than full houses with fewer cards of each suit
+        strength.category_rank = match strength.category_rank {
+            5 => 6,
+            6 => 5,
+            category_rank => category_rank,
+        };
+        strength
+    }
+}
+
+/// A low hand of five different ranks of eight or lower, aces are low and straights and flushes don't count
+#[derive(Clone, Copy, Debug, Eq, Ord, PartialEq, PartialOrd)]
+pub struct LowHand {
+    /// The ranks from highest to lowest with an ace as 1, so a smaller low hand is a better one
+    ranks: [u8; 5],
+}
+
+impl LowHand {
+    /// The highest rank a low hand may have to qualify
+    pub const QUALIFIER: u8 = 8;
+
+    /// Makes the low hand of five cards, None if they don't qualify
+    fn new(cards: &[Card]) -> Option<Self> {
+        let mut ranks: Vec<u8> = cards
+            .iter()
+            .map(|card| match card.rank() {
+                Rank::Ace => 1,
+                rank => rank as u8 + 2,
+            })
+            .collect();
+        ranks.sort_unstable_by(|a, b| b.cmp(a));
+        ranks.dedup();
+        if ranks.len() != 5 || ranks[0] > LowHand::QUALIFIER {
+            return None;
+        }
+        Some(LowHand {
+            ranks: ranks.try_into().unwrap(),
+        })
+    }
+}
+
+impl fmt::Display for LowHand {
+    /// Shows the ranks from highest to lowest, such as 8-6-4-2-A
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let ranks: Vec<String> = self
+            .ranks
+            .iter()
+            .map(|rank| match rank {
+                1 => "A".to_string(),
+                rank => rank.to_string(),
+            })
+            .collect();
+        write!(f, "{}", ranks.join("-"))
+    }
+}
+
+impl GameVariant {
+    /// The rounds of betting in a hand in order, and the cards dealt before each
+    pub fn get_streets(&self) -> &'static [Street] {
+        match self {
+            GameVariant::TexasHoldem | GameVariant::ShortDeckHoldem => &HOLDEM_STREETS,
+            GameVariant::PotLimitOmaha | GameVariant::OmahaHiLo => &OMAHA_STREETS,
+            GameVariant::KuhnPoker => &KUHN_STREETS,
+            GameVariant::LeducHoldem => &LEDUC_STREETS,
+            GameVariant::SevenCardStud => &STUD_STREETS,
+        }
+    }
+
+    /// Gets the streets up to and including the given betting stage
+    fn get_streets_through(&self, stage: BetStage) -> &'static [Street] {
+        let streets = self.get_streets();
+        &streets[..=self.get_street_index(stage)]
+    }
+
+    /// Where the betting stage is in the order of streets, 0 INDEXED
+    pub fn get_street_index(&self, stage: BetStage) -> usize {
+        self.get_streets()
+            .iter()
+            .position(|street| street.stage == stage)
+            .expect("The betting stage isn't played in this game")
+    }
+
+    /// The betting stage a hand starts in
+    pub fn get_first_stage(&self) -> BetStage {
+        self.get_streets()[0].stage
+    }
+
+    /// The betting stage after the given one, None after the last
+    pub fn get_next_stage(&self, stage: BetStage) -> Option<BetStage> {
+        self.get_streets()
+            .get(self.get_street_index(stage) + 1)
+            .map(|street| street.stage)
+    }
+
+    /// How many private cards each player is dealt over the whole hand, face up or down
+    pub fn get_hole_card_count(&self) -> usize {
+        self.get_streets()
+            .iter()
+            .map(|street| street.down_cards + street.up_cards)
+            .sum()
+    }
+
+    /// How many of their cards each player has been dealt by a betting stage
+    pub fn get_hole_cards_dealt(&self, stage: BetStage) -> usize {
+        self.get_streets_through(stage)
+            .iter()
+            .map(|street| street.down_cards + street.up_cards)
+            .sum()
+    }
+
+    /// Gets the cards of a hand that are face up by a betting stage
+    pub fn get_up_cards(&self, hole_cards: &[Card], stage: BetStage) -> Vec<Card> {
+        let mut up_cards = vec![];
+        let mut dealt = 0;
+        for street in self.get_streets_through(stage) {
+            dealt += street.down_cards;
+            up_cards.extend(&hole_cards[dealt..dealt + street.up_cards]);
+            dealt += street.up_cards;
+        }
+        up_cards
+    }
+
+    /// Returns true if players are dealt cards that everyone can see
+    pub fn has_up_cards(&self) -> bool {
+        self.get_streets().iter().any(|street| street.up_cards > 0)
+    }
+
+    /// Returns true if the lowest up card brings in the betting instead of blinds being posted
+    pub fn has_bring_in(&self) -> bool {
+        *self == GameVariant::SevenCardStud
+    }
+
+    /// How many cards are dealt to the table
+    pub fn get_board_card_count(&self) -> usize {
+        self.get_streets()
+            .iter()
+            .map(|street| street.board_cards)
+            .sum()
+    }
+
+    /// How many of the board cards are face up during a betting stage
+    pub fn get_board_cards_shown(&self, stage: BetStage) -> usize {
+        self.get_streets_through(stage)
+            .iter()
+            .map(|street| street.board_cards)
+            .sum()
+    }
+
+    /// The bet sizes of fixed limit games, None for the pot limit games
+    pub fn get_fixed_limit(&self) -> Option<FixedLimit> {
+        match self {
+            GameVariant::KuhnPoker => Some(FixedLimit {
+                bet_sizes: &[1],
+                max_bets: 1,
+            }),
+            GameVariant::LeducHoldem => Some(FixedLimit {
+                bet_sizes: &[2, 4],
+                max_bets: 2,
+            }),
+            _ => None,
+        }
+    }
+
+    /// The name of the game and its betting limit as hand histories write it
+    pub fn get_hand_history_name(&self) -> String {
+        let game = match self {
+            GameVariant::TexasHoldem => "Hold'em",
+            GameVariant::PotLimitOmaha => "Omaha",
+            GameVariant::OmahaHiLo => "Omaha Hi/Lo",
+            GameVariant::ShortDeckHoldem => "6+ Hold'em",
+            GameVariant::KuhnPoker => "Kuhn Poker",
+            GameVariant::LeducHoldem => "Leduc Hold'em",
+            GameVariant::SevenCardStud => "7 Card Stud",
+        };
+        let limit = match self.get_fixed_limit() {
+            Some(_) => "Limit",
+            None => "Pot Limit",
+        };
+        format!("{game} {limit}")
+    }
+
+    /// Returns true if every pot is split between a high and a low hand
+    pub fn is_split_pot(&self) -> bool {
+        *self == GameVariant::OmahaHiLo
+    }
+
+    /// Makes a deck of the cards the game is played with, shuffled by the given random number generator
+    pub fn generate_shuffled_deck(&self, rng: &mut impl Rng) -> Vec<Card> {
+        let mut deck: Vec<Card> = match self {
+            GameVariant::ShortDeckHoldem => Card::generate_deck()
+                .filter(|card| card.rank() >= Rank::Six)
+                .collect(),
+            GameVariant::KuhnPoker => GameVariant::generate_court_cards(&[Suit::Spades]),
+            GameVariant::LeducHoldem => {
+                GameVariant::generate_court_cards(&[Suit::Spades, Suit::Hearts])
+            }
+            _ => Card::generate_deck().collect(),
+        };
+        deck.shuffle(rng);
+        deck
+    }
+
+    /// Makes the jack, queen and king of each suit
+    fn generate_court_cards(suits: &[Suit]) -> Vec<Card> {
+        let mut cards = vec![];
+        for suit in suits {
+            for rank in [Rank::Jack, Rank::Queen, Rank::King] {
+                cards.push(Card::new(rank, *suit));
+            }
+        }
+        cards
+    }
+
+    /// How many cards are in the deck the game is played with
+    pub fn get_deck_size(&self) -> usize {
+        match self {
+            GameVariant::ShortDeckHoldem => 36,
+            GameVariant::KuhnPoker => 3,
+            GameVariant::LeducHoldem => 6,
+            _ => 52,
+        }
+    }
+
+    /// The most players that can be dealt into one table from a single deck
+    pub fn get_max_players(&self) -> usize {
+        (self.get_deck_size() - self.get_board_card_count()) / self.get_hole_card_count()
+    }
+
+    /// Evaluates the best five card hand a player can make from their hole cards and the board
+    pub fn evaluate(
+        &self,
+        evaluator: &Evaluator,
+        hole_cards: &[Card],
+        board: &[Card],
+    ) -> HandStrength {
+        match self {
+            GameVariant::TexasHoldem | GameVariant::SevenCardStud => {
+                let mut cards: Vec<Card> = board.into();
+                cards.extend(hole_cards);
+                HandStrength::new(evaluate(evaluator, &cards))
+            }
+            GameVariant::ShortDeckHoldem => {
+                let mut cards: Vec<Card> = board.into();
+                cards.extend(hole_cards);
+                get_combinations(&cards, 5)
+                    .iter()
+                    .map(|hand| HandStrength::new_short_deck(evaluator, hand))
+                    .max()
+                    .unwrap()
+            }
+            GameVariant::PotLimitOmaha | GameVariant::OmahaHiLo => {
+                let mut best = HandStrength::new(Eval::WORST);
+                for hole_combination in get_combinations(hole_cards, 2) {
+                    for board_combination in get_combinations(board, 3) {
+                        let mut cards = hole_combination.clone();
+                        cards.extend(board_combination);
+                        best = best.max(HandStrength::new(evaluate(evaluator, &cards)));
+                    }
+                }
+                best
+            }
+            GameVariant::KuhnPoker | GameVariant::LeducHoldem => {
+                HandStrength::new_single_card(hole_cards[0], board)
+            }
+        }
+    }
+
+    /// Evaluates the best low hand a player can make, None if the game has no low or none qualifies
+    pub fn evaluate_low(&self, hole_cards: &[Card], board: &[Card]) -> Option<LowHand> {
+        if !self.is_split_pot() {
+            return None;
+        }
+        let mut best: Option<LowHand> = None;
+        for hole_combination in get_combinations(hole_cards, 2) {
+            for board_combination in get_combinations(board, 3) {
+                let mut cards = hole_combination.clone();
+                cards.extend(board_combination);
+                if let Some(low) = LowHand::new(&cards) {
+                    best = Some(best.map_or(low, |best| best.min(low)));
+                }
+            }
+        }
+        best
+    }
+}
+
+/// Orders the up cards of stud hands, pairs and better by how many of a rank there are and then high cards. Straights
+/// and flushes don't count since there are never five up cards
+pub fn get_showing_rank(up_cards: &[Card]) -> (Vec<usize>, Vec<Rank>) {
+    let mut rank_counts: Vec<(usize, Rank)> = vec![];
+    for card in up_cards {
+        match rank_counts
+            .iter_mut()
+            .find(|(_, rank)| *rank == card.rank())
+        {
+            Some((count, _)) => *count += 1,
+            None => rank_counts.push((1, card.rank())),
+        }
+    }
+    rank_counts.sort_unstable_by(|a, b| b.cmp(a));
+    rank_counts.into_iter().unzip()
+}
+
+/// Orders up cards to pick the bring-in, the lowest rank and then clubs, diamonds, hearts and spades from lowest
+pub fn get_bring_in_rank(card: Card) -> (Rank, u8) {
+    let suit_rank = match card.suit() {
+        Suit::Clubs => 0,
+        Suit::Diamonds => 1,
+        Suit::Hearts => 2,
+        Suit::Spades => 3,
+    };
+    (card.rank(), suit_rank)
+}
+
+fn evaluate(evaluator: &Evaluator, cards: &[Card]) -> Eval {
+    evaluator.evaluate(cards).expect("Couldn't evaluate hand")
+}
+
+/// Gets every way to pick `size` of the cards, keeping their order
+pub fn get_combinations(cards: &[Card], size: usize) -> Vec<Vec<Card>> {
+    if size == 0 {
+        return vec![vec![]];
+    }
+    let mut combinations = vec![];
+    for (index, card) in cards.iter().enumerate() {
+        for mut rest in get_combinations(&cards[index + 1..], size - 1) {
+            rest.insert(0, *card);
+            combinations.push(rest);
+        }
+    }
+    combinations
+}
+
+#[cfg(test)]
+mod tests {
+    use poker::{cards, Card, EvalClass, Rank};
+    use rand::thread_rng;
+
+    use crate::bet_stage::BetStage;
+    use crate::global_immutables::SHARED_EVALUATOR;
+    use crate::table::game_variant::{get_bring_in_rank, get_combinations, get_showing_rank};
+    use crate::table::GameVariant;
+
+    fn parse(cards: &str) -> Vec<Card> {
+        cards!(cards).try_collect().unwrap()
+    }
+
+    #[test]
+    fn test_combinations() {
+        let cards = parse("As Ks Qs Js Ts");
+        assert_eq!(get_combinations(&cards, 2).len(), 10);
+        assert_eq!(get_combinations(&cards, 3).len(), 10);
+        assert_eq!(get_combinations(&cards, 5).len(), 1);
+    }
+
+    #[test]
+    fn test_max_players() {
+        assert_eq!(GameVariant::TexasHoldem.get_max_players(), 23);
+        assert_eq!(GameVariant::PotLimitOmaha.get_max_players(), 11);
+        assert_eq!(GameVariant::OmahaHiLo.get_max_players(), 11);
+        assert_eq!(GameVariant::ShortDeckHoldem.get_max_players(), 15);
+        assert_eq!(GameVariant::KuhnPoker.get_max_players(), 3);
+        assert_eq!(GameVariant::LeducHoldem.get_max_players(), 5);
+        assert_eq!(GameVariant::SevenCardStud.get_max_players(), 7);
+    }
+
+    #[test]
+    fn test_streets() {
+        let holdem = GameVariant::TexasHoldem;
+        let mut stages = vec![holdem.get_first_stage()];
+        while let Some(stage) = holdem.get_next_stage(*stages.last().unwrap()) {
+            stages.push(stage);
+        }
+        assert_eq!(
+            stages,
+            [
+                BetStage::PreFlop,
+                BetStage::Flop,
+                BetStage::Turn,
+                BetStage::River
+            ]
+        );
+        assert_eq!(holdem.get_board_cards_shown(BetStage::PreFlop), 0);
+        assert_eq!(holdem.get_board_cards_shown(BetStage::Turn), 4);
+        assert_eq!(holdem.get_hole_cards_dealt(BetStage::PreFlop), 2);
+        assert!(!holdem.has_up_cards());
+        assert_eq!(
+            GameVariant::LeducHoldem.get_board_cards_shown(BetStage::Flop),
+            1
+        );
+        assert_eq!(
+            GameVariant::KuhnPoker.get_next_stage(BetStage::PreFlop),
+            None
+        );
+        // Stud has five streets of cards for each player and no board
+        let stud = GameVariant::SevenCardStud;
+        assert_eq!(stud.get_first_stage(), BetStage::ThirdStreet);
+        assert_eq!(stud.get_streets().len(), 5);
+        assert_eq!(stud.get_next_stage(BetStage::SeventhStreet), None);
+        assert_eq!(stud.get_hole_card_count(), 7);
+        assert_eq!(stud.get_board_card_count(), 0);
+        assert_eq!(stud.get_hole_cards_dealt(BetStage::ThirdStreet), 3);
+        assert_eq!(stud.get_hole_cards_dealt(BetStage::SixthStreet), 6);
+        assert!(stud.has_up_cards());
+    }
+
+    #[test]
+    fn test_stud_up_cards() {
+        let stud = GameVariant::SevenCardStud;
+        let hand = parse("2c 3c 4c 5c 6c 7c 8c");
+        assert_eq!(stud.get_up_cards(&hand, BetStage::ThirdStreet), parse("4c"));
+        assert_eq!(
+            stud.get_up_cards(&hand, BetStage::SixthStreet),
+            parse("4c 5c 6c 7c")
+        );
+        // The last card is dealt face down
+        assert_eq!(
+            stud.get_up_cards(&hand, BetStage::SeventhStreet),
+            parse("4c 5c 6c 7c")
+        );
+        assert!(GameVariant::TexasHoldem
+            .get_up_cards(&parse("As Ks"), BetStage::River)
+            .is_empty());
+    }
+
+    #[test]
+    fn test_showing_rank() {
+        let pair = get_showing_rank(&parse("2c 2d 3h"));
+        let high_cards = get_showing_rank(&parse("Ac Kd Qh"));
+        let two_pair = get_showing_rank(&parse("2c 2d 3h 3s"));
+        let pair_of_aces = get_showing_rank(&parse("Ac Ad Kh Qs"));
+        let trips = get_showing_rank(&parse("2c 2d 2h 3s"));
+        assert!(pair > high_cards);
+        assert!(two_pair > pair_of_aces);
+        assert!(trips > two_pair);
+        assert!(get_showing_rank(&parse("Ac Kd")) > get_showing_rank(&parse("Ac Qd")));
+        // The bring-in goes to the lowest rank, and then by suit from clubs up to spades
+        let mut cards = parse("3c 2s 2h 2d 2c");
+        cards.sort_by_key(|card| get_bring_in_rank(*card));
+        assert_eq!(cards, parse("2c 2d 2h 2s 3c"));
+    }
+
+    #[test]
+    fn test_kuhn_and_leduc_decks() {
+        for variant in [GameVariant::KuhnPoker, GameVariant::LeducHoldem] {
+            let mut deck = variant.generate_shuffled_deck(&mut thread_rng());
+            assert_eq!(deck.len(), variant.get_deck_size());
+            assert!(deck.iter().all(|card| card.rank() >= Rank::Jack));
+            assert!(deck.iter().all(|card| card.rank() <= Rank::King));
+            deck.sort();
+            deck.dedup();
+            assert_eq!(deck.len(), variant.get_deck_size());
+        }
+    }
+
+    #[test]
+    fn test_kuhn_highest_card_wins() {
+        let kuhn = GameVariant::KuhnPoker;
+        let king = kuhn.evaluate(&SHARED_EVALUATOR, &parse("Ks"), &[]);
+        let queen = kuhn.evaluate(&SHARED_EVALUATOR, &parse("Qs"), &[]);
+        let jack = kuhn.evaluate(&SHARED_EVALUATOR, &parse("Js"), &[]);
+        assert!(king > queen);
+        assert!(queen > jack);
+    }
+
+    #[test]
+    fn test_leduc_pair_beats_high_card() {
+        let leduc = GameVariant::LeducHoldem;
+        let board = parse("Jh");
+        let pair = leduc.evaluate(&SHARED_EVALUATOR, &parse("Js"), &board);
+        let king = leduc.evaluate(&SHARED_EVALUATOR, &parse("Ks"), &board);
+        let queen = leduc.evaluate(&SHARED_EVALUATOR, &parse("Qh"), &board);
+        assert!(pair > king);
+        assert!(king > queen);
+        // Without a pair the same ranks tie no matter the suit
+        assert_eq!(
+            king,
+            leduc.evaluate(&SHARED_EVALUATOR, &parse("Kh"), &board)
+        );
+    }
+
+    #[test]
+    fn test_short_deck() {
+        let deck = GameVariant::ShortDeckHoldem.generate_shuffled_deck(&mut thread_rng());
+        assert_eq!(deck.len(), GameVariant::ShortDeckHoldem.get_deck_size());
+        assert!(deck.iter().all(|card| card.rank() >= Rank::Six));
+        assert_eq!(
+            GameVariant::TexasHoldem
+                .generate_shuffled_deck(&mut thread_rng())
+                .len(),
+            52
+        );
+    }
+
+    #[test]
+    fn test_short_deck_flush_beats_full_house() {
+        let board = parse("Ah Ad 9h 7h Kc");
+        let flush = parse("Th 6h");
+        let full_house = parse("Ac 9d");
+        let short_deck = GameVariant::ShortDeckHoldem;
+        assert!(
+            short_deck.evaluate(&SHARED_EVALUATOR, &flush, &board)
+                > short_deck.evaluate(&SHARED_EVALUATOR, &full_house, &board)
+        );
+        let holdem = GameVariant::TexasHoldem;
+        assert!(
+            holdem.evaluate(&SHARED_EVALUATOR, &flush, &board)
+                < holdem.evaluate(&SHARED_EVALUATOR, &full_house, &board)
+        );
+    }
+
+    #[test]
+    fn test_short_deck_ace_low_straight() {
+        let short_deck = GameVariant::ShortDeckHoldem;
+        let board = parse("6c 7d 8h Kc Qs");
+        let wheel = short_deck.evaluate(&SHARED_EVALUATOR, &parse("As 9d"), &board);
+        let trips = short_deck.evaluate(&SHARED_EVALUATOR, &parse("Kd Kh"), &board);
+        let straight = short_deck.evaluate(&SHARED_EVALUATOR, &parse("9s Td"), &board);
+        // A-6-7-8-9 beats three of a kind but loses to every other straight
+        assert!(wheel > trips);
+        assert!(wheel < straight);
+        // It isn't a straight in Hold'em
+        let holdem = GameVariant::TexasHoldem.evaluate(&SHARED_EVALUATOR, &parse("As 9d"), &board);
+        assert!(!holdem.eval.is_straight());
+        // All in one suit it is the lowest straight flush
+        let straight_flush =
+            short_deck.evaluate(&SHARED_EVALUATOR, &parse("Ac 9c"), &parse("6c 7c 8c Kd Qs"));
+        let quads =
+            short_deck.evaluate(&SHARED_EVALUATOR, &parse("Kd Kh"), &parse("Kc Ks 8c 7d 6s"));
+        assert!(straight_flush > quads);
+    }
+
+    #[test]
+    fn test_low_hands() {
+        let board = parse("Ah 3c 6d Ks Qs");
+        let low = GameVariant::OmahaHiLo
+            .evaluate_low(&parse("2d 8s Kc Kd"), &board)
+            .unwrap();
+        assert_eq!(low.to_string(), "8-6-3-2-A");
+        // A wheel is the best low even though it is also a straight
+        let wheel = GameVariant::OmahaHiLo
+            .evaluate_low(&parse("2d 4s 5c Kd"), &board)
+            .unwrap();
+        assert_eq!(wheel.to_string(), "6-4-3-2-A");
+        assert!(wheel < low);
+        // Lows are compared from the highest card down
+        let seven = GameVariant::OmahaHiLo
+            .evaluate_low(&parse("7d 5s Kc Kd"), &board)
+            .unwrap();
+        assert_eq!(seven.to_string(), "7-6-5-3-A");
+        assert!(seven < low);
+        assert!(wheel < seven);
+        // A pair in hand or only one low card in hand doesn't make a low
+        assert!(GameVariant::OmahaHiLo
+            .evaluate_low(&parse("2d 2s Kc Kd"), &board)
+            .is_none());
+        assert!(GameVariant::OmahaHiLo
+            .evaluate_low(&parse("2d 9s Kc Kd"), &board)
+            .is_none());
+        // Only two low cards on the board can't make a low
+        assert!(GameVariant::OmahaHiLo
+            .evaluate_low(&parse("2d 4s 5c 7d"), &parse("Ah 3c Td Ks Qs"))
+            .is_none());
+        // Games without a low never have one
+        assert!(GameVariant::PotLimitOmaha
+            .evaluate_low(&parse("2d 4s 5c Kd"), &board)
+            .is_none());
+    }
+
+    #[test]
+    fn test_omaha_uses_exactly_two_hole_cards() {
+        let board = parse("Ah Kh Qh 2h 3d");
+        // Four hearts on the board and one in hand isn't a flush in Omaha, it would be in Hold'em
+        let hole_cards = parse("Jh 9s 8s 7c");
+        let holdem = GameVariant::TexasHoldem.evaluate(&SHARED_EVALUATOR, &hole_cards, &board);
+        let omaha = GameVariant::PotLimitOmaha.evaluate(&SHARED_EVALUATOR, &hole_cards, &board);
+        assert!(holdem.eval.is_flush());
+        assert!(!omaha.eval.is_flush());
+        // Four of a kind on the board plays as trips with two hole cards
+        let board = parse("9c 9d 9h 9s 2d");
+        let omaha =
+            GameVariant::PotLimitOmaha.evaluate(&SHARED_EVALUATOR, &parse("Ac Kd 3s 4s"), &board);
+        assert_eq!(
+            omaha.eval.class(),
+            EvalClass::ThreeOfAKind { trips: Rank::Nine }
+        );
+    }
+
+    #[test]
+    fn test_omaha_best_combination() {
+        let board = parse("Th Jh Qc 4d 5s");
+        let straight =
+            GameVariant::PotLimitOmaha.evaluate(&SHARED_EVALUATOR, &parse("Ks As 2c 2d"), &board);
+        let pair =
+            GameVariant::PotLimitOmaha.evaluate(&SHARED_EVALUATOR, &parse("Qs 7s 2h 3c"), &board);
+        assert!(straight.eval.is_straight());
+        assert!(straight > pair);
+    }
+}
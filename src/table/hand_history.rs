@@ -0,0 +1,461 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use poker::Card;
+use serde::{Deserialize, Serialize};
+
+use crate::actions::HandAction;
+use crate::bet_stage::BetStage;
+
+/// The forced bets of a hand and the seats that post them, used to start a hand history
+pub struct HandStart {
+    pub hand_number: i32,
+    pub game_name: String,
+    pub small_blind: i32,
+    pub big_blind: i32,
+    pub seat_count: usize,
+    pub button: usize,
+    /// The seats that posted the small and big blind, None when nobody posted that blind
+    pub small_blind_seat: Option<usize>,
+    pub big_blind_seat: Option<usize>,
+    /// Each seat dealt into the hand with the chips it started the hand with
+    pub stacks: Vec<(usize, i32)>,
+}
+
+/// How a hand ended, used to finish a hand history
+pub struct HandEnd {
+    /// Every chip bet except the uncalled part of a bet that nobody matched
+    pub pot: i32,
+    /// The seat whose bet nobody matched and how much of it was returned
+    pub uncalled_bet: Option<(usize, i32)>,
+    /// The chips each seat collected from the pot
+    pub collected: Vec<i32>,
+    /// The hole cards of each seat that showed down, empty if everyone else folded
+    pub shown_hands: Vec<(usize, Vec<Card>)>,
+    /// The face up board cards, more than one board when the board was run more than once
+    pub boards: Vec<Vec<Card>>,
+}
+
+/// A hand written out in the PokerStars hand history format, line by line as the hand is played
+#[derive(Deserialize, Serialize)]
+pub struct HandHistory {
+    /// Every line written so far
+    lines: Vec<String>,
+    /// The first betting stage of the hand, folding in it is folding before the flop
+    first_stage: BetStage,
+    /// The current betting stage
+    stage: BetStage,
+    /// The chips each seat has put in this betting stage, not counting the ante
+    street_bets: Vec<i32>,
+    /// The stage each seat folded in, None while they are still in the hand
+    folded_stages: Vec<Option<BetStage>>,
+    button: usize,
+    small_blind_seat: Option<usize>,
+    big_blind_seat: Option<usize>,
+    /// The seats dealt into the hand
+    dealt_seats: Vec<usize>,
+}
+
+impl HandHistory {
+    /// Starts a hand history with the header and a line for each seat
+    pub fn new(start: HandStart, first_stage: BetStage) -> Self {
+        let mut lines = vec![
+            format!(
+                "PokerStars Hand #{}: {} ({}/{}) - {}",
+                start.hand_number,
+                start.game_name,
+                start.small_blind,
+                start.big_blind,
+                format_utc_time(SystemTime::now())
+            ),
+            format!(
+                "Table 'Bot Arena' {}-max Seat #{} is the button",
+                start.seat_count,
+                start.button + 1
+            ),
+        ];
+        for (seat, stack) in &start.stacks {
+            lines.push(format!(
+                "Seat {}: {} ({stack} in chips)",
+                seat + 1,
+                get_player_name(*seat)
+            ));
+        }
+        HandHistory {
+            lines,
+            first_stage,
+            stage: first_stage,
+            street_bets: vec![0; start.seat_count],
+            folded_stages: vec![None; start.seat_count],
+            button: start.button,
+            small_blind_seat: start.small_blind_seat,
+            big_blind_seat: start.big_blind_seat,
+            dealt_seats: start.stacks.iter().map(|(seat, _)| *seat).collect(),
+        }
+    }
+
+    /// Writes a forced bet such as "posts the ante" or "brings in for", only blinds and bring-ins count as a bet
+    pub fn add_post(
+        &mut self,
+        seat: usize,
+        description: &str,
+        amount: i32,
+        is_all_in: bool,
+        is_bet: bool,
+    ) {
+        if is_bet {
+            self.street_bets[seat] += amount;
+        }
+        self.add_player_line(seat, format!("{description} {amount}"), is_all_in);
+    }
+
+    /// Starts a betting stage with the board so far and the cards each seat was just dealt
+    pub fn start_stage(
+        &mut self,
+        stage: BetStage,
+        board: (&[Card], &[Card]),
+        dealt: &[(usize, Vec<Card>, Vec<Card>)],
+    ) {
+        // The blinds and bring-in posted before the first stage starts are bets in it
+        if stage != self.stage {
+            self.street_bets.iter_mut().for_each(|bet| *bet = 0);
+        }
+        self.stage = stage;
+        let mut title = format!("*** {} ***", get_stage_title(stage));
+        if !board.1.is_empty() {
+            title += format_new_cards(board.0, board.1).as_str();
+        }
+        self.lines.push(title);
+        for (seat, old_cards, new_cards) in dealt {
+            self.lines.push(format!(
+                "Dealt to {}{}",
+                get_player_name(*seat),
+                format_new_cards(old_cards, new_cards)
+            ));
+        }
+    }
+
+    /// Writes a player's action, the amount is the chips they put in with it
+    pub fn add_action(
+        &mut self,
+        seat: usize,
+        hand_action: HandAction,
+        amount: i32,
+        is_all_in: bool,
+    ) {
+        let largest_bet = self.street_bets.iter().max().copied().unwrap_or(0);
+        let total_bet = self.street_bets[seat] + amount;
+        let description = match hand_action {
+            HandAction::Fold => {
+                self.folded_stages[seat] = Some(self.stage);
+                "folds".to_string()
+            }
+            _ if amount == 0 => "checks".to_string(),
+            HandAction::Raise(_) if total_bet > largest_bet && largest_bet == 0 => {
+                format!("bets {amount}")
+            }
+            HandAction::Raise(_) if total_bet > largest_bet => {
+                format!("raises {} to {total_bet}", total_bet - largest_bet)
+            }
+            _ => format!("calls {amount}"),
+        };
+        self.street_bets[seat] = total_bet;
+        self.add_player_line(seat, description, is_all_in);
+    }
+
+    /// Writes the showdown, who collected the pot and the summary
+    pub fn finish(&mut self, end: HandEnd) {
+        if let Some((seat, amount)) = end.uncalled_bet {
+            self.lines.push(format!(
+                "Uncalled bet ({amount}) returned to {}",
+                get_player_name(seat)
+            ));
+        }
+        if !end.shown_hands.is_empty() {
+            self.lines.push("*** SHOW DOWN ***".to_string());
+            for (seat, cards) in &end.shown_hands {
+                self.add_player_line(*seat, format!("shows [{}]", format_cards(cards)), false);
+            }
+        }
+        for (seat, collected) in end.collected.iter().enumerate() {
+            if *collected > 0 {
+                self.lines.push(format!(
+                    "{} collected {collected} from pot",
+                    get_player_name(seat)
+                ));
+            }
+        }
+        self.lines.push("*** SUMMARY ***".to_string());
+        self.lines.push(format!("Total pot {} | Rake 0", end.pot));
+        match end.boards.as_slice() {
+            [] => {}
+            [board] if board.is_empty() => {}
+            [board] => self.lines.push(format!("Board [{}]", format_cards(board))),
+            boards => {
+                for (run, board) in boards.iter().enumerate() {
+                    self.lines
+                        .push(format!("Board {} [{}]", run + 1, format_cards(board)));
+                }
+            }
+        }
+        for seat in self.dealt_seats.clone() {
+            let shown = end
+                .shown_hands
+                .iter()
+                .find(|(shown_seat, _)| *shown_seat == seat);
+            let collected = end.collected[seat];
+            let outcome = match (self.folded_stages[seat], shown) {
+                (Some(stage), _) if stage == self.first_stage && stage == BetStage::PreFlop => {
+                    "folded before Flop".to_string()
+                }
+                (Some(stage), _) => format!("folded on the {}", get_stage_name(stage)),
+                (None, Some((_, cards))) if collected > 0 => {
+                    format!("showed [{}] and won ({collected})", format_cards(cards))
+                }
+                (None, Some((_, cards))) => format!("showed [{}] and lost", format_cards(cards)),
+                (None, None) => format!("collected ({collected})"),
+            };
+            self.lines.push(format!(
+                "Seat {}: {}{} {outcome}",
+                seat + 1,
+                get_player_name(seat),
+                self.get_position_label(seat)
+            ));
+        }
+    }
+
+    fn add_player_line(&mut self, seat: usize, description: String, is_all_in: bool) {
+        let all_in = if is_all_in { " and is all-in" } else { "" };
+        self.lines
+            .push(format!("{}: {description}{all_in}", get_player_name(seat)));
+    }
+
+    /// Gets the seat's position in the summary, such as " (button)"
+    fn get_position_label(&self, seat: usize) -> &'static str {
+        if Some(seat) == self.small_blind_seat {
+            " (small blind)"
+        } else if Some(seat) == self.big_blind_seat {
+            " (big blind)"
+        } else if seat == self.button {
+            " (button)"
+        } else {
+            ""
+        }
+    }
+}
+
+impl fmt::Display for HandHistory {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.lines.join("\n"))
+    }
+}
+
+fn get_player_name(seat: usize) -> String {
+    format!("Player {seat}")
+}
+
+/// The heading a hand history gives each betting stage
+fn get_stage_title(stage: BetStage) -> &'static str {
+    match stage {
+        BetStage::PreFlop => "HOLE CARDS",
+        BetStage::Flop => "FLOP",
+        BetStage::Turn => "TURN",
+        BetStage::River | BetStage::SeventhStreet => "RIVER",
+        BetStage::ThirdStreet => "3rd STREET",
+        BetStage::FourthStreet => "4th STREET",
+        BetStage::FifthStreet => "5th STREET",
+        BetStage::SixthStreet => "6th STREET",
+    }
+}
+
+/// The name a hand history summary gives each betting stage
+fn get_stage_name(stage: BetStage) -> &'static str {
+    match stage {
+        BetStage::PreFlop => "Pre-Flop",
+        BetStage::Flop => "Flop",
+        BetStage::Turn => "Turn",
+        BetStage::River | BetStage::SeventhStreet => "River",
+        BetStage::ThirdStreet => "3rd Street",
+        BetStage::FourthStreet => "4th Street",
+        BetStage::FifthStreet => "5th Street",
+        BetStage::SixthStreet => "6th Street",
+    }
+}
+
+fn format_cards(cards: &[Card]) -> String {
+    let cards: Vec<String> = cards.iter().map(|card| card.rank_suit_string()).collect();
+    cards.join(" ")
+}
+
+/// Writes the cards already known then the new ones, like " [2c 7d 9h] [Kc]"
+fn format_new_cards(old_cards: &[Card], new_cards: &[Card]) -> String {
+    if old_cards.is_empty() {
+        format!(" [{}]", format_cards(new_cards))
+    } else {
+        format!(
+            " [{}] [{}]",
+            format_cards(old_cards),
+            format_cards(new_cards)
+        )
+    }
+}
+
+/// Writes the time as a UTC date like "2023/11/14 22:13:20 UTC"
+fn format_utc_time(time: SystemTime) -> String {
+    let seconds = time.duration_since(UNIX_EPOCH).map_or(0, |x| x.as_secs());
+    let seconds_of_day = seconds % 86400;
+    // Converts days since the epoch to a date, treating March as the first month so leap days come last
+    let days = (seconds / 86400) as i64 + 719468;
+    let era = days.div_euclid(146097);
+    let day_of_era = days - era * 146097;
+    let year_of_era =
+        (day_of_era - day_of_era / 1460 + day_of_era / 36524 - day_of_era / 146096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let shifted_month = (5 * day_of_year + 2) / 153;
+    let day = day_of_year - (153 * shifted_month + 2) / 5 + 1;
+    let month = if shifted_month < 10 {
+        shifted_month + 3
+    } else {
+        shifted_month - 9
+    };
+    let year = year_of_era + era * 400 + i64::from(month <= 2);
+    format!(
+        "{year}/{month:02}/{day:02} {:02}:{:02}:{:02} UTC",
+        seconds_of_day / 3600,
+        seconds_of_day % 3600 / 60,
+        seconds_of_day % 60
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, UNIX_EPOCH};
+
+    use poker::Card;
+
+    use crate::actions::HandAction;
+    use crate::bet_stage::BetStage;
+    use crate::table::hand_history::{format_utc_time, HandEnd, HandHistory, HandStart};
+
+    fn parse(cards: &str) -> Vec<Card> {
+        poker::cards!(cards).try_collect().unwrap()
+    }
+
+    #[test]
+    fn test_utc_time() {
+        assert_eq!(format_utc_time(UNIX_EPOCH), "1970/01/01 00:00:00 UTC");
+        let time = UNIX_EPOCH + Duration::from_secs(1_700_000_000);
+        assert_eq!(format_utc_time(time), "2023/11/14 22:13:20 UTC");
+        // A leap day
+        let time = UNIX_EPOCH + Duration::from_secs(951_782_400);
+        assert_eq!(format_utc_time(time), "2000/02/29 00:00:00 UTC");
+    }
+
+    #[test]
+    fn test_hand_history_lines() {
+        let mut history = HandHistory::new(
+            HandStart {
+                hand_number: 3,
+                game_name: "Hold'em Pot Limit".to_string(),
+                small_blind: 5,
+                big_blind: 10,
+                seat_count: 3,
+                button: 0,
+                small_blind_seat: Some(1),
+                big_blind_seat: Some(2),
+                stacks: vec![(0, 500), (1, 500), (2, 40)],
+            },
+            BetStage::PreFlop,
+        );
+        history.add_post(1, "posts small blind", 5, false, true);
+        history.add_post(2, "posts big blind", 10, false, true);
+        history.start_stage(
+            BetStage::PreFlop,
+            (&[], &[]),
+            &[(0, vec![], parse("As Ad")), (1, vec![], parse("2c 3d"))],
+        );
+        history.add_action(0, HandAction::Raise(20), 30, false);
+        history.add_action(1, HandAction::Fold, 0, false);
+        history.add_action(2, HandAction::Call, 20, true);
+        history.start_stage(BetStage::Flop, (&[], &parse("2d 7h 9s")), &[]);
+        history.add_action(0, HandAction::Check, 0, false);
+        history.finish(HandEnd {
+            pot: 85,
+            uncalled_bet: None,
+            collected: vec![85, 0, 0],
+            shown_hands: vec![(0, parse("As Ad")), (2, parse("Ks Kd"))],
+            boards: vec![parse("2d 7h 9s 3c 4c")],
+        });
+        let text = history.to_string();
+        let lines: Vec<&str> = text.lines().collect();
+        assert!(lines[0].starts_with("PokerStars Hand #3: Hold'em Pot Limit (5/10) - "));
+        assert_eq!(lines[1], "Table 'Bot Arena' 3-max Seat #1 is the button");
+        assert_eq!(lines[4], "Seat 3: Player 2 (40 in chips)");
+        assert_eq!(lines[5], "Player 1: posts small blind 5");
+        assert_eq!(lines[7], "*** HOLE CARDS ***");
+        assert_eq!(lines[8], "Dealt to Player 0 [As Ad]");
+        assert_eq!(lines[10], "Player 0: raises 20 to 30");
+        assert_eq!(lines[11], "Player 1: folds");
+        assert_eq!(lines[12], "Player 2: calls 20 and is all-in");
+        assert_eq!(lines[13], "*** FLOP *** [2d 7h 9s]");
+        assert_eq!(lines[14], "Player 0: checks");
+        assert_eq!(lines[15], "*** SHOW DOWN ***");
+        assert_eq!(lines[16], "Player 0: shows [As Ad]");
+        assert_eq!(lines[18], "Player 0 collected 85 from pot");
+        assert_eq!(lines[19], "*** SUMMARY ***");
+        assert_eq!(lines[20], "Total pot 85 | Rake 0");
+        assert_eq!(lines[21], "Board [2d 7h 9s 3c 4c]");
+        assert_eq!(
+            lines[22],
+            "Seat 1: Player 0 (button) showed [As Ad] and won (85)"
+        );
+        assert_eq!(
+            lines[23],
+            "Seat 2: Player 1 (small blind) folded before Flop"
+        );
+        assert_eq!(
+            lines[24],
+            "Seat 3: Player 2 (big blind) showed [Ks Kd] and lost"
+        );
+    }
+
+    #[test]
+    fn test_bets_and_later_streets() {
+        let mut history = HandHistory::new(
+            HandStart {
+                hand_number: 1,
+                game_name: "7 Card Stud Pot Limit".to_string(),
+                small_blind: 0,
+                big_blind: 0,
+                seat_count: 2,
+                button: 1,
+                small_blind_seat: None,
+                big_blind_seat: None,
+                stacks: vec![(0, 100), (1, 100)],
+            },
+            BetStage::ThirdStreet,
+        );
+        history.start_stage(
+            BetStage::FourthStreet,
+            (&[], &[]),
+            &[(0, parse("2c 3d 4h"), parse("5s"))],
+        );
+        history.add_action(0, HandAction::Raise(4), 4, false);
+        history.add_action(1, HandAction::Fold, 0, false);
+        history.finish(HandEnd {
+            pot: 0,
+            uncalled_bet: Some((0, 4)),
+            collected: vec![0, 0],
+            shown_hands: vec![],
+            boards: vec![vec![]],
+        });
+        let text = history.to_string();
+        assert!(text.contains("*** 4th STREET ***\nDealt to Player 0 [2c 3d 4h] [5s]"));
+        assert!(text.contains("Player 0: bets 4\n"));
+        assert!(text.contains("Uncalled bet (4) returned to Player 0"));
+        assert!(!text.contains("Board"));
+        assert!(text.contains("Seat 2: Player 1 (button) folded on the 4th Street"));
+        assert!(text.contains("Seat 1: Player 0 collected (0)"));
+    }
+}
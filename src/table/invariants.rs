@@ -0,0 +1,72 @@
+//! Checks that a table is still in a state the rules allow, run after every action in debug and test builds.
+
+use crate::table::Table;
+
+/// The chips on the table, stacks and pot, less everything the players bought in for. Chips are only ever moved
+/// between players, and rebuys or top ups add as much to the buy ins as to the stacks, so no action changes it
+pub fn get_chip_balance(table: &Table) -> i64 {
+    table
+        .players
+        .iter()
+        .map(|player| player.total_money as i64 - player.total_buy_in as i64)
+        .sum::<i64>()
+        + table.get_pot_size() as i64
+}
+
+/// Checks that the chips were conserved since the balance was taken, that no stack is negative, that the pot
+/// holds what each player still in the hand has bet, and that the player to act can still act
+pub fn check_invariants(table: &Table, chip_balance_before: i64) -> Result<(), String> {
+    let chip_balance = get_chip_balance(table);
+    if chip_balance != chip_balance_before {
+        return Err(format!(
+            "The table had a chip balance of {chip_balance_before} but now has {chip_balance}"
+        ));
+    }
+    for player in &table.players {
+        if player.total_money < 0 {
+            return Err(format!(
+                "Player {} has a negative stack of {}",
+                player.get_id(),
+                player.total_money
+            ));
+        }
+        let bet = table.player_bets[player.get_id() as usize];
+        if bet < 0 {
+            return Err(format!(
+                "Player {} has a negative bet of {bet}",
+                player.get_id()
+            ));
+        }
+        if let Some(current_bet) = player.player_state.get_bet() {
+            if table.hand_in_progress && current_bet != bet {
+                return Err(format!(
+                    "Player {} has bet {current_bet} but has {bet} in the pot",
+                    player.get_id()
+                ));
+            }
+        }
+    }
+    if !table.hand_in_progress {
+        if table.get_pot_size() != 0 {
+            return Err(format!(
+                "{} chips are left in the pot between hands",
+                table.get_pot_size()
+            ));
+        }
+        return Ok(());
+    }
+    let current_player = table.get_current_player();
+    if !current_player.player_state.is_active() {
+        return Err(format!(
+            "Player {} is to act but isn't in the hand",
+            current_player.get_id()
+        ));
+    }
+    if current_player.total_money == 0 {
+        return Err(format!(
+            "Player {} is to act but is all in",
+            current_player.get_id()
+        ));
+    }
+    Ok(())
+}
@@ -0,0 +1,134 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+/// One level of a blind schedule, it lasts a number of hands or an amount of time
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Level {
+    #[serde(default)]
+    pub small_blind: i32,
+    #[serde(default)]
+    pub big_blind: i32,
+    #[serde(default)]
+    pub ante: i32,
+    /// How many hands the level lasts
+    pub hands: Option<i32>,
+    /// How many seconds the level lasts
+    pub seconds: Option<u64>,
+}
+
+/// The blind and ante levels of a tournament, the last level lasts until the game is over
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct LevelSchedule {
+    pub levels: Vec<Level>,
+}
+
+impl LevelSchedule {
+    /// Loads a schedule from a json file, see `schedules/` for examples
+    pub fn from_file(path: &Path) -> Result<Self, String> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|error| format!("Couldn't read the schedule {}: {error}", path.display()))?;
+        LevelSchedule::from_json_str(&contents)
+    }
+
+    pub fn from_json_str(contents: &str) -> Result<Self, String> {
+        let schedule: LevelSchedule = serde_json::from_str(contents)
+            .map_err(|error| format!("Couldn't parse the schedule: {error}"))?;
+        schedule.validate()?;
+        Ok(schedule)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        if self.levels.is_empty() {
+            return Err("The schedule needs at least one level".to_string());
+        }
+        for (index, level) in self.levels.iter().enumerate() {
+            let number = index + 1;
+            if level.small_blind < 0 || level.ante < 0 || level.big_blind < level.small_blind {
+                return Err(format!(
+                    "Level {number} needs a non negative ante and a big blind at least as big as the small blind"
+                ));
+            }
+            // Without any forced bets nobody has a reason to put chips in, so the game could never end
+            if level.ante == 0 && level.big_blind == 0 {
+                return Err(format!("Level {number} needs an ante or a big blind"));
+            }
+            let is_last = number == self.levels.len();
+            match (level.hands, level.seconds) {
+                (Some(_), Some(_)) => {
+                    return Err(format!(
+                        "Level {number} can last a number of hands or seconds, not both"
+                    ))
+                }
+                (None, None) if !is_last => {
+                    return Err(format!(
+                        "Level {number} needs to last a number of hands or seconds"
+                    ))
+                }
+                (Some(hands), _) if hands < 1 => {
+                    return Err(format!("Level {number} must last at least one hand"))
+                }
+                (_, Some(0)) => {
+                    return Err(format!("Level {number} must last at least one second"))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use crate::table::LevelSchedule;
+
+    #[test]
+    fn test_parse_schedule() {
+        let schedule = LevelSchedule::from_json_str(
+            r#"{"levels": [
+                {"ante": 1, "hands": 10},
+                {"small_blind": 5, "big_blind": 10, "ante": 1, "seconds": 300},
+                {"small_blind": 10, "big_blind": 20, "ante": 2}
+            ]}"#,
+        )
+        .unwrap();
+        assert_eq!(schedule.levels.len(), 3);
+        assert_eq!(schedule.levels[0].big_blind, 0);
+        assert_eq!(schedule.levels[0].hands, Some(10));
+        assert_eq!(schedule.levels[1].seconds, Some(300));
+        assert_eq!(schedule.levels[2].hands, None);
+    }
+
+    #[test]
+    fn test_bad_schedules() {
+        assert!(LevelSchedule::from_json_str("not json").is_err());
+        assert!(LevelSchedule::from_json_str(r#"{"levels": []}"#).is_err());
+        assert!(LevelSchedule::from_json_str(r#"{"levels": [{"hands": 5}]}"#).is_err());
+        assert!(LevelSchedule::from_json_str(
+            r#"{"levels": [{"ante": 1, "hands": 5, "seconds": 5}]}"#
+        )
+        .is_err());
+        assert!(LevelSchedule::from_json_str(
+            r#"{"levels": [{"ante": 1}, {"ante": 2, "hands": 5}]}"#
+        )
+        .is_err());
+        assert!(LevelSchedule::from_json_str(
+            r#"{"levels": [{"small_blind": 10, "big_blind": 5, "hands": 5}, {"ante": 2}]}"#
+        )
+        .is_err());
+        assert!(LevelSchedule::from_json_str(r#"{"levels": [{"ante": 1, "hands": 0}]}"#).is_err());
+    }
+
+    #[test]
+    fn test_example_schedules() {
+        for name in ["turbo.json", "deep_stack.json"] {
+            let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+                .join("schedules")
+                .join(name);
+            assert!(LevelSchedule::from_file(&path).is_ok(), "{name} is invalid");
+        }
+        assert!(LevelSchedule::from_file(Path::new("does_not_exist.json")).is_err());
+    }
+}
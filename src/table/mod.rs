@@ -1,46 +1,106 @@
-use std::cmp::{min, Ordering};
+use std::cmp::{min, Ordering, Reverse};
+use std::collections::HashSet;
 use std::fmt;
 use std::fmt::Formatter;
+use std::fs::OpenOptions;
+use std::io::Write;
 use std::slice::Iter;
 use std::sync::Arc;
+use std::time::{Duration, Instant};
 
 use json::{array, object, stringify_pretty, JsonValue};
 use poker::{Card, Evaluator};
+use rand::{thread_rng, Rng, SeedableRng};
+use rand_chacha::ChaCha12Rng;
+use serde::{Deserialize, Serialize};
 
 use crate::actions::HandAction;
 use crate::bet_stage::BetStage;
-use crate::bet_stage::BetStage::{Flop, PreFlop, River};
 use crate::global_immutables::SHARED_EVALUATOR;
-use crate::player_components::{ActiveState, Player, PlayerState};
+use crate::payout::{get_icm_equities, PayoutStructure};
+use crate::player_components::{ActiveState, HoleCards, Player, PlayerState, DEFAULT_START_MONEY};
 use crate::table::deal_information::DealInformation;
-use crate::table::table_action::{get_vec_of_strings_from_actions, TableAction};
+pub use crate::table::equity::DEFAULT_MONTE_CARLO_SAMPLES;
+use crate::table::equity::{get_expected_winnings, KnownCards, Pot};
+use crate::table::game_variant::{get_bring_in_rank, get_showing_rank};
+pub use crate::table::game_variant::{GameVariant, LowHand};
+use crate::table::hand_history::{HandEnd, HandHistory, HandStart};
+pub use crate::table::level_schedule::{Level, LevelSchedule};
+use crate::table::player_stats::{get_hand_stats, PlayerStats};
+use crate::table::storage::{Storage, StoredAction, StoredEntrant, StoredHandStart};
+use crate::table::table_action::get_vec_of_strings_from_actions;
+pub use crate::table::table_action::TableAction;
+pub use crate::table::table_config::{
+    ButtonRule, CashGameConfig, RebuyConfig, StopConditions, TableConfig,
+};
+pub use crate::table::table_error::TableError;
 
 mod deal_information;
+mod equity;
+mod game_variant;
+mod hand_history;
+#[cfg(debug_assertions)]
+mod invariants;
+mod level_schedule;
+mod player_stats;
+#[cfg(test)]
+mod property_test;
+mod snapshot;
+mod storage;
 mod table_action;
+mod table_config;
+mod table_error;
 #[cfg(test)]
 mod test;
 
+/// Saved with serde so a game can be checkpointed and resumed, see `snapshot` for the fields that need help
+#[derive(Deserialize, Serialize)]
 pub struct Table {
     /// All players
     players: Vec<Player>,
     /// The hand evaluator
+    #[serde(skip, default = "snapshot::get_shared_evaluator")]
     evaluator: Arc<Evaluator>,
-    /// The flop cards on the table (None if not dealt yet)
-    flop: Option<[Card; 3]>,
-    /// The turn card on the table (None if not dealt yet)
-    turn: Option<Card>,
-    /// The river card on the table (None if not dealt yet)
-    river: Option<Card>,
+    /// Shuffles every deck, it is seeded from the config so a game can be played again exactly
+    rng: ChaCha12Rng,
+    /// The cards dealt to the table face down, the flop then the turn and river (empty if not dealt yet)
+    #[serde(with = "snapshot::cards")]
+    board: Vec<Card>,
+    /// Every hole card dealt this hand, including to players that have since folded, none of them can be run out
+    #[serde(with = "snapshot::cards")]
+    dealt_hole_cards: Vec<Card>,
     /// Where the current dealer button is, informs turn order
     dealer_button_index: usize,
     /// The size of the ante
     ante: i32,
+    /// The size of the small blind, posted by the player after the button
+    small_blind: i32,
+    /// The size of the big blind, posted by the player after the small blind
+    big_blind: i32,
+    /// The seat of the small blind this hand, it is a knocked out seat when the small blind is dead
+    small_blind_index: usize,
+    /// The seat of the big blind this hand, None before the first hand
+    big_blind_index: Option<usize>,
+    /// The seat that posted the big blind or bring-in this hand, None when only antes were posted
+    forced_bet_index: Option<usize>,
+    /// The level of the schedule being played, 0 INDEXED
+    level_index: usize,
+    /// The first hand played at the current level
+    level_start_hand: i32,
+    /// When the current level started
+    #[serde(with = "snapshot::elapsed")]
+    level_start_time: Instant,
+    /// When the table was made, used for the time limit
+    #[serde(with = "snapshot::elapsed")]
+    start_time: Instant,
     /// How many hands have been played so far 1 INDEXED (not zero)
     hand_number: i32,
     /// Whose turn it is right now
     current_player_index: usize,
     /// State needed for table betting information
     table_state: BetStage,
+    /// The betting stage where betting ended because everyone left but one player was all in, None otherwise
+    all_in_stage: Option<BetStage>,
     /// Player bets, how much each player has bet so far
     player_bets: Vec<i32>,
     /// How frequently (after "ante_round_increase" rounds) the ante should be increased
@@ -49,6 +109,58 @@ pub struct Table {
     round_actions: Vec<TableAction>,
     /// A vector of previous round actions
     previous_round_actions: Vec<TableAction>,
+    /// Settings for how the table plays out
+    config: TableConfig,
+    /// True while a dealt hand is still being played, false between hands
+    hand_in_progress: bool,
+    /// For each seat, the players that won chips that seat put into the last pot
+    pot_winners_by_seat: Vec<Vec<i8>>,
+    /// Every player knocked out so far, in the order it happened
+    knockouts: Vec<Knockout>,
+    /// For each seat, the chips they were expected to win minus what they won in all in hands
+    all_in_adjustments: Vec<f64>,
+    /// For each seat, how they have played their hands so far
+    player_stats: Vec<PlayerStats>,
+    /// The current hand, or the last one between hands, written out as a hand history. None before the first hand
+    hand_history: Option<HandHistory>,
+    /// Writes the game to the database in the config, None without one or if it couldn't be opened
+    storage: Option<Storage>,
+}
+
+/// A limit that ended the game before enough players were knocked out
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum StopReason {
+    HandLimit(i32),
+    TimeLimit(Duration),
+    LevelCap(usize),
+}
+
+impl fmt::Display for StopReason {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            StopReason::HandLimit(hands) => {
+                write!(f, "The game reached the limit of {hands} hands")
+            }
+            StopReason::TimeLimit(duration) => {
+                write!(
+                    f,
+                    "The game reached the time limit of {} seconds",
+                    duration.as_secs_f64()
+                )
+            }
+            StopReason::LevelCap(level) => {
+                write!(f, "The game finished level {level}, the last level allowed")
+            }
+        }
+    }
+}
+
+/// A record of a player being knocked out, and who won the pot that did it
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct Knockout {
+    pub hand_number: i32,
+    pub eliminated: i8,
+    pub winners: Vec<i8>,
 }
 
 impl fmt::Display for Table {
@@ -67,41 +179,92 @@ impl fmt::Display for Table {
 }
 
 impl Table {
-    const ANTE_INCREASE_AMOUNT: i32 = 1;
+    pub const ANTE_INCREASE_AMOUNT: i32 = 1;
+    /// The most players that can be dealt into one table from a single deck
+    pub const MAX_PLAYERS: usize = 23;
     /// Makes a table of with the specified number of players.
-    pub fn new(number_of_players: usize) -> Self {
-        if number_of_players > 23 {
-            panic!("Too many players for one table!")
+    #[cfg(test)]
+    pub fn new(number_of_players: usize) -> Result<Self, TableError> {
+        Table::with_config(number_of_players, TableConfig::default())
+    }
+
+    /// Makes a table with the specified number of players that plays by the given config,
+    /// there can't be more players than the variant deals cards to.
+    pub fn with_config(
+        number_of_players: usize,
+        mut config: TableConfig,
+    ) -> Result<Self, TableError> {
+        let max = min(Table::MAX_PLAYERS, config.variant.get_max_players());
+        if number_of_players > max {
+            return Err(TableError::TooManyPlayers {
+                players: number_of_players,
+                max,
+            });
         }
         let mut players = Vec::new();
         for i in 0..number_of_players {
             players.push(Player::new(i as i8))
         }
+        let mut ante = 1;
+        // Cash games have their own stack size and forced bet
+        if let Some(cash_game) = &config.cash_game {
+            ante = cash_game.ante;
+            for player in &mut players {
+                player.total_money = cash_game.stack_size;
+                player.total_buy_in = cash_game.stack_size;
+            }
+        }
+        // Keep the seed used so the game can be replayed
+        let seed = *config.seed.get_or_insert_with(|| thread_rng().gen());
         let initial_index = number_of_players - 1;
         let mut table = Table {
             players,
             evaluator: SHARED_EVALUATOR.clone(),
-            flop: None,
-            turn: None,
-            river: None,
+            rng: ChaCha12Rng::seed_from_u64(seed),
+            board: vec![],
+            dealt_hole_cards: vec![],
             dealer_button_index: initial_index,
-            ante: 1,
+            ante,
+            small_blind: 0,
+            big_blind: 0,
+            small_blind_index: initial_index,
+            big_blind_index: None,
+            forced_bet_index: None,
+            level_index: 0,
+            level_start_hand: 1,
+            level_start_time: Instant::now(),
+            start_time: Instant::now(),
             hand_number: 0,
             current_player_index: initial_index,
-            table_state: PreFlop,
+            table_state: config.variant.get_first_stage(),
+            all_in_stage: None,
             player_bets: vec![0; number_of_players],
             ante_round_increase: number_of_players as i32 * 2,
             round_actions: vec![],
             previous_round_actions: vec![],
+            pot_winners_by_seat: vec![],
+            knockouts: vec![],
+            all_in_adjustments: vec![0.0; number_of_players],
+            player_stats: vec![PlayerStats::default(); number_of_players],
+            hand_history: None,
+            storage: None,
+            config,
+            hand_in_progress: false,
         };
-        table.deal();
-        table
+        table.open_storage();
+        table.apply_level();
+        if table.config.deal_automatically {
+            table.deal()?;
+        }
+        Ok(table)
     }
 
     /// Reset the table state to the starting round state
     fn reset_state_for_new_round(&mut self) {
-        // We will be in the pre flop stage
-        self.table_state = PreFlop;
+        // We will be in the first betting stage, pre flop in Hold'em
+        self.table_state = self.config.variant.get_first_stage();
+        self.all_in_stage = None;
+        self.forced_bet_index = None;
         // Reset all player bets to zero
         self.player_bets = vec![0; self.players.len()];
         // Save this round as the previous round
@@ -129,72 +292,66 @@ impl Table {
         self.current_player_index
     }
 
+    /// Gets the flop cards, up to three as some games deal fewer board cards
+    fn get_flop(&self) -> &[Card] {
+        &self.board[..self.board.len().min(3)]
+    }
+
     /// Translates the flop into json
     pub fn get_flop_string(&self) -> JsonValue {
-        match self.flop {
-            None => array!["None"],
-            Some(cards) => {
-                array![
-                    cards[0].to_string(),
-                    cards[1].to_string(),
-                    cards[2].to_string()
-                ]
-            }
+        if self.board.is_empty() {
+            return array!["None"];
         }
+        self.get_flop()
+            .iter()
+            .map(|card| card.to_string())
+            .collect::<Vec<_>>()
+            .into()
     }
 
     /// Translates the flop into a human readable string
     pub fn get_flop_string_secret(&self) -> JsonValue {
-        match self.flop {
-            None => array!["None"],
-            Some(cards) => match &self.table_state {
-                PreFlop => array!["Hidden"],
-                _ => {
-                    array![
-                        cards[0].to_string(),
-                        cards[1].to_string(),
-                        cards[2].to_string()
-                    ]
-                }
-            },
+        if !self.board.is_empty() && self.get_board_cards_shown() == 0 {
+            return array!["Hidden"];
         }
+        self.get_flop_string()
     }
 
     /// Translates the turn into a human readable string
     pub fn get_turn_string(&self) -> JsonValue {
-        match self.turn {
-            None => "None".into(),
-            Some(card) => card.to_string().into(),
-        }
+        Table::get_board_card_string(self.board.get(3))
     }
 
     pub fn get_turn_string_secret(&self) -> JsonValue {
-        match self.turn {
-            None => "None".into(),
-            Some(card) => match &self.table_state {
-                PreFlop => "Hidden".into(),
-                Flop => "Hidden".into(),
-                _ => card.to_string().into(),
-            },
-        }
+        self.get_board_card_string_secret(3)
     }
 
     /// Translates the river into a human readable string
     pub fn get_river_string(&self) -> JsonValue {
-        match self.river {
+        Table::get_board_card_string(self.board.get(4))
+    }
+
+    pub fn get_river_string_secret(&self) -> JsonValue {
+        self.get_board_card_string_secret(4)
+    }
+
+    fn get_board_card_string(card: Option<&Card>) -> JsonValue {
+        match card {
             None => "None".into(),
             Some(card) => card.to_string().into(),
         }
     }
 
-    pub fn get_river_string_secret(&self) -> JsonValue {
-        match self.river {
-            None => "None".into(),
-            Some(card) => match &self.table_state {
-                River => card.to_string().into(),
-                _ => "Hidden".into(),
-            },
+    fn get_board_card_string_secret(&self, index: usize) -> JsonValue {
+        if index < self.board.len() && index >= self.get_board_cards_shown() {
+            return "Hidden".into();
         }
+        Table::get_board_card_string(self.board.get(index))
+    }
+
+    /// How many board cards are face up in the current betting stage
+    fn get_board_cards_shown(&self) -> usize {
+        self.config.variant.get_board_cards_shown(self.table_state)
     }
 
     /// Returns the number of players
@@ -202,8 +359,21 @@ impl Table {
         self.players.len()
     }
 
-    /// Takes an action, could be recursive if the table needs no input
-    pub fn take_action(&mut self, hand_action: HandAction) {
+    /// Takes an action, could be recursive if the table needs no input. An error leaves the hand part way
+    /// through the action, so it has to be voided before the game can carry on
+    pub fn take_action(&mut self, hand_action: HandAction) -> Result<(), TableError> {
+        #[cfg(debug_assertions)]
+        let chip_balance = invariants::get_chip_balance(self);
+        self.apply_action(hand_action)?;
+        // Debug and test builds stop as soon as an action breaks the rules of the table
+        #[cfg(debug_assertions)]
+        if let Err(broken) = invariants::check_invariants(self, chip_balance) {
+            panic!("Taking the action {hand_action} broke the table: {broken}");
+        }
+        Ok(())
+    }
+
+    fn apply_action(&mut self, hand_action: HandAction) -> Result<(), TableError> {
         info!(
             "Player {} is taking action {}",
             self.get_current_player().get_id(),
@@ -215,180 +385,740 @@ impl Table {
                 "Game is over! Results are included below:\n{}",
                 self.get_results()
             );
-            return;
+            return Ok(());
+        }
+        // Between hands there is no one to act until the next deal
+        if !self.hand_in_progress {
+            return Err(TableError::NoHandInProgress);
         }
-        // Make sure the current player is active, or panic and end the program
+        // Make sure the current player is active
         if let PlayerState::Active(active) = self.get_current_player_mut().player_state {
-            self.take_provided_action(hand_action, active);
+            let money_before = self.get_current_player().total_money;
+            self.take_provided_action(hand_action, active)?;
+            self.add_action_to_hand_history(money_before);
         } else {
-            panic!("Tried to take an action on an inactive player");
+            return Err(TableError::InactivePlayer(
+                self.get_current_player().get_id(),
+            ));
         }
         // If there is only 1 active player evaluate the winner
         if self.get_active_player_count() == 1 {
-            self.resolve_hand();
-            return;
+            return self.resolve_hand();
         }
         // If the betting is over update the state
         while self.is_betting_over() && !self.is_game_over() {
-            // The showdown is occurring, pick the winner
-            if self.table_state == River {
-                self.resolve_hand();
-                return;
-            }
-            // Move to the next betting stage (can't hit the river case here)
-            match self.table_state {
-                PreFlop => self.round_actions.push(TableAction::AdvanceToFlop),
-                Flop => self.round_actions.push(TableAction::AdvanceToTurn),
-                _ => self.round_actions.push(TableAction::AdvanceToRiver),
-            }
-            self.table_state.next_stage();
-            // Reset the current player to the next person past the current dealer index
-            self.current_player_index = self.dealer_button_index;
+            // Once only one player can still bet the rest of the hand is dealt without betting
+            if self.all_in_stage.is_none() && self.get_players_able_to_bet_count() <= 1 {
+                self.all_in_stage = Some(self.table_state);
+            }
+            // After the last betting stage the showdown is occurring, pick the winner
+            let next_stage = match self.config.variant.get_next_stage(self.table_state) {
+                Some(next_stage) => next_stage,
+                None => return self.resolve_hand(),
+            };
+            self.push_action(TableAction::AdvanceToStage(next_stage));
+            let previous_stage = self.table_state;
+            self.table_state = next_stage;
+            self.add_stage_to_hand_history(Some(previous_stage));
+            // Reset the current player to the next person past the current dealer index, with up cards the best
+            // hand showing acts first instead
+            self.current_player_index = if self.config.variant.has_up_cards() {
+                self.get_seat_before(self.get_best_showing_index())
+            } else {
+                self.dealer_button_index
+            };
             // set everyone to not have a turn yet
             for player in &mut self.players {
                 player.has_had_turn_this_round = false;
             }
         }
         // The resolving didn't occur, update to the next player
-        self.update_current_player_index_to_next_active();
+        self.update_current_player_index_to_next_active()
+    }
+
+    /// Ends the hand being played without a winner, used after an action fails part way through.
+    /// Everyone dealt in gets back what they had when the hand was dealt, the hand isn't written to the
+    /// hand history, and the next hand is dealt as usual
+    pub fn void_hand(&mut self) -> Result<(), TableError> {
+        if !self.hand_in_progress {
+            return Err(TableError::NoHandInProgress);
+        }
+        for player in &mut self.players {
+            if player.is_alive() {
+                player.total_money = player.hand_starting_money;
+            }
+            player.player_state = PlayerState::Folded;
+        }
+        self.player_bets.iter_mut().for_each(|bet| *bet = 0);
+        // Nobody won a pot, so nobody can be credited with a knockout from it
+        self.pot_winners_by_seat.clear();
+        self.hand_history = None;
+        let result_string = format!(
+            "Hand {} was voided and every bet was refunded",
+            self.hand_number
+        );
+        warn!("{result_string}");
+        self.push_action(TableAction::EvaluateHand(result_string));
+        self.hand_in_progress = false;
+        if self.config.deal_automatically {
+            self.deal()?;
+        }
+        Ok(())
     }
 
-    fn take_provided_action(&mut self, hand_action: HandAction, active_state: ActiveState) {
+    fn take_provided_action(
+        &mut self,
+        hand_action: HandAction,
+        active_state: ActiveState,
+    ) -> Result<(), TableError> {
         let difference = self.get_largest_active_bet() - active_state.current_bet;
 
         // Now check how to advance the hand
         match hand_action {
             HandAction::Fold => {
-                self.get_current_player_mut().fold();
+                self.get_current_player_mut().fold()?;
                 let table_action = TableAction::TakePlayerAction(
                     self.get_current_player_mut().get_id(),
                     HandAction::Fold,
                 );
-                self.round_actions.push(table_action);
+                self.push_action(table_action);
             }
             HandAction::Check => {
                 // All in already, so stay all in
                 if difference == 0 {
-                    self.get_current_player_mut().bet(0);
+                    self.get_current_player_mut().bet(0)?;
                     let table_action = TableAction::TakePlayerAction(
                         self.get_current_player_mut().get_id(),
                         HandAction::Check,
                     );
-                    self.round_actions.push(table_action);
+                    self.push_action(table_action);
                 } else {
-                    self.get_current_player_mut().fold();
+                    self.get_current_player_mut().fold()?;
                     let table_action = TableAction::TakePlayerAction(
                         self.get_current_player_mut().get_id(),
                         HandAction::Fold,
                     );
-                    self.round_actions.push(table_action);
+                    self.push_action(table_action);
                 }
             }
             HandAction::Call => {
-                let bet_amount = self.get_current_player_mut().bet(difference);
+                let bet_amount = self.get_current_player_mut().bet(difference)?;
                 let index = self.get_current_player_mut().get_id() as usize;
                 *self.player_bets.get_mut(index).unwrap() += bet_amount;
                 let table_action = TableAction::TakePlayerAction(
                     self.get_current_player_mut().get_id(),
                     HandAction::Call,
                 );
-                self.round_actions.push(table_action);
+                self.push_action(table_action);
+            }
+            // A raise of less than nothing would take chips back out of the pot, so it is a call
+            HandAction::Raise(raise_amount) if raise_amount < 0 => {
+                return self.take_provided_action(HandAction::Call, active_state);
             }
             HandAction::Raise(raise_amount) => {
-                // Ensure the bet isn't larger than the pot limit (pot + amount required to call)
-                let acceptable_bet =
-                    min(raise_amount + difference, self.get_pot_size() + difference);
-                let bet_amount = self.get_current_player_mut().bet(acceptable_bet);
+                let acceptable_bet = match self.config.variant.get_fixed_limit() {
+                    // Fixed limit raises are always one bet, and once the bets are capped a raise is a call
+                    Some(fixed_limit) => {
+                        if self.get_bets_this_stage() >= fixed_limit.max_bets {
+                            return self.take_provided_action(HandAction::Call, active_state);
+                        }
+                        let street_index = self.config.variant.get_street_index(self.table_state);
+                        fixed_limit.bet_sizes[street_index] + difference
+                    }
+                    // Ensure the bet isn't larger than the pot limit (pot + amount required to call)
+                    None => min(raise_amount + difference, self.get_pot_size() + difference),
+                };
+                let bet_amount = self.get_current_player_mut().bet(acceptable_bet)?;
                 let index = self.get_current_player_mut().get_id() as usize;
                 *self.player_bets.get_mut(index).unwrap() += bet_amount;
                 let table_action = TableAction::TakePlayerAction(
                     self.get_current_player_mut().get_id(),
                     HandAction::Raise(bet_amount - difference),
                 );
-                self.round_actions.push(table_action);
+                self.push_action(table_action);
             }
         }
+        Ok(())
+    }
+
+    /// Counts the bets and raises made since the current betting stage started
+    fn get_bets_this_stage(&self) -> usize {
+        self.round_actions
+            .iter()
+            .rev()
+            .take_while(|action| {
+                !matches!(
+                    action,
+                    TableAction::DealCards(_) | TableAction::AdvanceToStage(_)
+                )
+            })
+            .filter(|action| {
+                matches!(
+                    action,
+                    TableAction::TakePlayerAction(_, HandAction::Raise(_))
+                )
+            })
+            .count()
     }
 
     pub fn get_pot_size(&self) -> i32 {
         self.player_bets.iter().sum::<i32>()
     }
 
-    fn get_player_result_string(player: &Player, rank: &usize) -> String {
-        let death_round = {
-            match player.death_hand_number {
-                None => "None".to_string(),
-                Some(a) => a.to_string(),
-            }
+    fn get_death_round_string(player: &Player) -> String {
+        match player.death_hand_number {
+            None => "None".to_string(),
+            Some(a) => a.to_string(),
+        }
+    }
+
+    fn get_player_result_string(
+        &self,
+        player: &Player,
+        rank: &usize,
+        prize: Option<f64>,
+    ) -> String {
+        let death_round = Table::get_death_round_string(player);
+        let mut result_string = format!("Rank:{rank:>3}, Death Round:,{death_round:>5}, ");
+        if self.config.all_in_adjusted {
+            result_string += format!(
+                "Net Chips:{:>7}, All-In Adjusted:{:>9.2}, ",
+                player.get_net_chips(),
+                self.get_all_in_adjusted_net_chips(player)
+            )
+            .as_str();
+        }
+        if let Some(prize) = prize {
+            // Until one player is left the prizes of anyone still alive aren't decided yet
+            let prize_label = if self.get_alive_player_count() > 1 {
+                "Equity"
+            } else {
+                "Prize"
+            };
+            result_string += format!("{prize_label}:{prize:>10.2}, ").as_str();
+        }
+        if self.config.rebuy.is_some() {
+            result_string += format!("Rebuys:{:>3}, ", player.rebuy_count).as_str();
+        }
+        if self.config.bounty.is_some() {
+            result_string += format!(
+                "Knockouts:{:>3}, Bounty Winnings:{:>6}, ",
+                player.knockout_count, player.bounty_winnings
+            )
+            .as_str();
+        }
+        result_string + format!("Player: {player}\n").as_str()
+    }
+
+    fn get_cash_game_result_string(&self, player: &Player, rank: &usize) -> String {
+        let hands_played = self.hand_number;
+        let net_chips = player.get_net_chips();
+        let chips_per_hundred_hands = if hands_played == 0 {
+            0.0
+        } else {
+            net_chips as f64 * 100.0 / hands_played as f64
         };
-        format!("Rank:{rank:>3}, Death Round:,{death_round:>5}, Player: {player}\n")
+        let mut result_string = format!("Rank:{rank:>3}, Net Chips:{net_chips:>7}, ");
+        if self.config.all_in_adjusted {
+            result_string += format!(
+                "All-In Adjusted:{:>9.2}, ",
+                self.get_all_in_adjusted_net_chips(player)
+            )
+            .as_str();
+        }
+        result_string
+            + format!("Chips/100 Hands:{chips_per_hundred_hands:>9.2}, Player: {player}\n").as_str()
     }
 
-    pub fn get_results(&self) -> String {
+    /// Gets every player sorted from best to worst along with their rank, tied players share a rank
+    pub fn get_ranked_players(&self) -> Vec<(usize, Player)> {
         let mut players_copy = self.players.clone();
-        players_copy.sort_by(|a, b| b.cmp(a));
+        if self.config.cash_game.is_some() {
+            // Nobody is knocked out of a cash game, so players are ranked by what they won or lost
+            players_copy.sort_by_key(|x| Reverse(x.get_net_chips()));
+        } else {
+            players_copy.sort_by(|a, b| b.cmp(a));
+        }
         let mut rank = 1;
-        let mut result_string = Table::get_player_result_string(&players_copy[0], &rank);
+        let mut ranked_players = vec![(rank, players_copy[0])];
         for (i, player) in players_copy.iter().skip(1).enumerate() {
             // The players didn't tie, so increase the rank
-            if player != players_copy.get(i).unwrap() {
+            if !self.is_tied(player, players_copy.get(i).unwrap()) {
                 rank = i + 2;
             }
-            result_string += &Table::get_player_result_string(player, &rank);
+            ranked_players.push((rank, *player));
+        }
+        ranked_players
+    }
+
+    fn is_tied(&self, player1: &Player, player2: &Player) -> bool {
+        if self.config.cash_game.is_some() {
+            player1.get_net_chips() == player2.get_net_chips()
+        } else {
+            player1 == player2
+        }
+    }
+
+    /// Gets the prize for each ranked player. Players that busted share the prizes of the places they
+    /// tied for, and players still alive split the prizes that are left by ICM equity.
+    fn get_prizes(ranked_players: &[(usize, Player)], payouts: &PayoutStructure) -> Vec<f64> {
+        // Alive players always rank above busted players
+        let stacks: Vec<i32> = ranked_players
+            .iter()
+            .filter(|(_, player)| player.is_alive())
+            .map(|(_, player)| player.total_money)
+            .collect();
+        let prizes: Vec<f64> = (0..stacks.len()).map(|x| payouts.get_prize(x)).collect();
+        let mut player_prizes = get_icm_equities(&stacks, &prizes);
+        for (rank, _) in ranked_players.iter().skip(stacks.len()) {
+            let tied_count = ranked_players.iter().filter(|x| x.0 == *rank).count();
+            let tied_prizes: f64 = (rank - 1..rank - 1 + tied_count)
+                .map(|x| payouts.get_prize(x))
+                .sum();
+            player_prizes.push(tied_prizes / tied_count as f64);
+        }
+        player_prizes
+    }
+
+    pub fn get_results(&self) -> String {
+        let mut result_string = String::new();
+        // Explain why the game ended early, the players still alive are then ranked by chips
+        if let Some(stop_reason) = self.get_stop_reason() {
+            if self.get_alive_player_count() > self.config.finish_player_count {
+                result_string +=
+                    format!("{stop_reason}, players still alive are ranked by their chips.\n")
+                        .as_str();
+            }
+        }
+        let ranked_players = self.get_ranked_players();
+        let prizes = match (&self.config.payouts, &self.config.cash_game) {
+            (Some(payouts), None) => Some(Table::get_prizes(&ranked_players, payouts)),
+            _ => None,
+        };
+        result_string += ranked_players
+            .iter()
+            .enumerate()
+            .map(|(i, (rank, player))| match self.config.cash_game {
+                Some(_) => self.get_cash_game_result_string(player, rank),
+                None => self.get_player_result_string(player, rank, prizes.as_ref().map(|x| x[i])),
+            })
+            .collect::<String>()
+            .as_str();
+        // Bounty tournaments also list every knockout
+        if self.config.bounty.is_some() {
+            result_string += "Knockouts:\n";
+            for knockout in &self.knockouts {
+                let winners: Vec<String> = knockout.winners.iter().map(|x| x.to_string()).collect();
+                result_string += format!(
+                    "Hand:{:>5}, Player:{:>3}, Knocked Out By: [{}]\n",
+                    knockout.hand_number,
+                    knockout.eliminated,
+                    winners.join(", ")
+                )
+                .as_str();
+            }
+        }
+        result_string += "Player Stats:\n";
+        for (player, stats) in self.players.iter().zip(&self.player_stats) {
+            result_string += format!("Player:{:>3}, {stats}\n", player.get_id()).as_str();
         }
         result_string
     }
 
+    /// Returns how many players have not been eliminated yet
+    pub fn get_alive_player_count(&self) -> usize {
+        self.players.iter().filter(|x| x.is_alive()).count()
+    }
+
     pub fn is_game_over(&self) -> bool {
-        let alive_player_count = self
-            .players
-            .iter()
-            .map(|x| i8::from(x.is_alive()))
-            .reduce(|x, y| x + y)
-            .unwrap();
-        alive_player_count == 1
+        let is_finished = match &self.config.cash_game {
+            // A cash game only stops once its last hand has been played out
+            Some(cash_game) => !self.hand_in_progress && self.hand_number >= cash_game.hand_count,
+            None => self.get_alive_player_count() <= self.config.finish_player_count,
+        };
+        is_finished || self.get_stop_reason().is_some()
+    }
+
+    /// Gets the limit that stopped the game, None if no limit has been reached.
+    /// Limits are only checked between hands, so a hand that was started is always played out.
+    pub fn get_stop_reason(&self) -> Option<StopReason> {
+        if self.hand_in_progress {
+            return None;
+        }
+        let stop_conditions = &self.config.stop_conditions;
+        if let Some(max_hands) = stop_conditions.max_hands {
+            if self.hand_number >= max_hands {
+                return Some(StopReason::HandLimit(max_hands));
+            }
+        }
+        if let Some(max_duration) = stop_conditions.max_duration {
+            if self.start_time.elapsed() >= max_duration {
+                return Some(StopReason::TimeLimit(max_duration));
+            }
+        }
+        if let Some(max_level) = stop_conditions.max_level {
+            if self.level_index + 1 >= max_level && self.is_level_over_before(self.hand_number + 1)
+            {
+                return Some(StopReason::LevelCap(max_level));
+            }
+        }
+        None
     }
 
     /// Deals cards to all players that are still alive,
     /// marks any dead players with their death turn number,
     /// moves the dealer chip,
     /// update all 5 table cards,
-    pub fn deal(&mut self) {
+    pub fn deal(&mut self) -> Result<(), TableError> {
         // If the game is over do not do anything
         if self.is_game_over() {
-            return;
+            self.finish_stored_game();
+            return Ok(());
         }
         // Increment the hand number
         self.hand_number += 1;
         // Reset the state for a new round of betting
         self.reset_state_for_new_round();
-        // Check all players for death
-        self.check_for_player_death();
-        // Make a deck
-        let deck = Card::generate_shuffled_deck();
+        // Move to the next level of the schedule if this one is over
+        self.update_level();
+        // Check all players for death, in a cash game busted players rebuy instead
+        match self.config.cash_game.as_ref().map(|x| x.stack_size) {
+            Some(stack_size) => self.top_up_stacks(stack_size),
+            None => self.check_for_player_death(),
+        }
+        // Those deaths may have finished the game, so don't deal a hand no one will play
+        if self.is_game_over() {
+            self.finish_stored_game();
+            return Ok(());
+        }
+        // Make a deck of the cards the game is played with
+        let deck = self.config.variant.generate_shuffled_deck(&mut self.rng);
         let mut deck_iterator = deck.iter();
         // Deal cards to the players and the table
         self.deal_table_cards(&mut deck_iterator);
-        self.deal_player_cards_collect_ante(&mut deck_iterator);
+        self.deal_player_cards_collect_ante(&mut deck_iterator)?;
+        // The antes are in the pot, so from here on an error voids this hand
+        self.hand_in_progress = true;
         // Find the next alive player index for dealer button
-        self.find_next_deal_button_index_and_update_current_player();
-        // If it is time to increase the ante do so.
-        if (self.hand_number) % self.ante_round_increase == 0 {
+        self.find_next_deal_button_index_and_update_current_player()?;
+        self.post_blinds()?;
+        self.start_hand_history();
+        self.start_stored_hand();
+        // If it is time to increase the ante do so, fixed limit games keep the ante their bets are sized for
+        if self.config.ante_increases
+            && self.config.cash_game.is_none()
+            && self.config.level_schedule.is_none()
+            && self.config.variant.get_fixed_limit().is_none()
+            && (self.hand_number) % self.ante_round_increase == 0
+        {
             self.ante += Table::ANTE_INCREASE_AMOUNT;
         }
+        Ok(())
+    }
+
+    /// Gets the level of the schedule being played, None without a schedule
+    fn get_level(&self) -> Option<&Level> {
+        self.config
+            .level_schedule
+            .as_ref()
+            .map(|schedule| &schedule.levels[self.level_index])
     }
 
-    /// Finds the next dealer button index (next player in the list that is alive
-    fn find_next_deal_button_index_and_update_current_player(&mut self) {
-        let next_dealer_button_index = self.get_next_dealer_button_index();
+    /// Sets the forced bets to the ones of the current level
+    fn apply_level(&mut self) {
+        if let Some(level) = self.get_level().cloned() {
+            self.small_blind = level.small_blind;
+            self.big_blind = level.big_blind;
+            self.ante = level.ante;
+        }
+    }
+
+    /// Gets how many hands are left in the current level including the one being played,
+    /// None if the level isn't limited by hands
+    fn get_level_hands_left(&self) -> Option<i32> {
+        let hands = self.get_level()?.hands?;
+        Some(hands - (self.hand_number - self.level_start_hand))
+    }
+
+    /// Gets how many seconds are left in the current level, None if the level isn't timed
+    fn get_level_seconds_left(&self) -> Option<u64> {
+        let seconds = self.get_level()?.seconds?;
+        Some(seconds.saturating_sub(self.level_start_time.elapsed().as_secs()))
+    }
+
+    /// Returns true if the current level runs out of hands or time before the given hand is played,
+    /// the last level never ends
+    fn is_level_over_before(&self, hand_number: i32) -> bool {
+        let levels = match &self.config.level_schedule {
+            Some(schedule) => &schedule.levels,
+            None => return false,
+        };
+        if self.level_index + 1 >= levels.len() {
+            return false;
+        }
+        let is_out_of_hands = levels[self.level_index]
+            .hands
+            .is_some_and(|hands| hand_number - self.level_start_hand >= hands);
+        is_out_of_hands || self.get_level_seconds_left() == Some(0)
+    }
+
+    /// Moves on to the next level once the current one has run out of hands or time
+    fn update_level(&mut self) {
+        if self.config.level_schedule.is_none() {
+            return;
+        }
+        while self.is_level_over_before(self.hand_number) {
+            self.level_index += 1;
+            self.level_start_hand = self.hand_number;
+            self.level_start_time = Instant::now();
+            info!("Moving to level {}", self.level_index + 1);
+        }
+        self.apply_level();
+    }
+
+    /// Gets the json describing the forced bets and how long the current level lasts
+    fn get_level_json(&self) -> JsonValue {
+        object! {
+            number: self.get_level().map(|_| self.level_index + 1),
+            small_blind: self.small_blind,
+            big_blind: self.big_blind,
+            ante: self.ante,
+            hands_left: self.get_level_hands_left(),
+            seconds_left: self.get_level_seconds_left(),
+        }
+    }
+
+    /// Collects the blinds from the players after the button, then the player after the big blind acts first
+    fn post_blinds(&mut self) -> Result<(), TableError> {
+        // Games with up cards are brought in by the lowest card instead
+        if self.config.variant.has_bring_in() {
+            return self.post_bring_in();
+        }
+        if self.small_blind == 0 && self.big_blind == 0 {
+            return Ok(());
+        }
+        let big_blind_index = self.big_blind_index.unwrap();
+        for (index, blind) in [
+            (self.small_blind_index, self.small_blind),
+            (big_blind_index, self.big_blind),
+        ] {
+            let player = &mut self.players[index];
+            // A dead small blind is not posted by anyone
+            if !player.is_alive() {
+                continue;
+            }
+            self.player_bets[index] += player.bet(blind)?;
+            // Like the ante the blinds don't count as a turn, so the big blind still gets to act
+            player.has_had_turn_this_round = false;
+        }
+        if self.big_blind > 0 {
+            self.forced_bet_index = Some(big_blind_index);
+        }
+        self.current_player_index = big_blind_index;
+        self.update_current_player_index_to_next_active()
+    }
+
+    /// The active player with the lowest up card posts the small blind, or the ante without blinds, and the
+    /// player after them acts next. They only act again if someone raises
+    fn post_bring_in(&mut self) -> Result<(), TableError> {
+        let variant = self.config.variant;
+        let stage = self.table_state;
+        let bring_in_index = (0..self.players.len())
+            .filter_map(|seat| match self.players[seat].player_state {
+                PlayerState::Active(active) => {
+                    Some((seat, variant.get_up_cards(&active.hand, stage)))
+                }
+                PlayerState::Folded => None,
+            })
+            .min_by_key(|(_, up_cards)| get_bring_in_rank(up_cards[0]))
+            .map(|(seat, _)| seat)
+            .unwrap();
+        let bring_in = if self.small_blind > 0 {
+            self.small_blind
+        } else {
+            self.ante
+        };
+        // Without a bring-in to post that player still acts first
+        self.current_player_index = self.get_seat_before(bring_in_index);
+        if bring_in > 0 {
+            self.player_bets[bring_in_index] += self.players[bring_in_index].bet(bring_in)?;
+            self.current_player_index = bring_in_index;
+            self.forced_bet_index = Some(bring_in_index);
+        }
+        self.update_current_player_index_to_next_active()
+    }
+
+    /// Gets the seat of the active player with the best up cards, a tie goes to the first seat after the button
+    fn get_best_showing_index(&self) -> usize {
+        let variant = self.config.variant;
+        let player_count = self.players.len();
+        (1..=player_count)
+            .map(|offset| (self.dealer_button_index + offset) % player_count)
+            .filter_map(|seat| match self.players[seat].player_state {
+                PlayerState::Active(active) => {
+                    let up_cards = variant.get_up_cards(&active.hand, self.table_state);
+                    Some((seat, get_showing_rank(&up_cards)))
+                }
+                PlayerState::Folded => None,
+            })
+            // The last of equal maximums is picked, so reversed that is the first seat after the button
+            .rev()
+            .max_by(|(_, a), (_, b)| a.cmp(b))
+            .map(|(seat, _)| seat)
+            .unwrap()
+    }
+
+    fn get_seat_before(&self, seat: usize) -> usize {
+        (seat + self.players.len() - 1) % self.players.len()
+    }
+
+    /// Returns true while a dealt hand is still being played
+    pub fn is_hand_in_progress(&self) -> bool {
+        self.hand_in_progress
+    }
+
+    #[cfg(test)]
+    pub fn get_ante(&self) -> i32 {
+        self.ante
+    }
+
+    /// Sets the ante that will be collected starting with the next deal
+    pub fn set_ante(&mut self, ante: i32) {
+        self.ante = ante;
+    }
+
+    pub fn get_players(&self) -> &[Player] {
+        &self.players
+    }
+
+    #[cfg(test)]
+    pub fn get_players_mut(&mut self) -> &mut [Player] {
+        &mut self.players
+    }
+
+    pub fn get_dealer_button_index(&self) -> usize {
+        self.dealer_button_index
+    }
+
+    pub fn get_config(&self) -> &TableConfig {
+        &self.config
+    }
+
+    pub fn get_hand_number(&self) -> i32 {
+        self.hand_number
+    }
+
+    /// Removes the player in the given seat between hands so they can sit at another table,
+    /// everyone seated after them moves down one seat.
+    pub fn remove_player(&mut self, seat: usize) -> Result<Player, TableError> {
+        if self.hand_in_progress {
+            return Err(TableError::HandInProgress);
+        }
+        let mut player = self.players.remove(seat);
+        player.player_state = PlayerState::Folded;
+        self.player_bets.remove(seat);
+        self.all_in_adjustments.remove(seat);
+        self.player_stats.remove(seat);
+        // The seats moved, so the last pot can't be used to credit knockouts anymore
+        self.pot_winners_by_seat.clear();
+        // The blinds can't be followed across the moved seats, so they start over from the button
+        self.big_blind_index = None;
+        for (index, player) in self.players.iter_mut().enumerate().skip(seat) {
+            player.set_id(index as i8);
+        }
+        // Keep the button with the same player, or if the button left give it to the seat before
+        // so the player after the one that left is the next dealer
+        if self.dealer_button_index >= seat && !self.players.is_empty() {
+            self.dealer_button_index =
+                (self.dealer_button_index + self.players.len() - 1) % self.players.len();
+        }
+        self.current_player_index = self.dealer_button_index;
+        Ok(player)
+    }
+
+    /// Seats a player from another table in the last seat between hands, returns their new seat
+    pub fn add_player(&mut self, mut player: Player) -> Result<usize, TableError> {
+        if self.hand_in_progress {
+            return Err(TableError::HandInProgress);
+        }
+        let max = min(Table::MAX_PLAYERS, self.config.variant.get_max_players());
+        if self.players.len() >= max {
+            return Err(TableError::TooManyPlayers {
+                players: self.players.len() + 1,
+                max,
+            });
+        }
+        let seat = self.players.len();
+        player.set_id(seat as i8);
+        player.player_state = PlayerState::Folded;
+        self.players.push(player);
+        self.player_bets.push(0);
+        self.all_in_adjustments.push(0.0);
+        self.player_stats.push(PlayerStats::default());
+        self.pot_winners_by_seat.clear();
+        self.big_blind_index = None;
+        Ok(seat)
+    }
+
+    /// Moves the dealer button and blinds by the table's button rule,
+    /// then the action starts after the button
+    fn find_next_deal_button_index_and_update_current_player(&mut self) -> Result<(), TableError> {
+        let (button_index, small_blind_index, big_blind_index) = self.get_next_positions();
+        self.dealer_button_index = button_index;
+        self.small_blind_index = small_blind_index;
+        self.big_blind_index = Some(big_blind_index);
+        // The deal was recorded before anyone was knocked out, so record where the button ended up
+        if let Some(TableAction::DealCards(deal_information)) = self.round_actions.first_mut() {
+            deal_information.dealer_button_index = button_index;
+        }
         // Set the current dealer button, and then increment that
-        self.current_player_index = next_dealer_button_index;
-        self.dealer_button_index = next_dealer_button_index;
-        self.update_current_player_index_to_next_active();
+        self.current_player_index = button_index;
+        self.update_current_player_index_to_next_active()
+    }
+
+    /// Gets the button, small blind, and big blind seats of the next hand
+    fn get_next_positions(&self) -> (usize, usize, usize) {
+        let is_heads_up = self.get_alive_player_count() == 2;
+        let last_big_blind_index = match (self.config.button_rule, self.big_blind_index) {
+            (ButtonRule::NextAlive, _) | (_, None) => {
+                let button_index = self.get_next_dealer_button_index();
+                // Heads up the button posts the small blind
+                let small_blind_index = if is_heads_up {
+                    button_index
+                } else {
+                    self.get_next_alive_player_index(button_index)
+                };
+                let big_blind_index = self.get_next_alive_player_index(small_blind_index);
+                return (button_index, small_blind_index, big_blind_index);
+            }
+            (_, Some(last_big_blind_index)) => last_big_blind_index,
+        };
+        // Otherwise nobody skips the big blind, it always moves to the next alive player
+        let big_blind_index = self.get_next_alive_player_index(last_big_blind_index);
+        if is_heads_up {
+            let button_index = self.get_next_alive_player_index(big_blind_index);
+            return (button_index, button_index, big_blind_index);
+        }
+        match self.config.button_rule {
+            ButtonRule::DeadButton => (
+                self.small_blind_index,
+                last_big_blind_index,
+                big_blind_index,
+            ),
+            _ => {
+                let small_blind_index = self.get_previous_alive_player_index(big_blind_index);
+                let button_index = self.get_previous_alive_player_index(small_blind_index);
+                (button_index, small_blind_index, big_blind_index)
+            }
+        }
     }
 
     fn get_next_dealer_button_index(&self) -> usize {
-        let mut next_dealer_button_index = self.dealer_button_index;
+        self.get_next_alive_player_index(self.dealer_button_index)
+    }
+
+    fn get_next_alive_player_index(&self, index: usize) -> usize {
+        let mut next_dealer_button_index = index;
         for _ in 0..self.players.len() {
             // set the next dealer index by finding the next alive player
 
@@ -408,17 +1138,27 @@ impl Table {
         next_dealer_button_index
     }
 
-    fn update_current_player_index_to_next_active(&mut self) {
-        self.current_player_index = self.get_next_valid_player(self.current_player_index);
-        if !self.get_current_player_mut().is_alive() {
-            panic!("Current player not alive after update!")
-        }
-        match self.get_current_player_mut().player_state {
-            PlayerState::Folded => {
-                panic!("Current player not active after update")
+    fn get_previous_alive_player_index(&self, index: usize) -> usize {
+        let mut previous_index = index;
+        for _ in 0..self.players.len() {
+            previous_index = previous_index
+                .checked_sub(1)
+                .unwrap_or(self.players.len() - 1);
+            if self.players[previous_index].is_alive() {
+                break;
             }
-            PlayerState::Active(_) => {}
         }
+        previous_index
+    }
+
+    /// Moves on to the next player still in the hand, it is an error if nobody is left to act
+    fn update_current_player_index_to_next_active(&mut self) -> Result<(), TableError> {
+        self.current_player_index = self.get_next_valid_player(self.current_player_index);
+        let player = self.get_current_player();
+        if !player.is_alive() || !player.player_state.is_active() {
+            return Err(TableError::NoActivePlayer);
+        }
+        Ok(())
     }
 
     fn get_next_valid_player(&self, mut index_to_search_from: usize) -> usize {
@@ -443,42 +1183,125 @@ impl Table {
         index_to_search_from
     }
 
-    /// Deals cards for the flop, turn, and river
+    /// Deals the board cards the game uses, the flop, turn, and river in Hold'em
     fn deal_table_cards(&mut self, deck_iterator: &mut Iter<Card>) {
-        self.flop = Option::from([
-            *deck_iterator.next().unwrap(),
-            *deck_iterator.next().unwrap(),
-            *deck_iterator.next().unwrap(),
-        ]);
-        self.turn = Option::from(*deck_iterator.next().unwrap());
-        self.river = Option::from(*deck_iterator.next().unwrap());
+        let board_card_count = self.config.variant.get_board_card_count();
+        self.board = deck_iterator.take(board_card_count).copied().collect();
     }
 
     /// Mark all players that died from the last round as dead now
     fn check_for_player_death(&mut self) {
-        // Check if players died on the past round
+        // The hand number was already increased for the coming hand
+        let hands_played = self.hand_number - 1;
+        // Check if players died on the past round, during the rebuy period they re-enter instead
+        let mut eliminated_seats = vec![];
+        for (seat, player) in self.players.iter_mut().enumerate() {
+            // Without chips or enough to cover the ante the player can't play another hand
+            let is_busted = player.total_money == 0 || player.total_money < self.ante;
+            if player.death_hand_number.is_none() && is_busted {
+                match &self.config.rebuy {
+                    Some(rebuy) if hands_played <= rebuy.period_hands => {
+                        player.rebuy(DEFAULT_START_MONEY)
+                    }
+                    _ => {
+                        player.death_hand_number = Some(self.hand_number);
+                        eliminated_seats.push(seat);
+                    }
+                }
+            }
+        }
+        for seat in eliminated_seats {
+            self.record_knockout(seat);
+        }
+        // Everyone still in gets the add-on once the rebuy period is over
+        if let Some(rebuy) = &self.config.rebuy {
+            if hands_played == rebuy.period_hands && rebuy.add_on_chips > 0 {
+                for player in self.players.iter_mut().filter(|x| x.is_alive()) {
+                    player.add_on(rebuy.add_on_chips);
+                }
+            }
+        }
+    }
+
+    /// Credits the knockout of the player in the given seat to whoever won the chips they put in the
+    /// last pot, splitting the bounty between them if there is one
+    fn record_knockout(&mut self, seat: usize) {
+        let winners = self
+            .pot_winners_by_seat
+            .get(seat)
+            .cloned()
+            .unwrap_or_default();
+        for (i, winner_id) in winners.iter().enumerate() {
+            let winner = &mut self.players[*winner_id as usize];
+            winner.knockout_count += 1;
+            if let Some(bounty) = self.config.bounty {
+                winner.bounty_winnings += bounty / winners.len() as i32;
+                if (i as i32) < bounty % winners.len() as i32 {
+                    winner.bounty_winnings += 1;
+                }
+            }
+        }
+        let eliminated = self.players[seat].get_id();
+        self.push_action(TableAction::EliminatePlayer(eliminated, winners.clone()));
+        self.knockouts.push(Knockout {
+            hand_number: self.hand_number,
+            eliminated,
+            winners,
+        });
+    }
+
+    #[cfg(test)]
+    pub fn get_knockouts(&self) -> &[Knockout] {
+        &self.knockouts
+    }
+
+    /// Remembers that the given winners took chips the contributing seats put in the pot
+    fn record_pot_winners(&mut self, contributing_seats: &[usize], winners: &[i8]) {
+        for &seat in contributing_seats {
+            for &winner in winners {
+                let seat_winners = &mut self.pot_winners_by_seat[seat];
+                if winner as usize != seat && !seat_winners.contains(&winner) {
+                    seat_winners.push(winner);
+                }
+            }
+        }
+    }
+
+    /// Rebuys every player below the cash game stack back up to it
+    fn top_up_stacks(&mut self, stack_size: i32) {
         for player in &mut self.players {
-            if player.death_hand_number.is_none() && player.total_money < self.ante {
-                player.death_hand_number = Some(self.hand_number)
+            if player.total_money < stack_size {
+                player.total_buy_in += stack_size - player.total_money;
+                player.total_money = stack_size;
             }
         }
     }
 
     /// Deal cards to the alive players and collect the ante from them.
-    fn deal_player_cards_collect_ante(&mut self, deck_iterator: &mut Iter<Card>) {
+    fn deal_player_cards_collect_ante(
+        &mut self,
+        deck_iterator: &mut Iter<Card>,
+    ) -> Result<(), TableError> {
         // Deal every alive player cards now
+        self.dealt_hole_cards.clear();
         for (i, player) in &mut self.players.iter_mut().enumerate() {
             if player.is_alive() {
-                let card1 = *deck_iterator.next().unwrap();
-                let card2 = *deck_iterator.next().unwrap();
-                player.deal([card1, card2]);
-                *self.player_bets.get_mut(i).unwrap() += player.bet(self.ante);
+                let cards: Vec<Card> = deck_iterator
+                    .take(self.config.variant.get_hole_card_count())
+                    .copied()
+                    .collect();
+                player.deal(&cards);
+                self.dealt_hole_cards.extend(&cards);
+                // Remembered to rank players that bust on the same hand
+                player.hand_starting_money = player.total_money;
+                *self.player_bets.get_mut(i).unwrap() += player.bet(self.ante)?;
                 // the ante doesn't count as a turn so clarify the bot hasn't had a turn
                 player.has_had_turn_this_round = false;
             } else {
                 player.player_state = PlayerState::Folded;
             }
         }
+        Ok(())
     }
 
     pub fn get_state_string_for_current_player(&self) -> String {
@@ -493,12 +1316,12 @@ impl Table {
         let player_strings: Vec<_> = self
             .players
             .iter()
-            .map(|x| x.as_json_no_secret_data())
+            .map(|x| self.get_player_json_no_secret_data(x))
             .collect();
         object! {
             id: id,
             current_bet: self.get_current_player().player_state.get_bet(),
-            cards: self.get_current_player().player_state.get_cards_json(),
+            cards: self.get_dealt_cards_json(self.get_current_player()),
             hand_number: self.hand_number,
             current_highest_bet: self.get_largest_active_bet(),
             flop: self.get_flop_string_secret(),
@@ -508,8 +1331,35 @@ impl Table {
             players: player_strings,
             actions: get_vec_of_strings_from_actions(&self.round_actions),
             previous_actions: get_vec_of_strings_from_actions(&self.previous_round_actions),
+            level: self.get_level_json(),
         }
     }
+    /// Gets the json of the cards a player has been dealt so far, games like stud deal more on later streets
+    fn get_dealt_cards_json(&self, player: &Player) -> JsonValue {
+        match player.player_state {
+            PlayerState::Active(active) => {
+                let dealt = self.config.variant.get_hole_cards_dealt(self.table_state);
+                HoleCards::new(&active.hand[..dealt]).as_json()
+            }
+            PlayerState::Folded => player.player_state.get_cards_json(),
+        }
+    }
+
+    /// Gets the json of a player that everyone can see, which includes their up cards in games that have them
+    fn get_player_json_no_secret_data(&self, player: &Player) -> JsonValue {
+        let mut json = player.as_json_no_secret_data();
+        if let PlayerState::Active(active) = player.player_state {
+            if self.config.variant.has_up_cards() {
+                let up_cards = self
+                    .config
+                    .variant
+                    .get_up_cards(&active.hand, self.table_state);
+                json["player_state"]["details"]["up_cards"] = HoleCards::new(&up_cards).as_json();
+            }
+        }
+        json
+    }
+
     fn is_betting_over(&self) -> bool {
         let all_players_ready = self.check_all_players_ready_for_next_round();
         let all_players_equal_bet = self.check_all_active_players_same_bet();
@@ -546,6 +1396,14 @@ impl Table {
             .reduce(|x, y| x && y)
             .unwrap()
     }
+    /// Counts the players still in the hand that have chips left to bet
+    fn get_players_able_to_bet_count(&self) -> usize {
+        self.players
+            .iter()
+            .filter(|player| player.player_state.is_active() && player.total_money > 0)
+            .count()
+    }
+
     fn get_active_player_count(&self) -> usize {
         self.players
             .iter()
@@ -564,21 +1422,19 @@ impl Table {
     ///
     /// * `players`: A list of players sorted by their bet amounts
     ///
-    /// returns: Vec<i32> The difference between all the current bets
+    /// returns: Result<Vec<i32>, TableError> The difference between all the current bets
     ///
-    fn get_bet_increases_amount(players: &[Player]) -> Vec<i32> {
+    fn get_bet_increases_amount(players: &[Player]) -> Result<Vec<i32>, TableError> {
         // Check that the slice is sorted
         let bets: Vec<i32> = players
             .iter()
             .map(|x| match x.player_state {
-                PlayerState::Folded => {
-                    panic!("Passed a folded player.")
-                }
-                PlayerState::Active(a) => a.current_bet,
+                PlayerState::Folded => Err(TableError::FoldedPlayerInPot(x.get_id())),
+                PlayerState::Active(a) => Ok(a.current_bet),
             })
-            .collect();
+            .collect::<Result<_, _>>()?;
         if bets.windows(2).any(|w| w[0] > w[1]) {
-            panic!("Players are not sorted by their bets.")
+            return Err(TableError::UnsortedBets);
         }
         let mut return_vector = vec![0; players.len()];
         let mut prev_bet = 0;
@@ -589,12 +1445,16 @@ impl Table {
                 return_vector[i] = push_back_amount;
             }
         }
-        return_vector
+        Ok(return_vector)
     }
     /// Picks winner(s), gives out winnings, and deals a new hand
-    fn resolve_hand(&mut self) {
+    fn resolve_hand(&mut self) -> Result<(), TableError> {
         // Generate the result string
         let mut result_string = "".to_string();
+        self.pot_winners_by_seat = vec![vec![]; self.players.len()];
+        let money_before: Vec<i32> = self.players.iter().map(|x| x.total_money).collect();
+        let bets = self.player_bets.clone();
+        let mut boards = vec![self.board[..self.get_board_cards_shown()].to_vec()];
         // This is the everyone but one person has folded case, give that person the winnings
         if self.get_active_player_count() == 1 {
             let pot_size = self.get_pot_size();
@@ -607,88 +1467,740 @@ impl Table {
                 })
                 .unwrap();
             winner.total_money += pot_size;
+            let winner_id = winner.get_id();
+            let contributing_seats: Vec<usize> = (0..self.player_bets.len())
+                .filter(|seat| self.player_bets[*seat] > 0)
+                .collect();
+            self.record_pot_winners(&contributing_seats, &[winner_id]);
+            self.player_bets.iter_mut().for_each(|bet| *bet = 0);
             result_string += format!(
                 "The following player won because everyone else folded: {}",
-                winner.get_id()
+                winner_id
             )
             .as_str();
         } else {
-            let header = self.make_comparison_header();
-            result_string += header.as_str();
-            // Otherwise we need to give out winnings based on hand strength
-            let sorted_players = self.get_hand_result();
-            // All player hands need to be shown so collect that information
-            for (index, list_of_players) in sorted_players.iter().enumerate() {
-                let rank = index + 1;
-                for player in list_of_players {
-                    if let PlayerState::Active(state) = player.player_state {
-                        result_string += format!(
-                            "Player {} ranked {} with hand {} {}\n",
-                            player.get_id(),
-                            rank,
-                            state.hand[0],
-                            state.hand[1]
-                        )
-                        .as_str();
-                    }
+            let expected_winnings = self.get_all_in_expected_winnings();
+            boards = self.get_run_out_boards();
+            if boards.len() == 1 {
+                result_string += self.showdown()?.as_str();
+            } else {
+                result_string += self.showdown_each_board(boards.clone())?.as_str();
+            }
+            if let Some(expected_winnings) = expected_winnings {
+                result_string += self
+                    .record_all_in_adjustments(&expected_winnings, &money_before)
+                    .as_str();
+            }
+        }
+        self.record_player_stats();
+        self.finish_hand_history(&money_before, &bets, boards);
+        info!("{result_string}");
+        self.push_action(TableAction::EvaluateHand(result_string));
+        self.hand_in_progress = false;
+        if self.config.deal_automatically {
+            self.deal()?;
+        }
+        Ok(())
+    }
+
+    /// Starts the hand history of the hand just dealt with the seats, the forced bets and the first cards dealt
+    fn start_hand_history(&mut self) {
+        let variant = self.config.variant;
+        let dealt_seats: Vec<usize> = (0..self.players.len())
+            .filter(|seat| self.players[*seat].is_alive())
+            .collect();
+        let get_blind_seat = |seat: Option<usize>, blind: i32| {
+            seat.filter(|seat| {
+                !variant.has_bring_in() && blind > 0 && self.players[*seat].is_alive()
+            })
+        };
+        let small_blind_seat = get_blind_seat(Some(self.small_blind_index), self.small_blind);
+        let big_blind_seat = get_blind_seat(self.big_blind_index, self.big_blind);
+        let mut history = HandHistory::new(
+            HandStart {
+                hand_number: self.hand_number,
+                game_name: variant.get_hand_history_name(),
+                small_blind: self.small_blind,
+                big_blind: self.big_blind,
+                seat_count: self.players.len(),
+                button: self.dealer_button_index,
+                small_blind_seat,
+                big_blind_seat,
+                stacks: dealt_seats
+                    .iter()
+                    .map(|seat| (*seat, self.players[*seat].hand_starting_money))
+                    .collect(),
+            },
+            variant.get_first_stage(),
+        );
+        let antes: Vec<i32> = self
+            .players
+            .iter()
+            .map(|player| min(self.ante, player.hand_starting_money))
+            .collect();
+        for &seat in &dealt_seats {
+            if antes[seat] > 0 {
+                let is_all_in = antes[seat] == self.players[seat].hand_starting_money;
+                history.add_post(seat, "posts the ante", antes[seat], is_all_in, false);
+            }
+        }
+        // Whatever each seat bet past the ante was a blind or the bring-in
+        let forced_bets = [
+            (small_blind_seat, "posts small blind"),
+            (big_blind_seat, "posts big blind"),
+            (
+                self.forced_bet_index.filter(|_| variant.has_bring_in()),
+                "brings in for",
+            ),
+        ];
+        for (seat, description) in forced_bets {
+            if let Some(seat) = seat {
+                let is_all_in = self.players[seat].total_money == 0;
+                let amount = self.player_bets[seat] - antes[seat];
+                history.add_post(seat, description, amount, is_all_in, true);
+            }
+        }
+        self.hand_history = Some(history);
+        self.add_stage_to_hand_history(None);
+    }
+
+    /// Starts the current betting stage in the hand history with the board cards and hole cards it dealt
+    fn add_stage_to_hand_history(&mut self, previous_stage: Option<BetStage>) {
+        let variant = self.config.variant;
+        let stage = self.table_state;
+        let board_shown = variant.get_board_cards_shown(stage);
+        let board_shown_before = previous_stage.map_or(0, |x| variant.get_board_cards_shown(x));
+        let hole_cards_dealt = variant.get_hole_cards_dealt(stage);
+        let hole_cards_dealt_before = previous_stage.map_or(0, |x| variant.get_hole_cards_dealt(x));
+        let dealt: Vec<(usize, Vec<Card>, Vec<Card>)> = self
+            .players
+            .iter()
+            .enumerate()
+            .filter(|_| hole_cards_dealt > hole_cards_dealt_before)
+            .filter_map(|(seat, player)| match player.player_state {
+                PlayerState::Active(active) => Some((
+                    seat,
+                    active.hand[..hole_cards_dealt_before].to_vec(),
+                    active.hand[hole_cards_dealt_before..hole_cards_dealt].to_vec(),
+                )),
+                PlayerState::Folded => None,
+            })
+            .collect();
+        if let Some(history) = &mut self.hand_history {
+            history.start_stage(
+                stage,
+                (
+                    &self.board[..board_shown_before],
+                    &self.board[board_shown_before..board_shown],
+                ),
+                &dealt,
+            );
+        }
+    }
+
+    /// Writes the action just taken to the hand history, the player had the given money before it
+    fn add_action_to_hand_history(&mut self, money_before: i32) {
+        if let Some(TableAction::TakePlayerAction(seat, hand_action)) = self.round_actions.last() {
+            let player = &self.players[*seat as usize];
+            let amount = money_before - player.total_money;
+            let is_all_in = amount > 0 && player.total_money == 0;
+            if let Some(history) = &mut self.hand_history {
+                history.add_action(*seat as usize, *hand_action, amount, is_all_in);
+            }
+        }
+    }
+
+    /// Finishes the hand history with who won what and writes it to the hand history file if there is one,
+    /// the same ending is written to the database. The money and bets are from before the pot was paid out
+    fn finish_hand_history(&mut self, money_before: &[i32], bets: &[i32], boards: Vec<Vec<Card>>) {
+        // Only the part of the largest bet that nobody else matched is returned
+        let largest_seat = (0..bets.len()).max_by_key(|seat| bets[*seat]);
+        let uncalled_bet = largest_seat.and_then(|largest_seat| {
+            let matched = (0..bets.len())
+                .filter(|seat| *seat != largest_seat)
+                .map(|seat| bets[seat])
+                .max()
+                .unwrap_or(0);
+            let uncalled = bets[largest_seat] - matched;
+            (uncalled > 0).then_some((largest_seat, uncalled))
+        });
+        let collected: Vec<i32> = (0..self.players.len())
+            .map(|seat| {
+                let won = self.players[seat].total_money - money_before[seat];
+                match uncalled_bet {
+                    Some((uncalled_seat, amount)) if uncalled_seat == seat => won - amount,
+                    _ => won,
                 }
+            })
+            .collect();
+        let shown_hands = match self.get_active_player_count() {
+            1 => vec![],
+            _ => self
+                .players
+                .iter()
+                .enumerate()
+                .filter_map(|(seat, player)| match player.player_state {
+                    PlayerState::Active(active) => Some((seat, active.hand.to_vec())),
+                    PlayerState::Folded => None,
+                })
+                .collect(),
+        };
+        let end = HandEnd {
+            pot: bets.iter().sum::<i32>() - uncalled_bet.map_or(0, |(_, amount)| amount),
+            uncalled_bet,
+            collected,
+            shown_hands,
+            boards,
+        };
+        if let Some(storage) = &mut self.storage {
+            let net_chips: Vec<i32> = self
+                .players
+                .iter()
+                .map(|x| x.total_money - x.hand_starting_money)
+                .collect();
+            if let Err(error) = storage.finish_hand(&end, &net_chips) {
+                warn!(
+                    "Couldn't write the hand to the database due to the following error: {error}"
+                );
             }
-            for mut list_of_players in sorted_players {
-                // Sort by the smallest bet to the largest bet
-                list_of_players.sort_by(Table::compare_players_by_bet_amount);
-                // filter out any folded players just in case
-                let list_of_players: Vec<Player> = list_of_players
-                    .into_iter()
-                    .filter(|x| x.player_state.is_active())
-                    .collect();
-                let mut player_size = list_of_players.len() as i32;
-                let bet_amounts = Table::get_bet_increases_amount(&list_of_players);
-                for (i, bet_amount) in bet_amounts.iter().enumerate() {
-                    if self.get_pot_size() == 0 {
-                        break;
-                    }
-                    // Take the bet from everyone
-                    let mut total = 0;
-                    for bet in &mut self.player_bets {
-                        let side_pot_amount = min(*bet_amount, *bet);
-                        *bet -= side_pot_amount;
-                        total += side_pot_amount;
-                    }
-                    let total = total;
-                    let each_player_payout = total / player_size;
-                    let remainder = total % player_size;
-                    for (j, player) in list_of_players.iter().enumerate().skip(i) {
-                        let winning_id = player.get_id();
-                        let winner = self
-                            .players
-                            .iter_mut()
-                            .find(|x| x.get_id() == winning_id)
-                            .unwrap();
-                        winner.total_money += each_player_payout;
-                        if (j as i32) < remainder {
-                            winner.total_money += 1;
-                        }
+        }
+        let history = match &mut self.hand_history {
+            Some(history) => history,
+            None => return,
+        };
+        history.finish(end);
+        if let Some(path) = &self.config.hand_history_file {
+            // Hands are separated by blank lines like other hand history files
+            let written = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .and_then(|mut file| write!(file, "{history}\n\n\n"));
+            if let Err(error) = written {
+                warn!("Couldn't write the hand history due to the following error: {error}");
+            }
+        }
+    }
+
+    /// Opens the database in the config and adds the game to it, a database that can't be opened is only logged
+    fn open_storage(&mut self) {
+        let Some(path) = &self.config.database_file else {
+            return;
+        };
+        let starting_stacks: Vec<i32> = self.players.iter().map(|x| x.total_money).collect();
+        let variant = self.config.variant.get_hand_history_name();
+        match Storage::create(path, &variant, self.config.seed, &starting_stacks) {
+            Ok(storage) => self.storage = Some(storage),
+            Err(error) => warn!("Couldn't open the database due to the following error: {error}"),
+        }
+    }
+
+    /// Adds an action to the actions of this hand, and writes it to the database if there is one
+    fn push_action(&mut self, action: TableAction) {
+        self.store_action(&action);
+        self.round_actions.push(action);
+    }
+
+    fn store_action(&mut self, action: &TableAction) {
+        let Some(storage) = &mut self.storage else {
+            return;
+        };
+        // A player's action is stored with what they have bet in total after it
+        let (total_bet, is_all_in) = match action {
+            TableAction::TakePlayerAction(seat, hand_action) => {
+                let seat = *seat as usize;
+                let is_all_in =
+                    *hand_action != HandAction::Fold && self.players[seat].total_money == 0;
+                (Some(self.player_bets[seat]), is_all_in)
+            }
+            _ => (None, false),
+        };
+        let stored = StoredAction {
+            stage: self.table_state,
+            action,
+            total_bet,
+            is_all_in,
+        };
+        if let Err(error) = storage.add_action(&stored) {
+            warn!("Couldn't write the action to the database due to the following error: {error}");
+        }
+    }
+
+    /// Starts the hand just dealt in the database along with its deal
+    fn start_stored_hand(&mut self) {
+        let Some(storage) = &mut self.storage else {
+            return;
+        };
+        let start = StoredHandStart {
+            hand_number: self.hand_number,
+            button: self.dealer_button_index,
+            small_blind: self.small_blind,
+            big_blind: self.big_blind,
+            ante: self.ante,
+        };
+        if let Err(error) = storage.start_hand(&start) {
+            warn!("Couldn't write the hand to the database due to the following error: {error}");
+        }
+        // Anyone knocked out before the deal was stored with the hand that knocked them out
+        if let Some(deal) = self.round_actions.first().cloned() {
+            self.store_action(&deal);
+        }
+    }
+
+    /// Writes the results and how each player finished to the database
+    fn finish_stored_game(&mut self) {
+        if self.storage.is_none() {
+            return;
+        }
+        let results = self.get_results();
+        let entrants: Vec<StoredEntrant> = self
+            .get_ranked_players()
+            .into_iter()
+            .map(|(place, player)| StoredEntrant {
+                seat: player.get_id() as usize,
+                place,
+                final_chips: player.total_money,
+                rebuys: player.rebuy_count,
+                knockouts: player.knockout_count,
+            })
+            .collect();
+        if let Some(storage) = &mut self.storage {
+            if let Err(error) = storage.finish_game(&results, &entrants) {
+                warn!("Couldn't write the results to the database due to the following error: {error}");
+            }
+        }
+    }
+
+    /// Adds the hand that just finished to the stats of everyone dealt into it
+    fn record_player_stats(&mut self) {
+        let dealt_seats: Vec<usize> = (0..self.players.len())
+            .filter(|seat| self.players[*seat].is_alive())
+            .collect();
+        // Nobody shows down when everyone else folded
+        let showdown: Vec<(usize, bool)> = match self.get_active_player_count() {
+            1 => vec![],
+            _ => (0..self.players.len())
+                .filter(|seat| self.players[*seat].player_state.is_active())
+                .map(|seat| {
+                    let won = self
+                        .pot_winners_by_seat
+                        .iter()
+                        .any(|winners| winners.contains(&(seat as i8)));
+                    (seat, won)
+                })
+                .collect(),
+        };
+        let hand_stats = get_hand_stats(
+            &dealt_seats,
+            &self.round_actions,
+            self.forced_bet_index,
+            &showdown,
+            self.players.len(),
+        );
+        for (stats, hand_stats) in self.player_stats.iter_mut().zip(hand_stats) {
+            *stats += hand_stats;
+        }
+    }
+
+    /// Gets the stats of every player by seat, along with their id
+    pub fn get_player_stats_json(&self) -> JsonValue {
+        self.players
+            .iter()
+            .zip(&self.player_stats)
+            .map(|(player, stats)| {
+                let mut json = stats.as_json();
+                json["id"] = player.get_id().into();
+                json
+            })
+            .collect::<Vec<_>>()
+            .into()
+    }
+
+    /// Gets how much each seat is expected to win when everyone went all in before the board was finished,
+    /// None if the table doesn't adjust for all ins or the hand wasn't one. Stud hands all in before seventh
+    /// street are still waiting on the players' own cards, so they aren't worked out either
+    fn get_all_in_expected_winnings(&mut self) -> Option<Vec<f64>> {
+        let variant = self.config.variant;
+        let all_in_stage = self.all_in_stage.filter(|_| self.config.all_in_adjusted)?;
+        let shown = variant.get_board_cards_shown(all_in_stage);
+        // Only hands waiting on board cards are worked out, not ones still dealing cards to the players
+        if shown == self.board.len()
+            || variant.get_hole_cards_dealt(all_in_stage) < variant.get_hole_card_count()
+        {
+            return None;
+        }
+        let hands: Vec<Option<Vec<Card>>> = self
+            .players
+            .iter()
+            .map(|player| match player.player_state {
+                PlayerState::Active(active) => Some(active.hand.to_vec()),
+                PlayerState::Folded => None,
+            })
+            .collect();
+        let pots = self.get_pots();
+        let known = KnownCards {
+            hands: &hands,
+            board: &self.board[..shown],
+            dead_cards: &self.dealt_hole_cards,
+        };
+        Some(get_expected_winnings(
+            &self.evaluator,
+            variant,
+            &known,
+            &pots,
+            self.config.all_in_samples,
+            &mut self.rng,
+        ))
+    }
+
+    /// Splits the bets into the main pot and the side pots, each pot holds the chips bet up to the next
+    /// all in amount of a player still in the hand
+    fn get_pots(&self) -> Vec<Pot> {
+        let mut levels: Vec<i32> = self
+            .players
+            .iter()
+            .filter_map(|player| player.player_state.get_bet())
+            .collect();
+        levels.sort_unstable();
+        levels.dedup();
+        let mut pots = vec![];
+        let mut previous_level = 0;
+        for level in levels {
+            let amount = self
+                .player_bets
+                .iter()
+                .map(|bet| (min(*bet, level) - previous_level).max(0))
+                .sum();
+            let eligible_seats = (0..self.players.len())
+                .filter(|seat| self.players[*seat].player_state.get_bet() >= Some(level))
+                .collect();
+            pots.push(Pot {
+                amount,
+                eligible_seats,
+            });
+            previous_level = level;
+        }
+        pots
+    }
+
+    /// Adds the difference between what each player was expected to win and what they won to their adjustment.
+    /// Returns each player's equity in the hand
+    fn record_all_in_adjustments(
+        &mut self,
+        expected_winnings: &[f64],
+        money_before: &[i32],
+    ) -> String {
+        let pot_size: f64 = expected_winnings.iter().sum();
+        let mut result_string = "\nAll in equity:\n".to_string();
+        for (seat, expected) in expected_winnings.iter().enumerate() {
+            if !self.players[seat].player_state.is_active() {
+                continue;
+            }
+            let won = self.players[seat].total_money - money_before[seat];
+            self.all_in_adjustments[seat] += expected - won as f64;
+            result_string += format!(
+                "Player {seat} had {:.2}% equity, expected to win {expected:.2} and won {won}\n",
+                expected / pot_size * 100.0
+            )
+            .as_str();
+        }
+        result_string
+    }
+
+    /// Gets a player's chips won or lost adjusted for their luck in all in hands, what they would have won
+    /// on average instead of what they did
+    pub fn get_all_in_adjusted_net_chips(&self, player: &Player) -> f64 {
+        player.get_net_chips() as f64 + self.all_in_adjustments[player.get_id() as usize]
+    }
+
+    /// Shows down the hands of the players still in and pays out every pot. Returns the hands and what they won
+    fn showdown(&mut self) -> Result<String, TableError> {
+        let mut result_string = self.make_comparison_header();
+        // Give out winnings based on hand strength
+        let sorted_players = self.get_hand_result();
+        // All player hands need to be shown so collect that information
+        for (index, list_of_players) in sorted_players.iter().enumerate() {
+            let rank = index + 1;
+            for player in list_of_players {
+                if let PlayerState::Active(state) = player.player_state {
+                    result_string += format!(
+                        "Player {} ranked {} with hand {}\n",
+                        player.get_id(),
+                        rank,
+                        state.hand
+                    )
+                    .as_str();
+                }
+            }
+        }
+        if self.config.variant.is_split_pot() {
+            result_string += self.split_pots_high_low(&sorted_players).as_str();
+        } else {
+            // Every player still in from the smallest bet to the largest, each increase in bet is a side pot
+            let mut players_by_bet: Vec<Player> = sorted_players
+                .iter()
+                .flatten()
+                .filter(|x| x.player_state.is_active())
+                .copied()
+                .collect();
+            players_by_bet.sort_by(Table::compare_players_by_bet_amount);
+            let bet_amounts = Table::get_bet_increases_amount(&players_by_bet)?;
+            for (i, bet_amount) in bet_amounts.iter().enumerate() {
+                // Take the bet from everyone
+                let mut total = 0;
+                let mut contributing_seats = vec![];
+                for (seat, bet) in self.player_bets.iter_mut().enumerate() {
+                    let side_pot_amount = min(*bet_amount, *bet);
+                    if side_pot_amount > 0 {
+                        contributing_seats.push(seat);
                     }
-                    player_size -= 1;
+                    *bet -= side_pot_amount;
+                    total += side_pot_amount;
+                }
+                if total == 0 {
+                    continue;
                 }
+                // The best ranked of the players that bet at least this much win it, the smaller bets
+                // get the odd chips first
+                let eligible = &players_by_bet[i..];
+                let winners: Vec<i8> = sorted_players
+                    .iter()
+                    .map(|group| {
+                        eligible
+                            .iter()
+                            .filter(|x| group.iter().any(|y| y.get_id() == x.get_id()))
+                            .map(|x| x.get_id())
+                            .collect::<Vec<i8>>()
+                    })
+                    .find(|group| !group.is_empty())
+                    .unwrap();
+                self.record_pot_winners(&contributing_seats, &winners);
+                self.pay_winners(&winners, total);
             }
         }
-        info!("{result_string}");
-        self.round_actions
-            .push(TableAction::EvaluateHand(result_string));
-        self.deal();
+        // The pots only go up to the largest bet of a player still in, so anything a folded player bet past
+        // that was never matched and goes back to them
+        for seat in 0..self.players.len() {
+            let unmatched = std::mem::take(&mut self.player_bets[seat]);
+            if unmatched > 0 {
+                self.players[seat].total_money += unmatched;
+                result_string +=
+                    format!("Player {seat} got back {unmatched} unmatched chips\n").as_str();
+            }
+        }
+        Ok(result_string)
     }
 
-    fn make_comparison_header(&mut self) -> String {
-        let flop_string = self.flop.map_or("None".to_string(), |cards| {
-            format!("{} {} {}", cards[0], cards[1], cards[2])
-        });
+    /// Deals the rest of the board again for each extra run when everyone went all in before it was finished,
+    /// the first run is the board already dealt. Only as many runs as the cards left in the deck allow are dealt
+    fn get_run_out_boards(&mut self) -> Vec<Vec<Card>> {
+        let mut boards = vec![self.board.clone()];
+        let variant = self.config.variant;
+        let all_in_stage = match self.all_in_stage {
+            Some(stage) if self.config.run_it_times > 1 => stage,
+            _ => return boards,
+        };
+        // Only the board is run again, games still dealing cards to the players are run once
+        if variant.get_hole_cards_dealt(all_in_stage) < variant.get_hole_card_count() {
+            return boards;
+        }
+        let shown = variant.get_board_cards_shown(all_in_stage);
+        let cards_needed = self.board.len() - shown;
+        if cards_needed == 0 {
+            return boards;
+        }
+        // Each run is dealt from the cards no one has seen and no other run used, folded hands are still used
+        let mut used_cards: HashSet<Card> = self.board.iter().copied().collect();
+        used_cards.extend(&self.dealt_hole_cards);
+        let deck: Vec<Card> = variant
+            .generate_shuffled_deck(&mut self.rng)
+            .into_iter()
+            .filter(|card| !used_cards.contains(card))
+            .collect();
+        for run_out in deck
+            .chunks_exact(cards_needed)
+            .take(self.config.run_it_times - 1)
+        {
+            let mut board = self.board[..shown].to_vec();
+            board.extend(run_out);
+            boards.push(board);
+        }
+        boards
+    }
+
+    /// Shows down the hands once on every board, each run wins an even share of every pot. The odd chips go to
+    /// the players with the most left over from their shares
+    fn showdown_each_board(&mut self, boards: Vec<Vec<Card>>) -> Result<String, TableError> {
+        let run_count = boards.len() as i32;
+        let first_board = boards[0].clone();
+        let bets = self.player_bets.clone();
+        let money_before: Vec<i32> = self.players.iter().map(|x| x.total_money).collect();
+        let mut winnings = vec![0; self.players.len()];
+        let mut result_string = format!("\nThe board was run {run_count} times.");
+        for (run_index, board) in boards.into_iter().enumerate() {
+            self.board = board;
+            self.player_bets = bets.clone();
+            result_string += format!("\nRun {}:", run_index + 1).as_str();
+            result_string += self.showdown()?.as_str();
+            for (seat, player) in self.players.iter_mut().enumerate() {
+                winnings[seat] += player.total_money - money_before[seat];
+                player.total_money = money_before[seat];
+            }
+        }
+        let mut shares: Vec<i32> = winnings.iter().map(|x| x / run_count).collect();
+        let odd_chips = winnings.iter().sum::<i32>() / run_count - shares.iter().sum::<i32>();
+        let mut seats: Vec<usize> = (0..self.players.len()).collect();
+        seats.sort_by_key(|seat| Reverse(winnings[*seat] % run_count));
+        for seat in seats.into_iter().take(odd_chips as usize) {
+            shares[seat] += 1;
+        }
+        result_string += "\nAcross the runs:\n";
+        for (seat, share) in shares.into_iter().enumerate() {
+            self.players[seat].total_money += share;
+            if share > 0 {
+                result_string += format!("Player {seat} won {share}\n").as_str();
+            }
+        }
+        self.board = first_board;
+        Ok(result_string)
+    }
+
+    /// Splits every side pot between the best high hand and the best low hand of the players in it,
+    /// the high hand scoops a pot when nobody in it has a qualifying low. Returns what each pot paid.
+    fn split_pots_high_low(&mut self, sorted_players: &[Vec<Player>]) -> String {
+        let board = self.get_board_cards();
+        let lows: Vec<(i8, LowHand)> = self
+            .players
+            .iter()
+            .filter_map(|player| match player.player_state {
+                PlayerState::Folded => None,
+                PlayerState::Active(active) => self
+                    .config
+                    .variant
+                    .evaluate_low(&active.hand, &board)
+                    .map(|low| (player.get_id(), low)),
+            })
+            .collect();
+        let mut result_string = String::new();
+        for (id, low) in &lows {
+            result_string += format!("Player {id} has low hand {low}\n").as_str();
+        }
+        // Each side pot holds the chips bet up to the next all in amount of a player still in the hand
+        let mut levels: Vec<i32> = self
+            .players
+            .iter()
+            .filter_map(|player| player.player_state.get_bet())
+            .collect();
+        levels.sort_unstable();
+        levels.dedup();
+        let mut previous_level = 0;
+        for (pot_index, level) in levels.into_iter().enumerate() {
+            let layer = level - previous_level;
+            previous_level = level;
+            let mut total = 0;
+            let mut contributing_seats = vec![];
+            for (seat, bet) in self.player_bets.iter_mut().enumerate() {
+                let side_pot_amount = min(layer, *bet);
+                if side_pot_amount > 0 {
+                    contributing_seats.push(seat);
+                }
+                *bet -= side_pot_amount;
+                total += side_pot_amount;
+            }
+            if total == 0 {
+                continue;
+            }
+            let eligible: Vec<i8> = self
+                .players
+                .iter()
+                .filter(|player| player.player_state.get_bet() >= Some(level))
+                .map(|player| player.get_id())
+                .collect();
+            let high_winners: Vec<i8> = sorted_players
+                .iter()
+                .map(|group| {
+                    group
+                        .iter()
+                        .map(|player| player.get_id())
+                        .filter(|id| eligible.contains(id))
+                        .collect::<Vec<i8>>()
+                })
+                .find(|group| !group.is_empty())
+                .unwrap();
+            let best_low = lows
+                .iter()
+                .filter(|(id, _)| eligible.contains(id))
+                .map(|(_, low)| *low)
+                .min();
+            let low_winners: Vec<i8> = lows
+                .iter()
+                .filter(|(id, low)| eligible.contains(id) && Some(*low) == best_low)
+                .map(|(id, _)| *id)
+                .collect();
+            let pot_number = pot_index + 1;
+            if low_winners.is_empty() {
+                self.pay_winners(&high_winners, total);
+                result_string += format!(
+                    "Pot {pot_number} of {total} was scooped by {high_winners:?}, no low qualified\n"
+                )
+                .as_str();
+            } else {
+                // The odd chip goes to the high hand
+                let low_share = total / 2;
+                self.pay_winners(&high_winners, total - low_share);
+                self.pay_winners(&low_winners, low_share);
+                result_string += format!(
+                    "Pot {pot_number} of {total} was split, high won by {high_winners:?}, low won by {low_winners:?}\n"
+                )
+                .as_str();
+            }
+            let mut winners = high_winners;
+            for id in low_winners {
+                if !winners.contains(&id) {
+                    winners.push(id);
+                }
+            }
+            self.record_pot_winners(&contributing_seats, &winners);
+        }
+        result_string
+    }
+
+    /// Splits the chips evenly between the winners, the first winners get any chips left over
+    fn pay_winners(&mut self, winners: &[i8], amount: i32) {
+        let each_player_payout = amount / winners.len() as i32;
+        let remainder = amount % winners.len() as i32;
+        for (index, winner) in winners.iter().enumerate() {
+            let player = &mut self.players[*winner as usize];
+            player.total_money += each_player_payout;
+            if (index as i32) < remainder {
+                player.total_money += 1;
+            }
+        }
+    }
 
+    /// Gets every board card, which must all be dealt
+    fn get_board_cards(&self) -> Vec<Card> {
+        self.board.clone()
+    }
+
+    fn make_comparison_header(&mut self) -> String {
+        let flop_string = if self.board.is_empty() {
+            "None".to_string()
+        } else {
+            let flop: Vec<String> = self
+                .get_flop()
+                .iter()
+                .map(|card| card.to_string())
+                .collect();
+            flop.join(" ")
+        };
         let turn_string = self
-            .turn
+            .board
+            .get(3)
             .map_or("None".to_string(), |card| card.to_string());
         let river_string = self
-            .river
+            .board
+            .get(4)
             .map_or("None".to_string(), |card| card.to_string());
         let header = format!("\nPlayers hands had to be compared.\nFlop: {flop_string}\nTurn: {turn_string}\nRiver: {river_string}\nThe hands are ranked as follows: \n");
         header
@@ -728,24 +2240,13 @@ impl Table {
             return Ordering::Less;
         }
 
-        let mut a_set: Vec<Card> = shared_cards.into();
-        if let PlayerState::Active(a) = &player1.player_state {
-            a_set.extend(a.hand.iter());
-        }
-
-        let mut b_set: Vec<Card> = shared_cards.into();
-        if let PlayerState::Active(b) = &player2.player_state {
-            b_set.extend(b.hand.iter());
-        }
-
-        let a = self
-            .evaluator
-            .evaluate(a_set)
-            .expect("Couldn't evaluate hand 1");
-        let b = self
-            .evaluator
-            .evaluate(b_set)
-            .expect("Couldn't evaluate hand 2");
+        let (a, b) = match (&player1.player_state, &player2.player_state) {
+            (PlayerState::Active(a), PlayerState::Active(b)) => (a.hand, b.hand),
+            _ => unreachable!("Both players were checked to be active"),
+        };
+        let variant = self.config.variant;
+        let a = variant.evaluate(&self.evaluator, &a, shared_cards);
+        let b = variant.evaluate(&self.evaluator, &b, shared_cards);
         b.cmp(&a)
     }
 
@@ -757,13 +2258,7 @@ impl Table {
     pub fn get_hand_result(&self) -> Vec<Vec<Player>> {
         let mut players_copy = self.players.clone();
 
-        let total_hand = vec![
-            *self.flop.unwrap().get(0).unwrap(),
-            *self.flop.unwrap().get(1).unwrap(),
-            *self.flop.unwrap().get(2).unwrap(),
-            self.turn.unwrap(),
-            self.river.unwrap(),
-        ];
+        let total_hand = self.get_board_cards();
         self.sort_by_hands(&total_hand, &mut players_copy);
         let mut rankings = Vec::new();
         rankings.push(Vec::new());
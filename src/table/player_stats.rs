@@ -0,0 +1,334 @@
+use std::fmt;
+use std::fmt::Formatter;
+use std::ops::AddAssign;
+
+use json::{object, JsonValue};
+use serde::{Deserialize, Serialize};
+
+use crate::actions::HandAction;
+use crate::table::table_action::TableAction;
+
+/// How a player has played their hands, counted from the actions each hand. The flop stands for the second
+/// betting round of any game variant.
+#[derive(Copy, Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct PlayerStats {
+    /// Hands the player was dealt into
+    pub hands: usize,
+    /// Hands the player called or raised before the flop without being forced to
+    pub vpip_hands: usize,
+    /// Hands the player raised before the flop
+    pub pfr_hands: usize,
+    /// Hands the player could re-raise the first raise before the flop
+    pub three_bet_chances: usize,
+    /// Hands the player did re-raise the first raise before the flop
+    pub three_bets: usize,
+    /// Every bet and raise the player made
+    pub bets_and_raises: usize,
+    /// Every call of a bet the player made
+    pub calls: usize,
+    /// Times the player faced a bet after the flop
+    pub fold_to_bet_chances: usize,
+    /// Times the player folded to a bet after the flop
+    pub folds_to_bet: usize,
+    /// Hands the player was still in when the flop was dealt
+    pub saw_flop_hands: usize,
+    /// Hands the player was still in at the showdown
+    pub showdowns: usize,
+    /// Showdowns where the player won chips from another player
+    pub showdown_wins: usize,
+}
+
+impl PlayerStats {
+    /// The percent of hands the player voluntarily put chips in before the flop
+    pub fn get_vpip(&self) -> Option<f64> {
+        get_percent(self.vpip_hands, self.hands)
+    }
+
+    /// The percent of hands the player raised before the flop
+    pub fn get_pfr(&self) -> Option<f64> {
+        get_percent(self.pfr_hands, self.hands)
+    }
+
+    /// The percent of chances to re-raise the first raise before the flop that the player took
+    pub fn get_three_bet(&self) -> Option<f64> {
+        get_percent(self.three_bets, self.three_bet_chances)
+    }
+
+    /// Bets and raises for every call, None if the player never called
+    pub fn get_aggression_factor(&self) -> Option<f64> {
+        if self.calls == 0 {
+            return None;
+        }
+        Some(self.bets_and_raises as f64 / self.calls as f64)
+    }
+
+    /// The percent of bets after the flop the player folded to
+    pub fn get_fold_to_bet(&self) -> Option<f64> {
+        get_percent(self.folds_to_bet, self.fold_to_bet_chances)
+    }
+
+    /// The percent of hands that saw the flop the player took to a showdown
+    pub fn get_went_to_showdown(&self) -> Option<f64> {
+        get_percent(self.showdowns, self.saw_flop_hands)
+    }
+
+    /// The percent of showdowns the player won chips at
+    pub fn get_won_at_showdown(&self) -> Option<f64> {
+        get_percent(self.showdown_wins, self.showdowns)
+    }
+
+    /// Gets the counts and the stats worked out from them, stats without any chances yet are null
+    pub fn as_json(&self) -> JsonValue {
+        object! {
+            hands: self.hands,
+            vpip: self.get_vpip(),
+            pfr: self.get_pfr(),
+            three_bet: self.get_three_bet(),
+            aggression_factor: self.get_aggression_factor(),
+            fold_to_bet: self.get_fold_to_bet(),
+            went_to_showdown: self.get_went_to_showdown(),
+            won_at_showdown: self.get_won_at_showdown(),
+            counts: object! {
+                vpip_hands: self.vpip_hands,
+                pfr_hands: self.pfr_hands,
+                three_bet_chances: self.three_bet_chances,
+                three_bets: self.three_bets,
+                bets_and_raises: self.bets_and_raises,
+                calls: self.calls,
+                fold_to_bet_chances: self.fold_to_bet_chances,
+                folds_to_bet: self.folds_to_bet,
+                saw_flop_hands: self.saw_flop_hands,
+                showdowns: self.showdowns,
+                showdown_wins: self.showdown_wins,
+            },
+        }
+    }
+}
+
+impl AddAssign for PlayerStats {
+    fn add_assign(&mut self, other: Self) {
+        self.hands += other.hands;
+        self.vpip_hands += other.vpip_hands;
+        self.pfr_hands += other.pfr_hands;
+        self.three_bet_chances += other.three_bet_chances;
+        self.three_bets += other.three_bets;
+        self.bets_and_raises += other.bets_and_raises;
+        self.calls += other.calls;
+        self.fold_to_bet_chances += other.fold_to_bet_chances;
+        self.folds_to_bet += other.folds_to_bet;
+        self.saw_flop_hands += other.saw_flop_hands;
+        self.showdowns += other.showdowns;
+        self.showdown_wins += other.showdown_wins;
+    }
+}
+
+impl fmt::Display for PlayerStats {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        let aggression_factor = match self.get_aggression_factor() {
+            Some(aggression_factor) => format!("{aggression_factor:>6.2}"),
+            None => format!("{:>6}", "-"),
+        };
+        write!(
+            f,
+            "Hands:{:>6}, VPIP:{}, PFR:{}, 3-Bet:{}, AF:{aggression_factor}, Fold To Bet:{}, WTSD:{}, W$SD:{}",
+            self.hands,
+            format_percent(self.get_vpip()),
+            format_percent(self.get_pfr()),
+            format_percent(self.get_three_bet()),
+            format_percent(self.get_fold_to_bet()),
+            format_percent(self.get_went_to_showdown()),
+            format_percent(self.get_won_at_showdown()),
+        )
+    }
+}
+
+fn get_percent(count: usize, chances: usize) -> Option<f64> {
+    if chances == 0 {
+        return None;
+    }
+    Some(count as f64 * 100.0 / chances as f64)
+}
+
+fn format_percent(percent: Option<f64>) -> String {
+    match percent {
+        Some(percent) => format!("{percent:>6.1}%"),
+        None => format!("{:>7}", "-"),
+    }
+}
+
+/// Gets the stats of each seat for one finished hand from the actions taken in it.
+/// The forced bet seat posted the big blind or bring-in the other players have to call before the flop,
+/// and the showdown has each seat that was still in at the end with whether it won chips from another player.
+pub fn get_hand_stats(
+    dealt_seats: &[usize],
+    actions: &[TableAction],
+    forced_bet_seat: Option<usize>,
+    showdown: &[(usize, bool)],
+    seat_count: usize,
+) -> Vec<PlayerStats> {
+    let mut stats = vec![PlayerStats::default(); seat_count];
+    let mut folded = vec![false; seat_count];
+    for &seat in dealt_seats {
+        stats[seat].hands = 1;
+    }
+    let mut stage_index = 0;
+    // The forced bet counts as the first bet before the flop
+    let mut bets_this_stage = usize::from(forced_bet_seat.is_some());
+    for action in actions {
+        let (seat, hand_action) = match action {
+            TableAction::AdvanceToStage(_) => {
+                if stage_index == 0 {
+                    for &seat in dealt_seats.iter().filter(|seat| !folded[**seat]) {
+                        stats[seat].saw_flop_hands = 1;
+                    }
+                }
+                stage_index += 1;
+                bets_this_stage = 0;
+                continue;
+            }
+            TableAction::TakePlayerAction(seat, hand_action) => (*seat as usize, *hand_action),
+            _ => continue,
+        };
+        let player_stats = &mut stats[seat];
+        // An all in for less than the amount to call is only a call
+        let is_raise = matches!(hand_action, HandAction::Raise(amount) if amount > 0);
+        // The forced bet seat only faces a bet once someone raises it
+        let is_facing_bet = if stage_index == 0 && forced_bet_seat == Some(seat) {
+            bets_this_stage > 1
+        } else {
+            bets_this_stage > 0
+        };
+        let is_call = hand_action == HandAction::Call && is_facing_bet;
+        if stage_index == 0 {
+            if is_raise || is_call {
+                player_stats.vpip_hands = 1;
+            }
+            if is_raise {
+                player_stats.pfr_hands = 1;
+            }
+            if bets_this_stage == 2 {
+                player_stats.three_bet_chances = 1;
+                if is_raise {
+                    player_stats.three_bets = 1;
+                }
+            }
+        } else if is_facing_bet {
+            player_stats.fold_to_bet_chances += 1;
+            if hand_action == HandAction::Fold {
+                player_stats.folds_to_bet += 1;
+            }
+        }
+        if is_raise {
+            player_stats.bets_and_raises += 1;
+            bets_this_stage += 1;
+        } else if is_call {
+            player_stats.calls += 1;
+        }
+        if hand_action == HandAction::Fold {
+            folded[seat] = true;
+        }
+    }
+    for &(seat, won) in showdown {
+        stats[seat].showdowns = 1;
+        stats[seat].showdown_wins = usize::from(won);
+    }
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::actions::HandAction;
+    use crate::actions::HandAction::{Call, Check, Fold, Raise};
+    use crate::bet_stage::BetStage;
+    use crate::table::deal_information::DealInformation;
+    use crate::table::player_stats::{get_hand_stats, PlayerStats};
+    use crate::table::table_action::TableAction;
+
+    fn make_actions(stages: &[&[(i8, HandAction)]]) -> Vec<TableAction> {
+        let mut actions = vec![TableAction::DealCards(DealInformation {
+            round_number: 1,
+            dealer_button_index: 0,
+        })];
+        for (i, stage) in stages.iter().enumerate() {
+            if i > 0 {
+                actions.push(TableAction::AdvanceToStage(BetStage::Flop));
+            }
+            for (seat, hand_action) in stage.iter() {
+                actions.push(TableAction::TakePlayerAction(*seat, *hand_action));
+            }
+        }
+        actions
+    }
+
+    #[test]
+    fn test_blinds_hand_stats() {
+        // Seat 2 is the big blind, seat 0 limps, seat 1 raises, seat 2 re-raises and seat 0 folds
+        let actions = make_actions(&[
+            &[
+                (0, Call),
+                (1, Raise(4)),
+                (2, Raise(8)),
+                (0, Fold),
+                (1, Call),
+            ],
+            &[(2, Raise(10)), (1, Fold)],
+        ]);
+        let stats = get_hand_stats(&[0, 1, 2], &actions, Some(2), &[], 3);
+        assert_eq!(stats[0].vpip_hands, 1);
+        assert_eq!(stats[0].pfr_hands, 0);
+        assert_eq!(stats[0].three_bet_chances, 0);
+        assert_eq!(stats[0].saw_flop_hands, 0);
+        assert_eq!(stats[1].pfr_hands, 1);
+        assert_eq!(stats[1].calls, 1);
+        assert_eq!(stats[1].fold_to_bet_chances, 1);
+        assert_eq!(stats[1].folds_to_bet, 1);
+        assert_eq!(stats[2].three_bet_chances, 1);
+        assert_eq!(stats[2].three_bets, 1);
+        assert_eq!(stats[2].bets_and_raises, 2);
+        assert_eq!(stats[2].saw_flop_hands, 1);
+        assert_eq!(stats[2].fold_to_bet_chances, 0);
+    }
+
+    #[test]
+    fn test_checking_big_blind_and_antes_are_not_voluntary() {
+        // With a big blind the big blind calling nothing is a check
+        let actions = make_actions(&[&[(0, Call), (1, Call)]]);
+        let stats = get_hand_stats(&[0, 1], &actions, Some(1), &[], 2);
+        assert_eq!(stats[0].vpip_hands, 1);
+        assert_eq!(stats[1].vpip_hands, 0);
+        // With only antes nobody faces a bet until someone raises
+        let actions = make_actions(&[&[(0, Call), (1, Check)], &[(0, Raise(5)), (1, Call)]]);
+        let stats = get_hand_stats(&[0, 1], &actions, None, &[(0, true), (1, false)], 2);
+        assert_eq!(stats[0].vpip_hands, 0);
+        assert_eq!(stats[1].vpip_hands, 0);
+        assert_eq!(stats[1].calls, 1);
+        assert_eq!(stats[1].fold_to_bet_chances, 1);
+        assert_eq!(stats[0].showdown_wins, 1);
+        assert_eq!(stats[1].showdowns, 1);
+        assert_eq!(stats[1].showdown_wins, 0);
+    }
+
+    #[test]
+    fn test_stat_percentages() {
+        let mut stats = PlayerStats::default();
+        assert_eq!(stats.get_vpip(), None);
+        assert_eq!(stats.get_aggression_factor(), None);
+        assert!(stats.as_json()["vpip"].is_null());
+        stats += PlayerStats {
+            hands: 4,
+            vpip_hands: 1,
+            bets_and_raises: 3,
+            calls: 2,
+            showdowns: 2,
+            showdown_wins: 1,
+            ..PlayerStats::default()
+        };
+        assert_eq!(stats.get_vpip(), Some(25.0));
+        assert_eq!(stats.get_aggression_factor(), Some(1.5));
+        assert_eq!(stats.get_won_at_showdown(), Some(50.0));
+        assert_eq!(stats.as_json()["counts"]["calls"], 2);
+        let string = stats.to_string();
+        assert!(string.starts_with("Hands:     4, VPIP:  25.0%, PFR:   0.0%, 3-Bet:      -,"));
+        assert!(string.contains("AF:  1.50"));
+    }
+}
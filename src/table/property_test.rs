@@ -0,0 +1,143 @@
+//! Drives tables with random configs and random actions, every action is checked against the invariants
+//! of the table as it is taken.
+
+use proptest::collection::vec;
+use proptest::prelude::*;
+
+use crate::actions::HandAction;
+use crate::table::{
+    ButtonRule, CashGameConfig, GameVariant, Level, LevelSchedule, StopConditions, Table,
+    TableConfig,
+};
+
+fn hand_action() -> impl Strategy<Value = HandAction> {
+    prop_oneof![
+        Just(HandAction::Fold),
+        Just(HandAction::Check),
+        Just(HandAction::Call),
+        // Bots can send any amount, including nothing or less than nothing
+        (-20..300).prop_map(HandAction::Raise),
+    ]
+}
+
+fn variant() -> impl Strategy<Value = GameVariant> {
+    prop_oneof![
+        Just(GameVariant::TexasHoldem),
+        Just(GameVariant::PotLimitOmaha),
+        Just(GameVariant::OmahaHiLo),
+        Just(GameVariant::ShortDeckHoldem),
+        Just(GameVariant::KuhnPoker),
+        Just(GameVariant::LeducHoldem),
+        Just(GameVariant::SevenCardStud),
+    ]
+}
+
+fn button_rule() -> impl Strategy<Value = ButtonRule> {
+    prop_oneof![
+        Just(ButtonRule::NextAlive),
+        Just(ButtonRule::DeadButton),
+        Just(ButtonRule::MovingButton),
+    ]
+}
+
+/// A tournament with or without blinds, or a short cash game. All in adjustments only change the results,
+/// not the chips, and working out the equity is too slow to do for every random game
+fn table_config() -> impl Strategy<Value = TableConfig> {
+    (
+        variant(),
+        button_rule(),
+        any::<u64>(),
+        1..=3usize,
+        prop_oneof![Just(0), 1..20],
+        any::<bool>(),
+    )
+        .prop_map(
+            |(variant, button_rule, seed, run_it_times, big_blind, is_cash_game)| {
+                let level_schedule = (big_blind > 0).then(|| LevelSchedule {
+                    levels: vec![Level {
+                        small_blind: big_blind / 2,
+                        big_blind,
+                        ante: 1,
+                        hands: None,
+                        seconds: None,
+                    }],
+                });
+                let cash_game = is_cash_game.then_some(CashGameConfig {
+                    stack_size: 100,
+                    ante: 2,
+                    hand_count: 20,
+                });
+                TableConfig {
+                    variant,
+                    button_rule,
+                    seed: Some(seed),
+                    run_it_times,
+                    level_schedule,
+                    cash_game,
+                    stop_conditions: StopConditions {
+                        max_hands: Some(100),
+                        ..StopConditions::default()
+                    },
+                    ..TableConfig::default()
+                }
+            },
+        )
+}
+
+/// A config and a number of players the variant can deal into
+fn table_setup() -> impl Strategy<Value = (TableConfig, usize)> {
+    table_config().prop_flat_map(|config| {
+        let max_players = config.variant.get_max_players().min(10);
+        (Just(config), 2..=max_players)
+    })
+}
+
+/// Every chip on the table, in the stacks or the pot
+fn get_table_chips(table: &Table) -> i32 {
+    table.players.iter().map(|x| x.total_money).sum::<i32>() + table.get_pot_size()
+}
+
+/// Every chip the players brought to the table
+fn get_bought_in_chips(table: &Table) -> i32 {
+    table.players.iter().map(|x| x.total_buy_in).sum()
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(64))]
+
+    #[test]
+    fn test_random_actions_keep_the_table_valid(
+        (config, player_count) in table_setup(),
+        actions in vec(hand_action(), 1..400),
+    ) {
+        let mut table = Table::with_config(player_count, config).unwrap();
+        for action in actions {
+            if table.is_game_over() {
+                break;
+            }
+            // The invariants are checked inside every action, so a broken table panics here
+            prop_assert_eq!(table.take_action(action), Ok(()));
+            prop_assert_eq!(get_table_chips(&table), get_bought_in_chips(&table));
+            prop_assert!(table.is_hand_in_progress() || table.is_game_over());
+        }
+    }
+
+    #[test]
+    fn test_random_games_finish_with_every_chip(
+        (config, player_count) in table_setup(),
+        actions in vec(hand_action(), 1..50),
+    ) {
+        let mut table = Table::with_config(player_count, config).unwrap();
+        // The actions repeat until the game is over
+        for action in actions.iter().cycle() {
+            if table.is_game_over() {
+                break;
+            }
+            table.take_action(*action).unwrap();
+        }
+        prop_assert!(!table.is_hand_in_progress());
+        prop_assert_eq!(table.get_pot_size(), 0);
+        prop_assert_eq!(get_table_chips(&table), get_bought_in_chips(&table));
+        prop_assert_eq!(table.get_ranked_players().len(), player_count);
+    }
+}
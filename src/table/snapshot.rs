@@ -0,0 +1,52 @@
+//! Helpers for saving the parts of a table serde can't save on its own, so a table can be written out between
+//! hands and read back identically.
+
+use std::sync::Arc;
+
+use poker::Evaluator;
+
+use crate::global_immutables::SHARED_EVALUATOR;
+
+/// The evaluator isn't saved, every table shares the same one
+pub fn get_shared_evaluator() -> Arc<Evaluator> {
+    SHARED_EVALUATOR.clone()
+}
+
+/// Saves cards as their strings, such as `As`
+pub mod cards {
+    use poker::Card;
+    use serde::de::Error;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(cards: &[Card], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(cards.iter().map(|card| card.rank_suit_string()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<Card>, D::Error> {
+        Vec::<String>::deserialize(deserializer)?
+            .iter()
+            .map(|card| {
+                card.parse()
+                    .map_err(|_| D::Error::custom(format!("{card} isn't a card")))
+            })
+            .collect()
+    }
+}
+
+/// Saves an instant as the time that had passed since it, so timers carry on from where they were when read back
+pub mod elapsed {
+    use std::time::{Duration, Instant};
+
+    use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+    pub fn serialize<S: Serializer>(instant: &Instant, serializer: S) -> Result<S::Ok, S::Error> {
+        instant.elapsed().serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Instant, D::Error> {
+        let elapsed = Duration::deserialize(deserializer)?;
+        Ok(Instant::now()
+            .checked_sub(elapsed)
+            .unwrap_or_else(Instant::now))
+    }
+}
@@ -0,0 +1,372 @@
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+
+use crate::actions::HandAction;
+use crate::bet_stage::BetStage;
+use crate::table::hand_history::HandEnd;
+use crate::table::table_action::TableAction;
+
+/// Every table keeps its rows in these tables, so games from many runs can share one database file
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS games (
+    id INTEGER PRIMARY KEY,
+    started_at INTEGER NOT NULL,
+    variant TEXT NOT NULL,
+    seed INTEGER,
+    player_count INTEGER NOT NULL,
+    results TEXT
+);
+CREATE TABLE IF NOT EXISTS entrants (
+    game_id INTEGER NOT NULL REFERENCES games(id),
+    seat INTEGER NOT NULL,
+    starting_stack INTEGER NOT NULL,
+    place INTEGER,
+    final_chips INTEGER,
+    rebuys INTEGER,
+    knockouts INTEGER,
+    PRIMARY KEY (game_id, seat)
+);
+CREATE TABLE IF NOT EXISTS hands (
+    id INTEGER PRIMARY KEY,
+    game_id INTEGER NOT NULL REFERENCES games(id),
+    hand_number INTEGER NOT NULL,
+    button INTEGER NOT NULL,
+    small_blind INTEGER NOT NULL,
+    big_blind INTEGER NOT NULL,
+    ante INTEGER NOT NULL,
+    pot INTEGER
+);
+CREATE TABLE IF NOT EXISTS actions (
+    hand_id INTEGER NOT NULL REFERENCES hands(id),
+    sequence INTEGER NOT NULL,
+    stage TEXT NOT NULL,
+    seat INTEGER,
+    action TEXT NOT NULL,
+    amount INTEGER,
+    total_bet INTEGER,
+    is_all_in INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    PRIMARY KEY (hand_id, sequence)
+);
+CREATE TABLE IF NOT EXISTS board_cards (
+    hand_id INTEGER NOT NULL REFERENCES hands(id),
+    board INTEGER NOT NULL,
+    position INTEGER NOT NULL,
+    card TEXT NOT NULL,
+    PRIMARY KEY (hand_id, board, position)
+);
+CREATE TABLE IF NOT EXISTS showdowns (
+    hand_id INTEGER NOT NULL REFERENCES hands(id),
+    seat INTEGER NOT NULL,
+    hole_cards TEXT NOT NULL,
+    collected INTEGER NOT NULL,
+    net_chips INTEGER NOT NULL,
+    won INTEGER NOT NULL,
+    PRIMARY KEY (hand_id, seat)
+);
+";
+
+/// The forced bets and button of a hand, used to start it in the database
+pub struct StoredHandStart {
+    pub hand_number: i32,
+    pub button: usize,
+    pub small_blind: i32,
+    pub big_blind: i32,
+    pub ante: i32,
+}
+
+/// How an action changed the table, the seat's chips in the pot and whether they are all in afterwards
+pub struct StoredAction<'a> {
+    pub stage: BetStage,
+    pub action: &'a TableAction,
+    pub total_bet: Option<i32>,
+    pub is_all_in: bool,
+}
+
+/// How one entrant finished the game
+pub struct StoredEntrant {
+    pub seat: usize,
+    pub place: usize,
+    pub final_chips: i32,
+    pub rebuys: i32,
+    pub knockouts: i32,
+}
+
+/// Writes a game with its entrants, hands, actions, board cards and showdowns to a SQLite database.
+/// Only the ids are saved with a table, the connection is opened again when a restored table writes.
+#[derive(Deserialize, Serialize)]
+pub struct Storage {
+    path: PathBuf,
+    game_id: i64,
+    /// The hand being played, None before the first deal
+    hand_id: Option<i64>,
+    /// How many actions of the current hand have been written
+    action_count: i64,
+    #[serde(skip)]
+    connection: Option<Connection>,
+}
+
+impl Storage {
+    /// Opens the database, making its tables if needed, and adds the game and its entrants
+    pub fn create(
+        path: &Path,
+        variant: &str,
+        seed: Option<u64>,
+        starting_stacks: &[i32],
+    ) -> rusqlite::Result<Self> {
+        let connection = Connection::open(path)?;
+        connection.execute_batch(SCHEMA)?;
+        let started_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |duration| duration.as_secs() as i64);
+        connection.execute(
+            "INSERT INTO games (started_at, variant, seed, player_count) VALUES (?1, ?2, ?3, ?4)",
+            // SQLite integers are signed, so the seed keeps its bits but may read back negative
+            params![
+                started_at,
+                variant,
+                seed.map(|seed| seed as i64),
+                starting_stacks.len()
+            ],
+        )?;
+        let game_id = connection.last_insert_rowid();
+        for (seat, stack) in starting_stacks.iter().enumerate() {
+            connection.execute(
+                "INSERT INTO entrants (game_id, seat, starting_stack) VALUES (?1, ?2, ?3)",
+                params![game_id, seat, stack],
+            )?;
+        }
+        Ok(Storage {
+            path: path.to_path_buf(),
+            game_id,
+            hand_id: None,
+            action_count: 0,
+            connection: Some(connection),
+        })
+    }
+
+    /// Opens the database again for a restored table, first removing whatever the game wrote after the table was
+    /// saved since it is about to be played and written again
+    fn get_connection(&mut self) -> rusqlite::Result<&Connection> {
+        if self.connection.is_none() {
+            let connection = Connection::open(&self.path)?;
+            self.remove_rows_after_save(&connection)?;
+            self.connection = Some(connection);
+        }
+        Ok(self.connection.as_ref().unwrap())
+    }
+
+    fn remove_rows_after_save(&self, connection: &Connection) -> rusqlite::Result<()> {
+        // Before the first hand no hand id is saved, so every hand of the game comes after it
+        let hand_id = self.hand_id.unwrap_or(0);
+        let later_hands = "SELECT id FROM hands WHERE game_id = ?1 AND id > ?2";
+        connection.execute(
+            &format!(
+                "DELETE FROM actions WHERE hand_id IN ({later_hands}) OR (hand_id = ?2 AND sequence >= ?3)"
+            ),
+            params![self.game_id, hand_id, self.action_count],
+        )?;
+        for table in ["board_cards", "showdowns"] {
+            connection.execute(
+                &format!("DELETE FROM {table} WHERE hand_id IN ({later_hands}) OR hand_id = ?2"),
+                params![self.game_id, hand_id],
+            )?;
+        }
+        connection.execute(
+            "DELETE FROM hands WHERE game_id = ?1 AND id > ?2",
+            params![self.game_id, hand_id],
+        )?;
+        connection.execute(
+            "UPDATE hands SET pot = NULL WHERE id = ?1",
+            params![hand_id],
+        )?;
+        Ok(())
+    }
+
+    pub fn start_hand(&mut self, start: &StoredHandStart) -> rusqlite::Result<()> {
+        let game_id = self.game_id;
+        let connection = self.get_connection()?;
+        connection.execute(
+            "INSERT INTO hands (game_id, hand_number, button, small_blind, big_blind, ante)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+            params![
+                game_id,
+                start.hand_number,
+                start.button,
+                start.small_blind,
+                start.big_blind,
+                start.ante
+            ],
+        )?;
+        self.hand_id = Some(connection.last_insert_rowid());
+        self.action_count = 0;
+        Ok(())
+    }
+
+    /// Adds an action to the current hand, actions before the first hand starts aren't written
+    pub fn add_action(&mut self, stored: &StoredAction) -> rusqlite::Result<()> {
+        let Some(hand_id) = self.hand_id else {
+            return Ok(());
+        };
+        let (seat, action, amount) = match stored.action {
+            TableAction::TakePlayerAction(seat, hand_action) => {
+                let (action, amount) = match hand_action {
+                    HandAction::Fold => ("fold", None),
+                    HandAction::Check => ("check", None),
+                    HandAction::Call => ("call", None),
+                    HandAction::Raise(amount) => ("raise", Some(*amount)),
+                };
+                (Some(*seat), action, amount)
+            }
+            TableAction::DealCards(_) => (None, "deal", None),
+            TableAction::AdvanceToStage(_) => (None, "advance", None),
+            TableAction::EvaluateHand(_) => (None, "evaluate", None),
+            TableAction::EliminatePlayer(seat, _) => (Some(*seat), "eliminate", None),
+        };
+        let sequence = self.action_count;
+        self.get_connection()?.execute(
+            "INSERT INTO actions (hand_id, sequence, stage, seat, action, amount, total_bet, is_all_in, description)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+            params![
+                hand_id,
+                sequence,
+                stored.stage.to_string(),
+                seat,
+                action,
+                amount,
+                stored.total_bet,
+                stored.is_all_in,
+                stored.action.to_string()
+            ],
+        )?;
+        self.action_count += 1;
+        Ok(())
+    }
+
+    /// Adds the pot, board cards and showdown of the current hand. Each seat's net chips are what they had
+    /// when the hand was dealt subtracted from what they have now
+    pub fn finish_hand(&mut self, end: &HandEnd, net_chips: &[i32]) -> rusqlite::Result<()> {
+        let Some(hand_id) = self.hand_id else {
+            return Ok(());
+        };
+        let connection = self.get_connection()?;
+        connection.execute(
+            "UPDATE hands SET pot = ?1 WHERE id = ?2",
+            params![end.pot, hand_id],
+        )?;
+        for (board, cards) in end.boards.iter().enumerate() {
+            for (position, card) in cards.iter().enumerate() {
+                connection.execute(
+                    "INSERT INTO board_cards (hand_id, board, position, card) VALUES (?1, ?2, ?3, ?4)",
+                    params![hand_id, board, position, card.rank_suit_string()],
+                )?;
+            }
+        }
+        for (seat, cards) in &end.shown_hands {
+            let hole_cards: Vec<String> =
+                cards.iter().map(|card| card.rank_suit_string()).collect();
+            connection.execute(
+                "INSERT INTO showdowns (hand_id, seat, hole_cards, collected, net_chips, won)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    hand_id,
+                    seat,
+                    hole_cards.join(" "),
+                    end.collected[*seat],
+                    net_chips[*seat],
+                    end.collected[*seat] > 0
+                ],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Adds the results and how each entrant finished, the game can be finished again if more results come in
+    pub fn finish_game(
+        &mut self,
+        results: &str,
+        entrants: &[StoredEntrant],
+    ) -> rusqlite::Result<()> {
+        let game_id = self.game_id;
+        let connection = self.get_connection()?;
+        connection.execute(
+            "UPDATE games SET results = ?1 WHERE id = ?2",
+            params![results, game_id],
+        )?;
+        for entrant in entrants {
+            connection.execute(
+                "UPDATE entrants SET place = ?1, final_chips = ?2, rebuys = ?3, knockouts = ?4
+                 WHERE game_id = ?5 AND seat = ?6",
+                params![
+                    entrant.place,
+                    entrant.final_chips,
+                    entrant.rebuys,
+                    entrant.knockouts,
+                    game_id,
+                    entrant.seat
+                ],
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rusqlite::Connection;
+
+    use crate::actions::HandAction;
+    use crate::bet_stage::BetStage;
+    use crate::table::storage::{Storage, StoredAction, StoredHandStart};
+    use crate::table::table_action::TableAction;
+
+    #[test]
+    fn test_storage_reopens_after_restore() {
+        let path = std::env::temp_dir().join("bot_arena_test_storage.db");
+        let _ = std::fs::remove_file(&path);
+        let mut storage =
+            Storage::create(&path, "Hold'em No Limit", Some(u64::MAX), &[500, 500]).unwrap();
+        storage
+            .start_hand(&StoredHandStart {
+                hand_number: 1,
+                button: 1,
+                small_blind: 0,
+                big_blind: 0,
+                ante: 1,
+            })
+            .unwrap();
+        // A restored table has no connection yet, it is opened on the next write
+        let mut storage: Storage =
+            serde_json::from_str(&serde_json::to_string(&storage).unwrap()).unwrap();
+        assert!(storage.connection.is_none());
+        let action = TableAction::TakePlayerAction(0, HandAction::Raise(20));
+        storage
+            .add_action(&StoredAction {
+                stage: BetStage::PreFlop,
+                action: &action,
+                total_bet: Some(21),
+                is_all_in: false,
+            })
+            .unwrap();
+        drop(storage);
+        let connection = Connection::open(&path).unwrap();
+        let (action, amount, total_bet): (String, i32, i32) = connection
+            .query_row(
+                "SELECT action, amount, total_bet FROM actions JOIN hands ON hands.id = actions.hand_id
+                 WHERE hands.hand_number = 1 AND actions.stage = 'pre flop'",
+                [],
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
+            )
+            .unwrap();
+        assert_eq!((action.as_str(), amount, total_bet), ("raise", 20, 21));
+        let seed: i64 = connection
+            .query_row("SELECT seed FROM games", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(seed as u64, u64::MAX);
+        drop(connection);
+        std::fs::remove_file(&path).unwrap();
+    }
+}
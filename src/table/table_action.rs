@@ -1,17 +1,21 @@
 use std::fmt;
 use std::fmt::Formatter;
 
+use serde::{Deserialize, Serialize};
+
 use crate::actions::HandAction;
+use crate::bet_stage::BetStage;
 use crate::table::deal_information::DealInformation;
 
-#[derive(Clone, Eq, PartialEq, Debug)]
+#[derive(Clone, Deserialize, Eq, PartialEq, Debug, Serialize)]
 pub enum TableAction {
     TakePlayerAction(i8, HandAction),
     DealCards(DealInformation),
-    AdvanceToFlop,
-    AdvanceToTurn,
-    AdvanceToRiver,
+    /// The table moved on to the next round of betting
+    AdvanceToStage(BetStage),
     EvaluateHand(String),
+    /// The eliminated player, and the players that won the pot that knocked them out
+    EliminatePlayer(i8, Vec<i8>),
 }
 
 impl fmt::Display for TableAction {
@@ -27,14 +31,8 @@ impl fmt::Display for TableAction {
             TableAction::DealCards(round_number) => {
                 write!(f, "Table dealt round {round_number}.")
             }
-            TableAction::AdvanceToFlop => {
-                write!(f, "Table advanced to flop.")
-            }
-            TableAction::AdvanceToTurn => {
-                write!(f, "Table advanced to turn.")
-            }
-            TableAction::AdvanceToRiver => {
-                write!(f, "Table advanced to river.")
+            TableAction::AdvanceToStage(stage) => {
+                write!(f, "Table advanced to {stage}.")
             }
             TableAction::EvaluateHand(string) => {
                 write!(
@@ -42,6 +40,14 @@ impl fmt::Display for TableAction {
                     "Table evaluated hand with the following result: {string}"
                 )
             }
+            TableAction::EliminatePlayer(player, winners) => {
+                let winners: Vec<String> = winners.iter().map(|x| x.to_string()).collect();
+                write!(
+                    f,
+                    "Player {player} was knocked out by: [{}].",
+                    winners.join(", ")
+                )
+            }
         }
     }
 }
@@ -0,0 +1,130 @@
+use std::path::PathBuf;
+use std::time::Duration;
+
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::payout::PayoutStructure;
+use crate::table::equity::DEFAULT_MONTE_CARLO_SAMPLES;
+use crate::table::{GameVariant, LevelSchedule};
+
+/// Settings that change how a table plays out, the defaults play a normal single table freezeout
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct TableConfig {
+    /// The game is over once this many players (or fewer) are still alive
+    pub finish_player_count: usize,
+    /// If true the next hand is dealt as soon as the last one is resolved,
+    /// otherwise the table waits between hands until `deal` is called
+    pub deal_automatically: bool,
+    /// If true the table raises its own ante as hands are played, except in fixed limit games,
+    /// otherwise the ante only changes through `set_ante`
+    pub ante_increases: bool,
+    /// Plays a cash game instead of a freezeout if set
+    pub cash_game: Option<CashGameConfig>,
+    /// Lets busted players re-enter early in a freezeout if set
+    pub rebuy: Option<RebuyConfig>,
+    /// The bounty every entrant carries, split between whoever wins the pot that knocks them out
+    pub bounty: Option<i32>,
+    /// Pays out a prize pool by finishing place if set, players still alive get their ICM equity
+    pub payouts: Option<PayoutStructure>,
+    /// Takes the blinds and ante from this schedule instead of growing the ante if set
+    pub level_schedule: Option<LevelSchedule>,
+    /// Limits that end the game early, the players still alive are then ranked by chips
+    pub stop_conditions: StopConditions,
+    /// How the button and blinds move after players are knocked out
+    pub button_rule: ButtonRule,
+    /// The poker game played, it decides the cards dealt and how hands are ranked
+    pub variant: GameVariant,
+    /// How many times the rest of the board is dealt when everyone is all in before it is finished,
+    /// each pot is split evenly between the runs
+    pub run_it_times: usize,
+    /// If true the expected winnings of every all in hand are worked out, and the results list each
+    /// player's chips adjusted for their luck in those hands. Stud hands all in before seventh street
+    /// count for what they won
+    pub all_in_adjusted: bool,
+    /// How many random boards the expected winnings of an all in hand are averaged over when there are
+    /// too many ways of finishing the board to check each one
+    #[serde(default = "get_default_all_in_samples")]
+    pub all_in_samples: usize,
+    /// Appends every finished hand to this file in the PokerStars hand history format if set
+    pub hand_history_file: Option<PathBuf>,
+    /// The seed every deck is shuffled from, a random seed is picked when the table is made if None
+    pub seed: Option<u64>,
+    /// Writes the game with its hands, actions, board cards and showdowns to this SQLite database if set
+    #[serde(default)]
+    pub database_file: Option<PathBuf>,
+}
+
+fn get_default_all_in_samples() -> usize {
+    DEFAULT_MONTE_CARLO_SAMPLES
+}
+
+/// How the button and blinds move between hands once players start getting knocked out
+#[derive(ValueEnum, Clone, Copy, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub enum ButtonRule {
+    /// The button moves to the next alive player and the blinds follow it,
+    /// so after a knockout a player can skip the big blind or post it twice
+    #[default]
+    NextAlive,
+    /// The big blind moves to the next alive player, the small blind and button move one seat each
+    /// even onto a knocked out seat, in which case there is no small blind or the button is dead
+    DeadButton,
+    /// The big blind moves to the next alive player and the small blind and button are the alive
+    /// players right before it, so a player may get the button twice
+    MovingButton,
+}
+
+/// Limits that end a game early, no limits are set by default
+#[derive(Clone, Debug, Default, Deserialize, Eq, PartialEq, Serialize)]
+pub struct StopConditions {
+    /// The game stops once this many hands have been played
+    pub max_hands: Option<i32>,
+    /// The game stops after the first hand that finishes once this much time has passed
+    pub max_duration: Option<Duration>,
+    /// The game stops instead of moving past this level of the schedule, 1 INDEXED
+    pub max_level: Option<usize>,
+}
+
+/// Settings for a cash game, where nobody is knocked out and the game stops after a set number of hands
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct CashGameConfig {
+    /// Every player starts with this stack and is topped back up to it before each hand
+    pub stack_size: i32,
+    /// The forced bet every player posts each hand, it never grows
+    pub ante: i32,
+    /// How many hands are played before the game stops
+    pub hand_count: i32,
+}
+
+/// Settings for a rebuy tournament, players that bust early re-enter with a fresh stack
+#[derive(Clone, Debug, Deserialize, Eq, PartialEq, Serialize)]
+pub struct RebuyConfig {
+    /// Players that bust in one of this many first hands rebuy instead of being knocked out
+    pub period_hands: i32,
+    /// Chips every player still alive gets once when the rebuy period ends, 0 for no add-on
+    pub add_on_chips: i32,
+}
+
+impl Default for TableConfig {
+    fn default() -> Self {
+        TableConfig {
+            finish_player_count: 1,
+            deal_automatically: true,
+            ante_increases: true,
+            cash_game: None,
+            rebuy: None,
+            bounty: None,
+            payouts: None,
+            level_schedule: None,
+            stop_conditions: StopConditions::default(),
+            button_rule: ButtonRule::default(),
+            variant: GameVariant::default(),
+            run_it_times: 1,
+            all_in_adjusted: false,
+            all_in_samples: DEFAULT_MONTE_CARLO_SAMPLES,
+            hand_history_file: None,
+            seed: None,
+            database_file: None,
+        }
+    }
+}
@@ -0,0 +1,48 @@
+use std::fmt;
+use std::fmt::Formatter;
+
+/// Why the table couldn't do what it was asked, the table is left as it was or the hand has to be voided
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum TableError {
+    /// More players than one deck can deal into
+    TooManyPlayers { players: usize, max: usize },
+    /// Players can only join or leave a table between hands
+    HandInProgress,
+    /// Actions can only be taken and hands voided while a hand is being played
+    NoHandInProgress,
+    /// The seat had to still be in the hand, but it had folded or wasn't dealt in
+    InactivePlayer(i8),
+    /// Nobody is left in the hand to act next
+    NoActivePlayer,
+    /// A seat that folded was given a share of a pot
+    FoldedPlayerInPot(i8),
+    /// The side pots were split with the players out of order
+    UnsortedBets,
+}
+
+impl fmt::Display for TableError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> fmt::Result {
+        match self {
+            TableError::TooManyPlayers { players, max } => {
+                write!(
+                    f,
+                    "{players} players can't sit at one table, the most is {max}."
+                )
+            }
+            TableError::HandInProgress => {
+                write!(f, "Players can only join or leave a table between hands.")
+            }
+            TableError::NoHandInProgress => write!(f, "No hand is being played."),
+            TableError::InactivePlayer(seat) => {
+                write!(f, "Player {seat} isn't in the hand.")
+            }
+            TableError::NoActivePlayer => write!(f, "No player in the hand is left to act."),
+            TableError::FoldedPlayerInPot(seat) => {
+                write!(f, "Player {seat} folded but was in a pot.")
+            }
+            TableError::UnsortedBets => write!(f, "Players are not sorted by their bets."),
+        }
+    }
+}
+
+impl std::error::Error for TableError {}
@@ -7,21 +7,27 @@ use rand::thread_rng;
 use rand::Rng;
 
 use crate::actions::HandAction;
+use crate::bet_stage::BetStage;
 use crate::bet_stage::BetStage::{Flop, PreFlop, River, Turn};
-use crate::player_components::{PlayerState, DEFAULT_START_MONEY};
+use crate::payout::PayoutStructure;
+use crate::player_components::{HoleCards, Player, PlayerState, DEFAULT_START_MONEY};
+use crate::table::game_variant::get_bring_in_rank;
 use crate::table::table_action::get_vec_of_strings_from_actions;
-use crate::table::{DealInformation, Table, TableAction};
+use crate::table::{
+    ButtonRule, CashGameConfig, DealInformation, GameVariant, Knockout, LevelSchedule, RebuyConfig,
+    StopConditions, StopReason, Table, TableAction, TableConfig, TableError,
+};
 
 fn deal_test_cards() -> Table {
-    let mut table = Table::new(6);
+    let mut table = Table::new(6).unwrap();
     // After the deal set the cards to known values
-    table.flop = Some([
+    table.board = vec![
         Card::new(poker::Rank::Ten, poker::Suit::Spades),
         Card::new(poker::Rank::Jack, poker::Suit::Spades),
         Card::new(poker::Rank::Queen, poker::Suit::Spades),
-    ]);
-    table.turn = Some(Card::new(poker::Rank::Two, poker::Suit::Hearts));
-    table.river = Some(Card::new(poker::Rank::Seven, poker::Suit::Diamonds));
+        Card::new(poker::Rank::Two, poker::Suit::Hearts),
+        Card::new(poker::Rank::Seven, poker::Suit::Diamonds),
+    ];
 
     let hands = vec![
         [
@@ -51,41 +57,41 @@ fn deal_test_cards() -> Table {
     ];
     for (i, hand) in hands.into_iter().enumerate() {
         if let PlayerState::Active(active) = &mut table.players[i].player_state {
-            active.hand = hand;
+            active.hand = HoleCards::new(&hand);
         }
     }
-    table.players[4].fold();
-    table.players[5].fold();
+    table.players[4].fold().unwrap();
+    table.players[5].fold().unwrap();
     table
 }
 
 fn deal_test_cards_tied_best() -> Table {
     let mut table = deal_test_cards();
     if let PlayerState::Active(active) = &mut table.players[0].player_state {
-        active.hand = [
+        active.hand = HoleCards::new(&[
             Card::new(poker::Rank::Ace, poker::Suit::Hearts),
             Card::new(poker::Rank::King, poker::Suit::Hearts),
-        ];
+        ]);
     }
     if let PlayerState::Active(active) = &mut table.players[1].player_state {
-        active.hand = [
+        active.hand = HoleCards::new(&[
             Card::new(poker::Rank::Ace, poker::Suit::Diamonds),
             Card::new(poker::Rank::King, poker::Suit::Diamonds),
-        ];
+        ]);
     }
     table
 }
 
 fn two_sets_of_ties() -> Table {
-    let mut table = Table::new(6);
+    let mut table = Table::new(6).unwrap();
     // After the deal set the cards to known values
-    table.flop = Some([
+    table.board = vec![
         Card::new(poker::Rank::Ten, poker::Suit::Spades),
         Card::new(poker::Rank::Jack, poker::Suit::Spades),
         Card::new(poker::Rank::Queen, poker::Suit::Spades),
-    ]);
-    table.turn = Some(Card::new(poker::Rank::Two, poker::Suit::Hearts));
-    table.river = Some(Card::new(poker::Rank::Seven, poker::Suit::Diamonds));
+        Card::new(poker::Rank::Two, poker::Suit::Hearts),
+        Card::new(poker::Rank::Seven, poker::Suit::Diamonds),
+    ];
     let hands = vec![
         [
             Card::new(poker::Rank::Ace, poker::Suit::Hearts),
@@ -114,7 +120,7 @@ fn two_sets_of_ties() -> Table {
     ];
     for (i, hand) in hands.into_iter().enumerate() {
         if let PlayerState::Active(active) = &mut table.players[i].player_state {
-            active.hand = hand;
+            active.hand = HoleCards::new(&hand);
         }
     }
     table.players[0].total_money = 0;
@@ -141,7 +147,7 @@ pub fn check_table_has_right_amount(table: &Table) {
 #[test]
 pub fn check_table_string() {
     let mut table = deal_test_cards();
-    table.deal();
+    table.deal().unwrap();
     let player_string = table.get_state_string_for_current_player();
     assert!(!player_string.is_empty());
 }
@@ -163,15 +169,15 @@ pub fn check_table_action_strings() {
         "Table dealt round hand number: 3, dealer index: 0."
     );
     assert_eq!(
-        format!("{}", TableAction::AdvanceToFlop),
+        format!("{}", TableAction::AdvanceToStage(Flop)),
         "Table advanced to flop."
     );
     assert_eq!(
-        format!("{}", TableAction::AdvanceToTurn),
+        format!("{}", TableAction::AdvanceToStage(Turn)),
         "Table advanced to turn."
     );
     assert_eq!(
-        format!("{}", TableAction::AdvanceToRiver),
+        format!("{}", TableAction::AdvanceToStage(River)),
         "Table advanced to river."
     );
     assert_eq!(
@@ -184,33 +190,40 @@ pub fn check_table_action_strings() {
 }
 
 #[test]
-#[should_panic]
 fn check_all_players_dead_breaks_update() {
     let mut table = deal_test_cards();
     for player in &mut table.players {
         player.death_hand_number = Some(1);
     }
-    table.update_current_player_index_to_next_active();
+    assert_eq!(
+        table.update_current_player_index_to_next_active(),
+        Err(TableError::NoActivePlayer)
+    );
 }
 
 #[test]
-#[should_panic]
 fn check_all_players_inactive_breaks_update() {
     let mut table = deal_test_cards();
     for player in &mut table.players {
         player.player_state = PlayerState::Folded;
     }
-    table.update_current_player_index_to_next_active();
+    assert_eq!(
+        table.update_current_player_index_to_next_active(),
+        Err(TableError::NoActivePlayer)
+    );
 }
 
 #[test]
-#[should_panic]
 fn check_all_players_inactive_breaks_take_action() {
     let mut table = deal_test_cards();
     for player in &mut table.players {
         player.player_state = PlayerState::Folded;
     }
-    table.take_action(HandAction::Check);
+    let seat = table.current_player_index as i8;
+    assert_eq!(
+        table.take_action(HandAction::Check),
+        Err(TableError::InactivePlayer(seat))
+    );
 }
 
 #[test]
@@ -218,25 +231,25 @@ pub fn test_side_pot() {
     let mut table = deal_test_cards_tied_best_side_pot();
     table.current_player_index = 0;
     table.dealer_button_index = table.players.len() - 1;
-    table.update_current_player_index_to_next_active();
+    table.update_current_player_index_to_next_active().unwrap();
     assert_eq!(2, table.current_player_index);
-    table.take_action(HandAction::Raise(1));
+    table.take_action(HandAction::Raise(1)).unwrap();
     assert_eq!(3, table.current_player_index);
-    table.take_action(HandAction::Call);
+    table.take_action(HandAction::Call).unwrap();
     for i in 2..4 {
         assert_eq!(table.table_state, Flop);
         assert_eq!(table.current_player_index, i);
-        table.take_action(HandAction::Check);
+        table.take_action(HandAction::Check).unwrap();
     }
     for i in 2..4 {
         assert_eq!(table.table_state, Turn);
         assert_eq!(table.current_player_index, i);
-        table.take_action(HandAction::Check);
+        table.take_action(HandAction::Check).unwrap();
     }
     for i in 2..4 {
         assert_eq!(table.table_state, River);
         assert_eq!(table.current_player_index, i);
-        table.take_action(HandAction::Check);
+        table.take_action(HandAction::Check).unwrap();
     }
     assert_eq!(table.table_state, PreFlop);
     assert!(table.players[0].is_alive());
@@ -256,7 +269,7 @@ pub fn test_get_bet_increases_amount() {
     let mut players = vec![];
     for i in 0..10 {
         let mut player = Player::new(i as i8);
-        player.deal([
+        player.deal(&[
             Card::new(poker::Rank::Ace, poker::Suit::Hearts),
             Card::new(poker::Rank::King, poker::Suit::Hearts),
         ]);
@@ -268,7 +281,7 @@ pub fn test_get_bet_increases_amount() {
         }
         players.push(player)
     }
-    let result = Table::get_bet_increases_amount(&players);
+    let result = Table::get_bet_increases_amount(&players).unwrap();
     assert_eq!(result[0], 0);
 
     for (index, &value) in result.iter().enumerate().take(10).skip(1) {
@@ -282,14 +295,14 @@ pub fn one_winner() {
     // Required for the table evaluator
     let mut table = deal_test_cards();
     check_table_has_right_amount(&table);
-    table.resolve_hand();
+    table.resolve_hand().unwrap();
     for player in &table.players {
         assert!(player.is_alive());
     }
     check_table_has_right_amount(&table);
     assert_eq!(
         (table.players.len() as i32) * table.ante - (table.ante * 2) + DEFAULT_START_MONEY,
-        table.players.get(0).unwrap().total_money
+        table.players.first().unwrap().total_money
     );
 }
 
@@ -298,14 +311,14 @@ pub fn two_winners() {
     // Required for the table evaluator
     let mut table = deal_test_cards_tied_best();
     check_table_has_right_amount(&table);
-    table.resolve_hand();
+    table.resolve_hand().unwrap();
     for player in &table.players {
         assert!(player.is_alive());
     }
     check_table_has_right_amount(&table);
     assert_eq!(
         (table.players.len() as i32) * table.ante / 2 - (table.ante * 2) + DEFAULT_START_MONEY,
-        table.players.get(0).unwrap().total_money
+        table.players.first().unwrap().total_money
     );
     assert_eq!(
         (table.players.len() as i32) * table.ante / 2 - (table.ante * 2) + DEFAULT_START_MONEY,
@@ -317,54 +330,54 @@ pub fn two_winners() {
 pub fn test_two_side_pots_with_actions_checked() {
     let mut table = two_sets_of_ties();
     assert_eq!(table.get_current_player_mut().get_id(), 1);
-    table.take_action(HandAction::Check);
+    table.take_action(HandAction::Check).unwrap();
     assert_eq!(
         *table.round_actions.last().unwrap(),
         TableAction::TakePlayerAction(1_i8, HandAction::Check)
     );
     assert_eq!(table.get_current_player_mut().get_id(), 2);
-    table.take_action(HandAction::Raise(1));
+    table.take_action(HandAction::Raise(1)).unwrap();
     assert_eq!(
         *table.round_actions.last().unwrap(),
         TableAction::TakePlayerAction(2_i8, HandAction::Raise(1))
     );
     assert_eq!(table.get_current_player_mut().get_id(), 3);
-    table.take_action(HandAction::Call);
+    table.take_action(HandAction::Call).unwrap();
     assert_eq!(
         *table.round_actions.last().unwrap(),
         TableAction::TakePlayerAction(3_i8, HandAction::Call)
     );
     assert_eq!(table.get_current_player_mut().get_id(), 4);
-    table.take_action(HandAction::Raise(10));
+    table.take_action(HandAction::Raise(10)).unwrap();
     // The max raise at this point is 8, so the actual raise should be 8 now
     assert_eq!(
         *table.round_actions.last().unwrap(),
         TableAction::TakePlayerAction(4_i8, HandAction::Raise(8))
     );
     assert_eq!(table.get_current_player_mut().get_id(), 5);
-    table.take_action(HandAction::Call);
+    table.take_action(HandAction::Call).unwrap();
     // Now we are in the next stage
     assert_eq!(
         *table.round_actions.last().unwrap(),
-        TableAction::AdvanceToFlop
+        TableAction::AdvanceToStage(Flop)
     );
     for _ in 0..2 {
-        table.take_action(HandAction::Check);
+        table.take_action(HandAction::Check).unwrap();
     }
     assert_eq!(
         *table.round_actions.last().unwrap(),
-        TableAction::AdvanceToTurn
+        TableAction::AdvanceToStage(Turn)
     );
     // Check we advance as the non-allin players check
     for _ in 0..2 {
-        table.take_action(HandAction::Check);
+        table.take_action(HandAction::Check).unwrap();
     }
     assert_eq!(
         *table.round_actions.last().unwrap(),
-        TableAction::AdvanceToRiver
+        TableAction::AdvanceToStage(River)
     );
     for _ in 0..2 {
-        table.take_action(HandAction::Check);
+        table.take_action(HandAction::Check).unwrap();
     }
     //Now the table should have dealt again
     assert_eq!(
@@ -377,11 +390,12 @@ pub fn test_two_side_pots_with_actions_checked() {
     // First two tied for 6, and ante up for the next round so they're at 2
     assert_eq!(table.players[0].total_money, 2);
     assert_eq!(table.players[1].total_money, 2);
-    // Second two bet 2 each, total of 12, lose 6 to above, split the other 6
-    assert_eq!(table.players[2].total_money, 2);
-    assert_eq!(table.players[3].total_money, 2);
-    // This one takes 7 from player 6, and has lost 3 from the above pots, and anted 1
-    assert_eq!(table.players[4].total_money, 503);
+    // Second two split the side pot of the second chip from the four players that bet it
+    assert_eq!(table.players[2].total_money, 1);
+    assert_eq!(table.players[3].total_money, 1);
+    // This one takes the side pot of the other 8 chips each from player 6, having lost 2 to the above pots,
+    // and anted 1
+    assert_eq!(table.players[4].total_money, 505);
     // This one just loses 11
     assert_eq!(table.players[5].total_money, 489);
     // Generate the latest round string and make sure some events occurred
@@ -397,18 +411,16 @@ pub fn test_two_side_pots_with_actions_checked() {
 pub fn test_deal_correct_size() {
     // Required for the table evaluator
     const PLAYER_SIZE: usize = 23;
-    let mut table = Table::new(PLAYER_SIZE);
+    let mut table = Table::new(PLAYER_SIZE).unwrap();
     // Deal the largest table size allowed
-    table.deal();
+    table.deal().unwrap();
     // Make read only now
     let table = table;
     // Make a set to make sure there are unique cards
     let mut cards = HashSet::new();
-    for card in &table.flop.unwrap() {
+    for card in &table.board {
         cards.insert(card.to_string());
     }
-    cards.insert(table.turn.unwrap().to_string());
-    cards.insert(table.river.unwrap().to_string());
 
     for player in &table.players {
         match player.player_state {
@@ -433,10 +445,10 @@ pub fn test_deal_correct_size() {
 pub fn test_deal_with_dead_players() {
     // Required for the table evaluator
     const PLAYER_SIZE: usize = 23;
-    let mut table = Table::new(PLAYER_SIZE);
+    let mut table = Table::new(PLAYER_SIZE).unwrap();
     // Deal the largest table size allowed
     table.players.get_mut(0).unwrap().total_money = 0;
-    table.deal();
+    table.deal().unwrap();
     // Make sure one player has died.
     let alive_players = table
         .players
@@ -451,7 +463,7 @@ pub fn test_deal_with_dead_players() {
 pub fn test_lots_of_deals() {
     // Required for the table evaluator
     const PLAYER_SIZE: usize = 23;
-    let mut table = Table::new(PLAYER_SIZE);
+    let mut table = Table::new(PLAYER_SIZE).unwrap();
     // Add a player that will die later, so as to be seen as an alive winner
     table.players.get_mut(0).unwrap().total_money = DEFAULT_START_MONEY * 10;
     // Deal the largest table size allowed until the game is over
@@ -459,22 +471,26 @@ pub fn test_lots_of_deals() {
         if table.is_game_over() {
             break;
         }
-        table.deal();
+        table.deal().unwrap();
     }
 }
 
 #[test]
-#[should_panic]
 pub fn test_deal_too_many_players() {
-    // Add to many players and expect a panic
-    let mut table = Table::new(24);
-    table.deal()
+    // Too many players for one deck can't sit at a table
+    assert_eq!(
+        Table::new(24).err(),
+        Some(TableError::TooManyPlayers {
+            players: 24,
+            max: Table::MAX_PLAYERS
+        })
+    );
 }
 
 #[test]
 pub fn test_print() {
-    let mut table = Table::new(23);
-    table.deal();
+    let mut table = Table::new(23).unwrap();
+    table.deal().unwrap();
     let string = table.to_string();
     assert!(string.contains("\"flop\":["));
     assert!(string.contains("\"turn\":"));
@@ -488,8 +504,8 @@ pub fn test_print() {
 
 #[test]
 pub fn test_print_fold_and_active_players() {
-    let mut table = Table::new(23);
-    table.players.get_mut(0).unwrap().fold();
+    let mut table = Table::new(23).unwrap();
+    table.players.get_mut(0).unwrap().fold().unwrap();
     let string = table.to_string();
     assert!(string.contains("\"flop\":["));
     assert!(string.contains("\"turn\":"));
@@ -503,8 +519,8 @@ pub fn test_print_fold_and_active_players() {
 
 #[test]
 pub fn check_correct_number_of_lists_present() {
-    let mut table = Table::new(23);
-    table.deal();
+    let mut table = Table::new(23).unwrap();
+    table.deal().unwrap();
     let json_string = table.get_table_state_json_for_player(0).to_string();
     // 5 open brackets, 1 for the player list, 1 for the card list, 1 for the flop, 1 for actions, 1 for previous actions
     assert_eq!(json_string.matches('[').count(), 7);
@@ -513,8 +529,8 @@ pub fn check_correct_number_of_lists_present() {
 #[test]
 pub fn test_results_all_tied() {
     const NUMBER_OF_PLAYERS: usize = 23;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
-    table.deal();
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
+    table.deal().unwrap();
     // Get results for for a starting table, which should be all tied
     let results = table.get_results();
     // Split the lines
@@ -528,103 +544,105 @@ pub fn test_results_all_tied() {
 #[test]
 fn test_players_all_checks() {
     const NUMBER_OF_PLAYERS: usize = 23;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
-    table.deal();
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
+    table.deal().unwrap();
     assert_eq!(table.table_state, PreFlop);
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     for _ in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Check);
+        table.take_action(HandAction::Check).unwrap();
     }
     assert_eq!(table.table_state, Flop);
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     for _ in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Check);
+        table.take_action(HandAction::Check).unwrap();
     }
     assert_eq!(table.table_state, Turn);
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     for _ in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Check);
+        table.take_action(HandAction::Check).unwrap();
     }
     assert_eq!(table.table_state, River);
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     for _ in 0..(NUMBER_OF_PLAYERS - 1) {
-        table.take_action(HandAction::Check);
+        table.take_action(HandAction::Check).unwrap();
     }
 }
 
 #[test]
 fn test_players_calling() {
     const NUMBER_OF_PLAYERS: usize = 23;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
-    table.deal();
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
+    table.deal().unwrap();
     assert_eq!(table.table_state, PreFlop);
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     for _ in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Call);
+        table.take_action(HandAction::Call).unwrap();
     }
     assert_eq!(table.table_state, Flop);
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     for _ in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Call);
+        table.take_action(HandAction::Call).unwrap();
     }
     assert_eq!(table.table_state, Turn);
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     for _ in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Call);
+        table.take_action(HandAction::Call).unwrap();
     }
     assert_eq!(table.table_state, River);
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     for _ in 0..(NUMBER_OF_PLAYERS - 1) {
-        table.take_action(HandAction::Call);
+        table.take_action(HandAction::Call).unwrap();
     }
 }
 
 #[test]
 fn test_everyone_all_in() {
     const NUMBER_OF_PLAYERS: usize = 3;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
     assert_eq!(table.table_state, PreFlop);
     table.players.get_mut(1).unwrap().total_money = DEFAULT_START_MONEY / 2;
-    table.take_action(HandAction::Check);
-    table.take_action(HandAction::Raise(DEFAULT_START_MONEY / 2 + 1));
+    table.take_action(HandAction::Check).unwrap();
+    table
+        .take_action(HandAction::Raise(DEFAULT_START_MONEY / 2 + 1))
+        .unwrap();
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     for _ in 0..NUMBER_OF_PLAYERS - 2 {
-        table.take_action(HandAction::Call);
+        table.take_action(HandAction::Call).unwrap();
     }
     assert!(!table.check_all_active_players_same_bet());
-    table.take_action(HandAction::Call);
+    table.take_action(HandAction::Call).unwrap();
     assert!(table.check_all_active_players_same_bet());
 }
 
 #[test]
 fn test_players_raising_and_calling() {
     const NUMBER_OF_PLAYERS: usize = 23;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
     assert_eq!(table.table_state, PreFlop);
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     // Everyone raises by one
     for i in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Raise(1));
+        table.take_action(HandAction::Raise(1)).unwrap();
         let actual_largest_active_bet = table.get_largest_active_bet() as usize;
         let correct_largest_bet = i + 2;
         assert_eq!(actual_largest_active_bet, correct_largest_bet);
     }
     assert_eq!(table.get_largest_active_bet(), 1 + NUMBER_OF_PLAYERS as i32);
     for _ in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Call);
+        table.take_action(HandAction::Call).unwrap();
     }
     assert_eq!(table.table_state, Flop);
     // Just one person raises and everyone else calls
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
-    table.take_action(HandAction::Raise(1));
+    table.take_action(HandAction::Raise(1)).unwrap();
     for _ in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Call);
+        table.take_action(HandAction::Call).unwrap();
     }
     assert_eq!(table.get_largest_active_bet(), 2 + NUMBER_OF_PLAYERS as i32);
     assert_eq!(table.table_state, Turn);
     // Have everyone bet again
     for _ in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Raise(1));
+        table.take_action(HandAction::Raise(1)).unwrap();
     }
     assert_eq!(
         table.get_largest_active_bet(),
@@ -632,12 +650,12 @@ fn test_players_raising_and_calling() {
     );
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     for _ in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Call);
+        table.take_action(HandAction::Call).unwrap();
     }
     assert_eq!(table.table_state, River);
     // Have everyone bet again
     for _ in 0..NUMBER_OF_PLAYERS {
-        table.take_action(HandAction::Raise(3));
+        table.take_action(HandAction::Raise(3)).unwrap();
     }
     assert_eq!(
         table.get_largest_active_bet(),
@@ -645,14 +663,14 @@ fn test_players_raising_and_calling() {
     );
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     for _ in 0..(NUMBER_OF_PLAYERS - 1) {
-        table.take_action(HandAction::Call);
+        table.take_action(HandAction::Call).unwrap();
     }
 }
 
 #[test]
 fn test_players_raising_over_pot_limit() {
     const NUMBER_OF_PLAYERS: usize = 23;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
     assert_eq!(table.table_state, PreFlop);
     assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
     let mut correct_largest_bet = 1;
@@ -660,7 +678,9 @@ fn test_players_raising_over_pot_limit() {
     for _ in 0..NUMBER_OF_PLAYERS {
         correct_largest_bet += table.get_pot_size();
         correct_largest_bet = min(correct_largest_bet, DEFAULT_START_MONEY);
-        table.take_action(HandAction::Raise(DEFAULT_START_MONEY * 100 / 2));
+        table
+            .take_action(HandAction::Raise(DEFAULT_START_MONEY * 100 / 2))
+            .unwrap();
         let actual_largest_active_bet = table.get_largest_active_bet();
         assert_eq!(actual_largest_active_bet, correct_largest_bet);
     }
@@ -671,12 +691,12 @@ fn test_one_raise_all_checks() {
     const NUMBER_OF_PLAYERS: usize = 23;
     let raise_amounts = vec![1, 2, 3, 4];
     for raise_amount in raise_amounts {
-        let mut table = Table::new(NUMBER_OF_PLAYERS);
-        table.take_action(HandAction::Raise(raise_amount));
+        let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
+        table.take_action(HandAction::Raise(raise_amount)).unwrap();
         assert_eq!(table.table_state, PreFlop);
         assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
         for _ in 0..NUMBER_OF_PLAYERS - 1 {
-            table.take_action(HandAction::Check);
+            table.take_action(HandAction::Check).unwrap();
         }
         // Check the table has the right amount of money
         check_table_has_right_amount(&table);
@@ -693,7 +713,7 @@ fn test_api_reasonable(table: &Table) {
     let json = table.get_state_json_for_current_player();
     let _json_string = json.to_string();
     // The object is filled out
-    assert_eq!(json.len(), 12);
+    assert_eq!(json.len(), 13);
     // Id check
     assert!(json["id"].as_i8().is_some());
     // Current bet check
@@ -723,6 +743,9 @@ fn test_api_reasonable(table: &Table) {
     assert_eq!(json["players"].len(), table.players.len());
     // Actions
     assert!(!json["actions"].is_empty());
+    // Level
+    assert!(json["level"]["ante"].as_i32().is_some());
+    assert!(json["level"]["big_blind"].as_i32().is_some());
     // Previous actions, empty first hand then should have previous hands
     if table.hand_number == 1 {
         assert!(json["previous_actions"].is_empty());
@@ -737,7 +760,7 @@ fn test_rounds_with_some_folding() {
     const NUMBER_OF_GAMES: i32 = 25;
     for game_number in 0..NUMBER_OF_GAMES {
         info!("Starting game: {game_number}");
-        let mut table = Table::new(NUMBER_OF_PLAYERS);
+        let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
         test_api_reasonable(&table);
         assert_eq!(table.table_state, PreFlop);
         assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
@@ -748,10 +771,10 @@ fn test_rounds_with_some_folding() {
         for _ in 0..1000000 {
             if table.is_game_over() {
                 // Make sure dealing also doesn't enable the game
-                table.deal();
+                table.deal().unwrap();
                 assert!(table.is_game_over());
                 // Make sure taking actions doesn't somehow enable the game
-                table.take_action(HandAction::Call);
+                table.take_action(HandAction::Call).unwrap();
                 assert!(table.is_game_over());
                 break;
             }
@@ -785,10 +808,10 @@ fn test_rounds_with_some_folding() {
             let mut rng = thread_rng();
             let action_int = rng.gen_range(0..4);
             match action_int {
-                0 => table.take_action(HandAction::Raise(1)),
-                1 => table.take_action(HandAction::Check),
-                2 => table.take_action(HandAction::Call),
-                _ => table.take_action(HandAction::Fold),
+                0 => table.take_action(HandAction::Raise(1)).unwrap(),
+                1 => table.take_action(HandAction::Check).unwrap(),
+                2 => table.take_action(HandAction::Call).unwrap(),
+                _ => table.take_action(HandAction::Fold).unwrap(),
             }
         }
         info!("The following game ended: {game_number}")
@@ -800,7 +823,7 @@ fn test_rounds_with_only_calling() {
     const NUMBER_OF_PLAYERS: usize = 2;
     for round_number in 0..25 {
         info!("Starting round: {round_number}");
-        let mut table = Table::new(NUMBER_OF_PLAYERS);
+        let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
         test_api_reasonable(&table);
         assert_eq!(table.table_state, PreFlop);
         assert_eq!(table.get_active_player_count(), NUMBER_OF_PLAYERS);
@@ -809,10 +832,10 @@ fn test_rounds_with_only_calling() {
         for _ in 0..1000000 {
             if table.is_game_over() {
                 // Make sure dealing also doesn't enable the game
-                table.deal();
+                table.deal().unwrap();
                 assert!(table.is_game_over());
                 // Make sure taking actions doesn't somehow enable the game
-                table.take_action(HandAction::Call);
+                table.take_action(HandAction::Call).unwrap();
                 assert!(table.is_game_over());
                 break;
             }
@@ -822,7 +845,7 @@ fn test_rounds_with_only_calling() {
                 previous_dealer_index = Some(table.dealer_button_index);
             }
             assert!(table.get_current_player_mut().player_state.is_active());
-            table.take_action(HandAction::Call)
+            table.take_action(HandAction::Call).unwrap()
         }
         info!("Following round passed: {round_number}")
     }
@@ -831,8 +854,8 @@ fn test_rounds_with_only_calling() {
 #[test]
 fn test_flop_string() {
     const NUMBER_OF_PLAYERS: usize = 23;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
-    table.flop = None;
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
+    table.board = vec![];
     let string_value = table.get_flop_string().to_string();
     assert_eq!(string_value, "[\"None\"]");
 }
@@ -840,24 +863,24 @@ fn test_flop_string() {
 #[test]
 fn test_flop_string_secret() {
     const NUMBER_OF_PLAYERS: usize = 23;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
     assert_eq!(table.get_flop_string_secret().to_string(), "[\"Hidden\"]");
     table.table_state = Flop;
     assert!(!table.get_flop_string_secret().contains("Hidden"));
-    table.flop = None;
+    table.board = vec![];
     assert_eq!(table.get_flop_string_secret().to_string(), "[\"None\"]");
 }
 
 #[test]
 fn test_turn_string() {
     const NUMBER_OF_PLAYERS: usize = 23;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
     assert_eq!(table.get_turn_string_secret(), "Hidden");
     table.table_state = Flop;
     assert_eq!(table.get_turn_string_secret(), "Hidden");
     table.table_state = Turn;
     assert!(!table.get_turn_string_secret().contains("Hidden"));
-    table.turn = None;
+    table.board.truncate(3);
     assert_eq!(table.get_turn_string_secret(), "None");
     assert_eq!(table.get_turn_string(), "None");
 }
@@ -865,13 +888,13 @@ fn test_turn_string() {
 #[test]
 fn test_river_string() {
     const NUMBER_OF_PLAYERS: usize = 23;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
     assert_eq!(table.get_river_string_secret(), "Hidden");
     table.table_state = Flop;
     assert_eq!(table.get_river_string_secret(), "Hidden");
     table.table_state = River;
     assert!(!table.get_river_string_secret().contains("Hidden"));
-    table.river = None;
+    table.board.truncate(4);
     assert_eq!(table.get_river_string_secret(), "None");
     assert_eq!(table.get_river_string(), "None");
 }
@@ -949,14 +972,14 @@ pub fn test_get_hand_result_folds_in_middle() {
 #[test]
 pub fn test_ante_increase() {
     const NUMBER_OF_PLAYERS: usize = 2;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
     for _ in 0..(NUMBER_OF_PLAYERS * 2 - 1) {
         assert_eq!(table.ante, 1);
-        table.deal();
+        table.deal().unwrap();
     }
     for _ in 0..NUMBER_OF_PLAYERS * 2 {
         assert_eq!(table.ante, 1 + Table::ANTE_INCREASE_AMOUNT);
-        table.deal();
+        table.deal().unwrap();
     }
     assert_eq!(table.ante, 1 + 2 * Table::ANTE_INCREASE_AMOUNT);
 }
@@ -996,11 +1019,9 @@ pub fn test_sort_by_bet_amount_reversed() {
 pub fn test_only_unique_cards() {
     const NUMBER_OF_PLAYERS: usize = 23;
     for _ in 0..100000 {
-        let table = Table::new(NUMBER_OF_PLAYERS);
+        let table = Table::new(NUMBER_OF_PLAYERS).unwrap();
         let mut set: HashSet<Card> = HashSet::new();
-        set.extend(table.flop.unwrap().iter());
-        set.insert(table.turn.unwrap());
-        set.insert(table.river.unwrap());
+        set.extend(table.board.iter());
 
         for player in table.players {
             match player.player_state {
@@ -1015,16 +1036,14 @@ pub fn test_only_unique_cards() {
 #[test]
 pub fn test_only_unique_cards_with_deal() {
     const NUMBER_OF_PLAYERS: usize = 23;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
     table.ante = 0;
     const ROUNDS: i32 = 100000;
     table.ante_round_increase = ROUNDS;
     for _ in 0..ROUNDS {
-        table.deal();
+        table.deal().unwrap();
         let mut set: HashSet<Card> = HashSet::new();
-        set.extend(table.flop.unwrap().iter());
-        set.insert(table.turn.unwrap());
-        set.insert(table.river.unwrap());
+        set.extend(table.board.iter());
 
         for player in &table.players {
             match player.player_state {
@@ -1039,16 +1058,16 @@ pub fn test_only_unique_cards_with_deal() {
 #[test]
 pub fn test_raise_action_string() {
     const NUMBER_OF_PLAYERS: usize = 2;
-    let mut table = Table::new(NUMBER_OF_PLAYERS);
+    let mut table = Table::new(NUMBER_OF_PLAYERS).unwrap();
     assert_eq!(table.table_state, PreFlop);
-    table.take_action(HandAction::Raise(1));
-    table.take_action(HandAction::Raise(1));
-    table.take_action(HandAction::Raise(1));
-    table.take_action(HandAction::Raise(1));
-    table.take_action(HandAction::Raise(1));
-    table.take_action(HandAction::Raise(1));
-    table.take_action(HandAction::Raise(1));
-    table.take_action(HandAction::Call);
+    table.take_action(HandAction::Raise(1)).unwrap();
+    table.take_action(HandAction::Raise(1)).unwrap();
+    table.take_action(HandAction::Raise(1)).unwrap();
+    table.take_action(HandAction::Raise(1)).unwrap();
+    table.take_action(HandAction::Raise(1)).unwrap();
+    table.take_action(HandAction::Raise(1)).unwrap();
+    table.take_action(HandAction::Raise(1)).unwrap();
+    table.take_action(HandAction::Call).unwrap();
     assert_eq!(table.table_state, Flop);
     let strings = get_vec_of_strings_from_actions(&table.round_actions);
     for string in &strings {
@@ -1062,3 +1081,1634 @@ pub fn test_raise_action_string() {
         7
     );
 }
+
+#[test]
+pub fn test_negative_raise_is_a_call() {
+    let mut table = Table::new(3).unwrap();
+    table.take_action(HandAction::Raise(2)).unwrap();
+    let seat = table.current_player_index;
+    table.take_action(HandAction::Raise(-20)).unwrap();
+    assert_eq!(
+        *table.round_actions.last().unwrap(),
+        TableAction::TakePlayerAction(seat as i8, HandAction::Call)
+    );
+    assert_eq!(table.player_bets[seat], 3);
+}
+
+#[test]
+pub fn test_folded_bet_past_every_all_in_is_refunded() {
+    for variant in [GameVariant::TexasHoldem, GameVariant::OmahaHiLo] {
+        let mut table = make_variant_table(3, variant);
+        let raiser = table.current_player_index;
+        // Both other players can only just cover the ante
+        for offset in 1..3 {
+            table.players[(raiser + offset) % 3].total_money = offset as i32;
+        }
+        let chips = table.players.iter().map(|x| x.total_money).sum::<i32>() + table.get_pot_size();
+        // The raise is capped at the pot of 3, then both calls are all in for less
+        table.take_action(HandAction::Raise(3)).unwrap();
+        table.take_action(HandAction::Call).unwrap();
+        table.take_action(HandAction::Call).unwrap();
+        // Alone on the flop the raiser folds with 1 chip more in than anyone still in the hand, it comes back
+        assert_eq!(table.current_player_index, raiser);
+        table.take_action(HandAction::Fold).unwrap();
+        assert_eq!(table.hand_number, 2);
+        assert_eq!(table.players[raiser].hand_starting_money, 497);
+        assert_eq!(
+            table.players.iter().map(|x| x.total_money).sum::<i32>() + table.get_pot_size(),
+            chips
+        );
+    }
+}
+
+#[test]
+pub fn test_void_hand_refunds_every_bet() {
+    let mut table = Table::new(3).unwrap();
+    table.take_action(HandAction::Raise(20)).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    assert!(table.get_pot_size() > 0);
+    // Break the hand so the next action fails part way through
+    let seat = table.current_player_index;
+    table.players[seat].player_state = PlayerState::Folded;
+    assert_eq!(
+        table.take_action(HandAction::Call),
+        Err(TableError::InactivePlayer(seat as i8))
+    );
+    table.void_hand().unwrap();
+    assert_eq!(table.hand_number, 2);
+    assert!(table.is_hand_in_progress());
+    for player in &table.players {
+        assert_eq!(player.hand_starting_money, DEFAULT_START_MONEY);
+    }
+    assert!(table.previous_round_actions.iter().any(
+        |action| matches!(action, TableAction::EvaluateHand(result) if result.contains("voided"))
+    ));
+    // Between hands there is nothing to void
+    let config = TableConfig {
+        deal_automatically: false,
+        ..TableConfig::default()
+    };
+    let mut table = Table::with_config(3, config).unwrap();
+    assert_eq!(table.void_hand(), Err(TableError::NoHandInProgress));
+}
+
+#[test]
+pub fn test_manual_deal_waits_between_hands() {
+    let config = TableConfig {
+        deal_automatically: false,
+        ante_increases: false,
+        ..TableConfig::default()
+    };
+    let mut table = Table::with_config(3, config).unwrap();
+    assert!(!table.is_hand_in_progress());
+    assert_eq!(table.hand_number, 0);
+    // Actions between hands are refused
+    assert_eq!(
+        table.take_action(HandAction::Raise(5)),
+        Err(TableError::NoHandInProgress)
+    );
+    assert_eq!(table.get_pot_size(), 0);
+    table.set_ante(4);
+    table.deal().unwrap();
+    assert!(table.is_hand_in_progress());
+    assert_eq!(table.get_pot_size(), 12);
+    table.take_action(HandAction::Fold).unwrap();
+    table.take_action(HandAction::Fold).unwrap();
+    // The hand is over but the next one hasn't been dealt
+    assert!(!table.is_hand_in_progress());
+    assert_eq!(table.hand_number, 1);
+    assert_eq!(table.get_pot_size(), 0);
+    for _ in 0..10 {
+        table.deal().unwrap();
+        assert_eq!(table.ante, 4);
+        table.take_action(HandAction::Fold).unwrap();
+        table.take_action(HandAction::Fold).unwrap();
+    }
+}
+
+#[test]
+pub fn test_remove_and_add_player() {
+    let config = TableConfig {
+        deal_automatically: false,
+        ..TableConfig::default()
+    };
+    let mut table = Table::with_config(4, config.clone()).unwrap();
+    table.deal().unwrap();
+    assert_eq!(table.dealer_button_index, 0);
+    table.take_action(HandAction::Fold).unwrap();
+    table.take_action(HandAction::Fold).unwrap();
+    table.take_action(HandAction::Fold).unwrap();
+    let money: Vec<i32> = table.players.iter().map(|x| x.total_money).collect();
+    // The player after the button leaves, the button stays with the same player
+    let player = table.remove_player(1).unwrap();
+    assert_eq!(player.total_money, money[1]);
+    assert_eq!(table.get_player_count(), 3);
+    assert_eq!(table.dealer_button_index, 0);
+    for (seat, player) in table.players.iter().enumerate() {
+        assert_eq!(player.get_id() as usize, seat);
+    }
+    assert_eq!(table.players[1].total_money, money[2]);
+    // The player joins a different table in the last seat with their stack
+    let mut other_table = Table::with_config(2, config).unwrap();
+    assert_eq!(other_table.add_player(player).unwrap(), 2);
+    assert_eq!(other_table.players[2].get_id(), 2);
+    assert_eq!(other_table.players[2].total_money, money[1]);
+    other_table.deal().unwrap();
+    assert_eq!(other_table.get_active_player_count(), 3);
+    let other_table_money = other_table
+        .players
+        .iter()
+        .map(|x| x.total_money)
+        .sum::<i32>()
+        + other_table.get_pot_size();
+    assert_eq!(other_table_money, DEFAULT_START_MONEY * 2 + money[1]);
+    // Removing the dealer hands the button to the next player on the next deal
+    table.dealer_button_index = 2;
+    table.remove_player(2).unwrap();
+    assert_eq!(table.dealer_button_index, 1);
+    table.deal().unwrap();
+    assert_eq!(table.dealer_button_index, 0);
+}
+
+#[test]
+pub fn test_remove_player_during_hand() {
+    let mut table = Table::new(4).unwrap();
+    assert_eq!(
+        table.remove_player(0).err(),
+        Some(TableError::HandInProgress)
+    );
+    assert_eq!(
+        table.add_player(Player::new(0)),
+        Err(TableError::HandInProgress)
+    );
+}
+
+fn cash_game_config(hand_count: i32) -> TableConfig {
+    TableConfig {
+        cash_game: Some(CashGameConfig {
+            stack_size: 100,
+            ante: 2,
+            hand_count,
+        }),
+        ..TableConfig::default()
+    }
+}
+
+#[test]
+pub fn test_cash_game_tops_up_stacks() {
+    let mut table = Table::with_config(3, cash_game_config(1000)).unwrap();
+    assert_eq!(table.ante, 2);
+    assert_eq!(table.get_pot_size(), 6);
+    // Everyone shoves every hand, so somebody busts and has to rebuy
+    for _ in 0..50 {
+        let hand_number = table.hand_number;
+        while table.hand_number == hand_number {
+            table.take_action(HandAction::Raise(1000)).unwrap();
+        }
+        assert_eq!(table.ante, 2);
+        assert_eq!(table.get_alive_player_count(), 3);
+        for player in &table.players {
+            assert!(player.total_money + table.ante >= 100);
+        }
+    }
+    assert!(table.players.iter().any(|x| x.total_buy_in > 100));
+    // Chips are never created, only bought in for
+    let net_chips: i32 =
+        table.players.iter().map(|x| x.get_net_chips()).sum::<i32>() + table.get_pot_size();
+    assert_eq!(net_chips, 0);
+    assert!(!table.is_game_over());
+}
+
+#[test]
+pub fn test_cash_game_stops_after_hand_count() {
+    let mut table = Table::with_config(4, cash_game_config(5)).unwrap();
+    assert!(!table.is_game_over());
+    while !table.is_game_over() {
+        table.take_action(HandAction::Call).unwrap();
+    }
+    assert_eq!(table.hand_number, 5);
+    assert!(!table.is_hand_in_progress());
+    let results = table.get_results();
+    // A line for each player, then the stats heading and a stats line for each player
+    assert_eq!(results.lines().count(), 9);
+    assert!(results.starts_with("Rank:  1, Net Chips:"));
+    assert!(results.contains("Chips/100 Hands:"));
+    // The ranking follows the net chips
+    let ranked_players = table.get_ranked_players();
+    for pair in ranked_players.windows(2) {
+        assert!(pair[0].1.get_net_chips() >= pair[1].1.get_net_chips());
+    }
+}
+
+#[test]
+pub fn test_cash_game_results_ties() {
+    let mut table = Table::with_config(3, cash_game_config(10)).unwrap();
+    table.players[0].total_money = 110;
+    table.players[1].total_money = 95;
+    table.players[2].total_money = 95;
+    table.hand_number = 10;
+    let ranks: Vec<usize> = table
+        .get_ranked_players()
+        .iter()
+        .map(|(rank, _)| *rank)
+        .collect();
+    assert_eq!(ranks, vec![1, 2, 2]);
+    let results = table.get_results();
+    let first_line = results.lines().next().unwrap();
+    assert!(first_line.contains("Net Chips:     10"));
+    assert!(first_line.contains("Chips/100 Hands:   100.00"));
+}
+
+#[test]
+pub fn test_rebuys_and_add_on() {
+    let config = TableConfig {
+        deal_automatically: false,
+        ante_increases: false,
+        rebuy: Some(RebuyConfig {
+            period_hands: 2,
+            add_on_chips: 100,
+        }),
+        ..TableConfig::default()
+    };
+    let mut table = Table::with_config(3, config).unwrap();
+    // Player 0 busts in the first hand and rebuys on top of what they had left
+    table.players[0].total_money = 0;
+    table.deal().unwrap();
+    assert_eq!(table.players[0].rebuy_count, 1);
+    assert!(table.players[0].is_alive());
+    assert_eq!(table.players[0].total_buy_in, DEFAULT_START_MONEY * 2);
+    assert_eq!(table.players[0].total_money, DEFAULT_START_MONEY - 1);
+    table.take_action(HandAction::Fold).unwrap();
+    table.take_action(HandAction::Fold).unwrap();
+    // Busting again still rebuys while the period lasts
+    table.players[0].total_money = 0;
+    table.deal().unwrap();
+    assert_eq!(table.players[0].rebuy_count, 2);
+    assert_eq!(table.players[0].total_money, DEFAULT_START_MONEY - 1);
+    table.take_action(HandAction::Fold).unwrap();
+    table.take_action(HandAction::Fold).unwrap();
+    // The rebuy period is over, so everyone alive gets the add-on once
+    let money: Vec<i32> = table.players.iter().map(|x| x.total_money).collect();
+    table.deal().unwrap();
+    for (i, player) in table.players.iter().enumerate() {
+        assert_eq!(player.total_money, money[i] + 100 - 1);
+    }
+    table.take_action(HandAction::Fold).unwrap();
+    table.take_action(HandAction::Fold).unwrap();
+    let money: Vec<i32> = table.players.iter().map(|x| x.total_money).collect();
+    // Busting now knocks the player out, and the add-on isn't given again
+    table.players[1].total_money = 0;
+    table.deal().unwrap();
+    assert!(!table.players[1].is_alive());
+    assert_eq!(table.players[1].rebuy_count, 0);
+    assert_eq!(table.players[0].total_money + 1, money[0]);
+    table.take_action(HandAction::Fold).unwrap();
+    table.players[0].total_money = 0;
+    table.deal().unwrap();
+    assert!(table.is_game_over());
+    let results = table.get_results();
+    assert!(results.contains("Rebuys:  2"));
+    assert!(results.contains("Rebuys:  0"));
+    let ranked_players = table.get_ranked_players();
+    assert_eq!(ranked_players[0].1.get_id(), 2);
+    assert_eq!(ranked_players[1].1.get_id(), 0);
+}
+
+#[test]
+pub fn test_knockout_after_everyone_folds() {
+    let config = TableConfig {
+        deal_automatically: false,
+        ante_increases: false,
+        bounty: Some(10),
+        ..TableConfig::default()
+    };
+    let mut table = Table::with_config(3, config).unwrap();
+    table.deal().unwrap();
+    // Player 2 has nothing left after the ante, so losing this pot knocks them out
+    table.players[2].total_money = 0;
+    table.current_player_index = 2;
+    table.take_action(HandAction::Fold).unwrap();
+    table.take_action(HandAction::Fold).unwrap();
+    let winner = table.players.iter().max_by_key(|x| x.total_money).unwrap();
+    let winner_id = winner.get_id();
+    assert_ne!(winner_id, 2);
+    table.deal().unwrap();
+    assert!(!table.players[2].is_alive());
+    assert_eq!(table.players[winner_id as usize].knockout_count, 1);
+    assert_eq!(table.players[winner_id as usize].bounty_winnings, 10);
+    assert_eq!(
+        table.get_knockouts()[0],
+        Knockout {
+            hand_number: 2,
+            eliminated: 2,
+            winners: vec![winner_id],
+        }
+    );
+    // The knockout is part of the action history
+    let actions = get_vec_of_strings_from_actions(&table.round_actions);
+    assert!(actions.contains(&format!("Player 2 was knocked out by: [{winner_id}].")));
+    let results = table.get_results();
+    assert!(results.contains("Knockouts:  1, Bounty Winnings:    10"));
+    assert!(results.contains(&format!(
+        "Hand:    2, Player:  2, Knocked Out By: [{winner_id}]"
+    )));
+}
+
+fn bust_players_on_same_hand(starting_money: [i32; 2]) -> Table {
+    let config = TableConfig {
+        deal_automatically: false,
+        ante_increases: false,
+        ..TableConfig::default()
+    };
+    let mut table = Table::with_config(4, config).unwrap();
+    table.players[2].total_money = starting_money[0];
+    table.players[3].total_money = starting_money[1];
+    table.deal().unwrap();
+    assert_eq!(table.players[2].hand_starting_money, starting_money[0]);
+    assert_eq!(table.players[3].hand_starting_money, starting_money[1]);
+    // Both players lose everything this hand
+    for seat in [2, 3] {
+        table.players[seat].total_money = 0;
+        table.current_player_index = seat;
+        table.take_action(HandAction::Fold).unwrap();
+    }
+    table.take_action(HandAction::Fold).unwrap();
+    table.deal().unwrap();
+    assert_eq!(table.players[2].death_hand_number, Some(2));
+    assert_eq!(table.players[3].death_hand_number, Some(2));
+    table
+}
+
+#[test]
+pub fn test_same_hand_busts_ranked_by_starting_money() {
+    let table = bust_players_on_same_hand([25, 40]);
+    let results = table.get_results();
+    let lines: Vec<&str> = results.lines().collect();
+    assert!(lines[2].starts_with("Rank:  3"));
+    assert!(lines[2].ends_with(&table.players[3].to_string()));
+    assert!(lines[3].starts_with("Rank:  4"));
+    assert!(lines[3].ends_with(&table.players[2].to_string()));
+}
+
+#[test]
+pub fn test_same_hand_busts_with_same_starting_money_tie() {
+    let table = bust_players_on_same_hand([30, 30]);
+    let results = table.get_results();
+    let lines: Vec<&str> = results.lines().collect();
+    assert!(lines[2].starts_with("Rank:  3"));
+    assert!(lines[3].starts_with("Rank:  3"));
+}
+
+#[test]
+pub fn test_split_pot_shares_bounty() {
+    let mut table = deal_test_cards_tied_best();
+    table.config.bounty = Some(11);
+    table.config.deal_automatically = false;
+    table.players[2].total_money = 0;
+    table.resolve_hand().unwrap();
+    table.deal().unwrap();
+    assert!(!table.players[2].is_alive());
+    assert!(table.players[3].is_alive());
+    // Both players that split the pot get credit, the odd chip of the bounty goes to the first
+    assert_eq!(table.get_knockouts()[0].winners, vec![0, 1]);
+    assert_eq!(table.players[0].knockout_count, 1);
+    assert_eq!(table.players[1].knockout_count, 1);
+    assert_eq!(table.players[0].bounty_winnings, 6);
+    assert_eq!(table.players[1].bounty_winnings, 5);
+    assert_eq!(table.players[3].knockout_count, 0);
+}
+
+#[test]
+pub fn test_payouts_when_game_is_over() {
+    let mut table = Table::with_config(
+        4,
+        TableConfig {
+            payouts: Some(PayoutStructure::new(1000.0, vec![50.0, 30.0, 20.0]).unwrap()),
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    // Players 1 and 2 bust on the same hand and share 2nd and 3rd
+    table.players[0].total_money = 2000;
+    table.players[1].death_hand_number = Some(5);
+    table.players[2].death_hand_number = Some(5);
+    table.players[3].death_hand_number = Some(2);
+    let results = table.get_results();
+    let lines: Vec<&str> = results.lines().collect();
+    assert!(lines[0].contains("Prize:    500.00"));
+    assert!(lines[1].contains("Prize:    250.00"));
+    assert!(lines[2].contains("Prize:    250.00"));
+    assert!(lines[3].contains("Prize:      0.00"));
+}
+
+#[test]
+pub fn test_payouts_use_icm_when_stopped_early() {
+    let mut table = Table::with_config(
+        4,
+        TableConfig {
+            payouts: Some(PayoutStructure::new(100.0, vec![50.0, 30.0, 20.0]).unwrap()),
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    table.players[0].total_money = 5000;
+    table.players[1].total_money = 3000;
+    table.players[2].total_money = 2000;
+    table.players[3].death_hand_number = Some(3);
+    let results = table.get_results();
+    let lines: Vec<&str> = results.lines().collect();
+    assert!(lines[0].contains("Equity:     38.39"));
+    assert!(lines[1].contains("Equity:     32.75"));
+    assert!(lines[2].contains("Equity:     28.86"));
+    assert!(lines[3].contains("Equity:      0.00"));
+}
+
+fn make_schedule_table(player_count: usize, schedule: &str) -> Table {
+    Table::with_config(
+        player_count,
+        TableConfig {
+            deal_automatically: false,
+            level_schedule: Some(LevelSchedule::from_json_str(schedule).unwrap()),
+            ..TableConfig::default()
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn test_blinds_are_posted() {
+    let mut table = make_schedule_table(
+        4,
+        r#"{"levels": [{"small_blind": 5, "big_blind": 10, "ante": 1}]}"#,
+    );
+    table.deal().unwrap();
+    assert_eq!(table.dealer_button_index, 0);
+    assert_eq!(table.player_bets, vec![1, 6, 11, 1]);
+    assert_eq!(table.get_largest_active_bet(), 11);
+    // The player after the big blind acts first, and the big blind acts last
+    assert_eq!(table.current_player_index, 3);
+    table.take_action(HandAction::Call).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    assert_eq!(table.table_state, PreFlop);
+    assert_eq!(table.current_player_index, 2);
+    table.take_action(HandAction::Check).unwrap();
+    assert_eq!(table.table_state, Flop);
+    assert_eq!(table.get_pot_size(), 44);
+}
+
+#[test]
+pub fn test_heads_up_button_posts_small_blind() {
+    let mut table = make_schedule_table(2, r#"{"levels": [{"small_blind": 1, "big_blind": 2}]}"#);
+    table.deal().unwrap();
+    let button = table.dealer_button_index;
+    assert_eq!(table.player_bets[button], 1);
+    assert_eq!(table.player_bets[1 - button], 2);
+    // Heads up the button acts first before the flop
+    assert_eq!(table.current_player_index, button);
+}
+
+#[test]
+pub fn test_levels_change_by_hands() {
+    let mut table = make_schedule_table(
+        3,
+        r#"{"levels": [
+            {"ante": 1, "hands": 2},
+            {"small_blind": 2, "big_blind": 4, "ante": 1, "hands": 1},
+            {"small_blind": 5, "big_blind": 10, "ante": 2}
+        ]}"#,
+    );
+    let mut levels = vec![];
+    for _ in 0..5 {
+        table.deal().unwrap();
+        let level = table.get_state_json_for_current_player()["level"].clone();
+        levels.push((
+            level["number"].as_usize().unwrap(),
+            level["hands_left"].as_i32(),
+            table.big_blind,
+            table.ante,
+        ));
+        while table.is_hand_in_progress() {
+            table.take_action(HandAction::Fold).unwrap();
+        }
+    }
+    assert_eq!(
+        levels,
+        vec![
+            (1, Some(2), 0, 1),
+            (1, Some(1), 0, 1),
+            (2, Some(1), 4, 1),
+            (3, None, 10, 2),
+            (3, None, 10, 2),
+        ]
+    );
+}
+
+#[test]
+pub fn test_levels_change_by_time() {
+    let mut table = make_schedule_table(
+        3,
+        r#"{"levels": [{"ante": 1, "seconds": 600}, {"ante": 3, "seconds": 600}, {"ante": 5}]}"#,
+    );
+    table.deal().unwrap();
+    let level = table.get_state_json_for_current_player()["level"].clone();
+    assert_eq!(level["number"], 1);
+    assert!(level["hands_left"].is_null());
+    assert!(level["seconds_left"].as_u64().unwrap() > 590);
+    while table.is_hand_in_progress() {
+        table.take_action(HandAction::Fold).unwrap();
+    }
+    // Pretend the level started long ago
+    table.level_start_time -= std::time::Duration::from_secs(601);
+    table.deal().unwrap();
+    assert_eq!(table.ante, 3);
+    let level = table.get_state_json_for_current_player()["level"].clone();
+    assert_eq!(level["number"], 2);
+    assert!(level["seconds_left"].as_u64().unwrap() > 590);
+}
+
+#[test]
+pub fn test_no_schedule_level_json() {
+    let table = Table::new(3).unwrap();
+    let level = table.get_state_json_for_current_player()["level"].clone();
+    assert!(level["number"].is_null());
+    assert_eq!(level["ante"], 1);
+    assert_eq!(level["small_blind"], 0);
+    assert!(level["seconds_left"].is_null());
+}
+
+#[test]
+pub fn test_scheduled_games_finish() {
+    let schedule =
+        std::fs::read_to_string(concat!(env!("CARGO_MANIFEST_DIR"), "/schedules/turbo.json"))
+            .unwrap();
+    for player_count in [2, 3, 9, 23] {
+        let mut table = Table::with_config(
+            player_count,
+            TableConfig {
+                level_schedule: Some(LevelSchedule::from_json_str(&schedule).unwrap()),
+                ..TableConfig::default()
+            },
+        )
+        .unwrap();
+        let mut rng = thread_rng();
+        while !table.is_game_over() {
+            let action = match rng.gen_range(0..4) {
+                0 => HandAction::Fold,
+                1 => HandAction::Check,
+                2 => HandAction::Call,
+                _ => HandAction::Raise(rng.gen_range(1..100)),
+            };
+            table.take_action(action).unwrap();
+        }
+        assert_eq!(table.get_alive_player_count(), 1);
+    }
+}
+
+#[test]
+pub fn test_hand_limit_ranks_alive_players_by_chips() {
+    let mut table = Table::with_config(
+        5,
+        TableConfig {
+            stop_conditions: StopConditions {
+                max_hands: Some(10),
+                ..StopConditions::default()
+            },
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    while !table.is_game_over() {
+        table.take_action(HandAction::Fold).unwrap();
+    }
+    assert_eq!(table.hand_number, 10);
+    assert!(!table.is_hand_in_progress());
+    assert_eq!(table.get_stop_reason(), Some(StopReason::HandLimit(10)));
+    // Dealing again does nothing once the limit is reached
+    table.deal().unwrap();
+    assert_eq!(table.hand_number, 10);
+    let results = table.get_results();
+    let lines: Vec<&str> = results.lines().collect();
+    assert_eq!(
+        lines[0],
+        "The game reached the limit of 10 hands, players still alive are ranked by their chips."
+    );
+    // The stop reason and a line for each player, then the stats heading and a stats line for each player
+    assert_eq!(lines.len(), 12);
+    // Everyone is alive, so the ranks follow the chip counts and only equal stacks tie
+    let ranked_players = table.get_ranked_players();
+    for pair in ranked_players.windows(2) {
+        assert!(pair[0].1.total_money >= pair[1].1.total_money);
+        assert_eq!(
+            pair[0].0 == pair[1].0,
+            pair[0].1.total_money == pair[1].1.total_money
+        );
+    }
+}
+
+#[test]
+pub fn test_time_limit_finishes_the_hand() {
+    let mut table = Table::with_config(
+        3,
+        TableConfig {
+            stop_conditions: StopConditions {
+                max_duration: Some(std::time::Duration::from_secs(60)),
+                ..StopConditions::default()
+            },
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    assert!(!table.is_game_over());
+    // Pretend the game started long ago, the hand being played still finishes
+    table.start_time -= std::time::Duration::from_secs(61);
+    assert!(!table.is_game_over());
+    table.take_action(HandAction::Fold).unwrap();
+    table.take_action(HandAction::Fold).unwrap();
+    assert!(table.is_game_over());
+    assert_eq!(table.hand_number, 1);
+    assert!(table
+        .get_results()
+        .starts_with("The game reached the time limit of 60 seconds"));
+}
+
+#[test]
+pub fn test_level_cap() {
+    let mut table = Table::with_config(
+        3,
+        TableConfig {
+            level_schedule: Some(
+                LevelSchedule::from_json_str(
+                    r#"{"levels": [{"ante": 1, "hands": 2}, {"ante": 2, "hands": 3}, {"ante": 5}]}"#,
+                )
+                .unwrap(),
+            ),
+            stop_conditions: StopConditions {
+                max_level: Some(2),
+                ..StopConditions::default()
+            },
+            ..TableConfig::default()
+        },
+    ).unwrap();
+    while !table.is_game_over() {
+        table.take_action(HandAction::Fold).unwrap();
+    }
+    // Both levels were played out, and the third was never started
+    assert_eq!(table.hand_number, 5);
+    assert_eq!(table.ante, 2);
+    assert_eq!(table.get_stop_reason(), Some(StopReason::LevelCap(2)));
+    assert!(table
+        .get_results()
+        .starts_with("The game finished level 2, the last level allowed"));
+}
+
+#[test]
+pub fn test_no_stop_reason_when_game_finishes() {
+    let mut table = Table::new(2).unwrap();
+    table.players[1].total_money = 0;
+    table.players[1].death_hand_number = Some(1);
+    assert!(table.is_game_over());
+    assert!(table.get_stop_reason().is_none());
+    assert!(table.get_results().starts_with("Rank:  1"));
+}
+
+fn make_button_rule_table(player_count: usize, button_rule: ButtonRule) -> Table {
+    Table::with_config(
+        player_count,
+        TableConfig {
+            deal_automatically: false,
+            level_schedule: Some(
+                LevelSchedule::from_json_str(
+                    r#"{"levels": [{"small_blind": 5, "big_blind": 10}]}"#,
+                )
+                .unwrap(),
+            ),
+            button_rule,
+            ..TableConfig::default()
+        },
+    )
+    .unwrap()
+}
+
+/// Folds the hand being played, knocks out the given seats, then deals the next hand
+fn knock_out_and_deal(table: &mut Table, seats: &[usize]) {
+    while table.is_hand_in_progress() {
+        table.take_action(HandAction::Fold).unwrap();
+    }
+    for seat in seats {
+        table.players[*seat].total_money = 0;
+    }
+    table.deal().unwrap();
+}
+
+/// Checks where the button is and who posted the blinds, None for a dead small blind
+fn assert_positions(table: &Table, button: usize, small_blind: Option<usize>, big_blind: usize) {
+    assert_eq!(table.dealer_button_index, button);
+    let mut expected_bets = vec![0; table.players.len()];
+    if let Some(small_blind) = small_blind {
+        expected_bets[small_blind] = 5;
+    }
+    expected_bets[big_blind] = 10;
+    assert_eq!(table.player_bets, expected_bets);
+}
+
+#[test]
+pub fn test_button_rules_big_blind_knocked_out() {
+    // The first hand is the same for every rule: button 0, small blind 1, big blind 2
+    for (button_rule, button, small_blind, big_blind) in [
+        // Player 3 is the small blind again and skips the big blind
+        (ButtonRule::NextAlive, 1, Some(3), 4),
+        // The small blind would be the knocked out seat, so it is dead
+        (ButtonRule::DeadButton, 1, None, 3),
+        // Player 1 posts the small blind twice in a row and player 0 keeps the button
+        (ButtonRule::MovingButton, 0, Some(1), 3),
+    ] {
+        let mut table = make_button_rule_table(5, button_rule);
+        table.deal().unwrap();
+        assert_positions(&table, 0, Some(1), 2);
+        knock_out_and_deal(&mut table, &[2]);
+        assert_positions(&table, button, small_blind, big_blind);
+    }
+}
+
+#[test]
+pub fn test_button_rules_small_blind_knocked_out() {
+    for (button_rule, button, small_blind, big_blind) in [
+        (ButtonRule::NextAlive, 2, Some(3), 4),
+        // The button moves onto the knocked out seat
+        (ButtonRule::DeadButton, 1, Some(2), 3),
+        (ButtonRule::MovingButton, 0, Some(2), 3),
+    ] {
+        let mut table = make_button_rule_table(5, button_rule);
+        table.deal().unwrap();
+        knock_out_and_deal(&mut table, &[1]);
+        assert_positions(&table, button, small_blind, big_blind);
+    }
+    // With a dead button the first player after it still acts first after the flop
+    let mut table = make_button_rule_table(5, ButtonRule::DeadButton);
+    table.deal().unwrap();
+    knock_out_and_deal(&mut table, &[1]);
+    for _ in 0..3 {
+        table.take_action(HandAction::Call).unwrap();
+    }
+    table.take_action(HandAction::Check).unwrap();
+    assert_eq!(table.table_state, Flop);
+    assert_eq!(table.current_player_index, 2);
+}
+
+#[test]
+pub fn test_button_rules_knockouts_before_the_button() {
+    // Knocking out the button or the player before it doesn't change who posts the blinds
+    for button_rule in [
+        ButtonRule::NextAlive,
+        ButtonRule::DeadButton,
+        ButtonRule::MovingButton,
+    ] {
+        let mut table = make_button_rule_table(5, button_rule);
+        table.deal().unwrap();
+        knock_out_and_deal(&mut table, &[0, 4]);
+        assert_positions(&table, 1, Some(2), 3);
+        knock_out_and_deal(&mut table, &[]);
+        assert_positions(&table, 2, Some(3), 1);
+    }
+}
+
+#[test]
+pub fn test_button_rules_going_heads_up() {
+    // Heads up the button posts the small blind, player 2 posted the big blind last hand
+    for (button_rule, button, big_blind) in [
+        // Player 2 posts the big blind twice in a row
+        (ButtonRule::NextAlive, 1, 2),
+        (ButtonRule::DeadButton, 2, 1),
+        (ButtonRule::MovingButton, 2, 1),
+    ] {
+        let mut table = make_button_rule_table(3, button_rule);
+        table.deal().unwrap();
+        assert_positions(&table, 0, Some(1), 2);
+        knock_out_and_deal(&mut table, &[0]);
+        assert_positions(&table, button, Some(button), big_blind);
+        // The button acts first before the flop heads up
+        assert_eq!(table.current_player_index, button);
+    }
+}
+
+#[test]
+pub fn test_button_rules_never_skip_the_big_blind() {
+    for button_rule in [ButtonRule::DeadButton, ButtonRule::MovingButton] {
+        let mut table = make_button_rule_table(6, button_rule);
+        table.deal().unwrap();
+        // Knock out one player each hand, on either side of the button
+        for seat in [3, 0, 4] {
+            let next_big_blind = table.get_next_alive_player_index(table.big_blind_index.unwrap());
+            let next_big_blind = if next_big_blind == seat {
+                table.get_next_alive_player_index(next_big_blind)
+            } else {
+                next_big_blind
+            };
+            knock_out_and_deal(&mut table, &[seat]);
+            assert_eq!(table.big_blind_index, Some(next_big_blind));
+            assert_eq!(table.player_bets[next_big_blind], 10);
+        }
+    }
+}
+
+fn make_omaha_table(player_count: usize) -> Table {
+    make_variant_table(player_count, GameVariant::PotLimitOmaha)
+}
+
+fn make_variant_table(player_count: usize, variant: GameVariant) -> Table {
+    Table::with_config(
+        player_count,
+        TableConfig {
+            variant,
+            ..TableConfig::default()
+        },
+    )
+    .unwrap()
+}
+
+#[test]
+pub fn test_omaha_deals_four_cards() {
+    let table = make_omaha_table(11);
+    let mut cards = table.board.clone();
+    for player in &table.players {
+        if let PlayerState::Active(active) = player.player_state {
+            assert_eq!(active.hand.len(), 4);
+            cards.extend(active.hand.iter());
+        } else {
+            panic!("Player wasn't dealt in");
+        }
+    }
+    // Every card comes from the same deck
+    assert_eq!(cards.iter().collect::<HashSet<_>>().len(), 11 * 4 + 5);
+    let json = table.get_state_json_for_current_player();
+    assert_eq!(json["cards"].len(), 4);
+}
+
+#[test]
+pub fn test_omaha_too_many_players() {
+    let config = TableConfig {
+        variant: GameVariant::PotLimitOmaha,
+        ..TableConfig::default()
+    };
+    assert_eq!(
+        Table::with_config(12, config).err(),
+        Some(TableError::TooManyPlayers {
+            players: 12,
+            max: 11
+        })
+    );
+}
+
+#[test]
+pub fn test_omaha_showdown_uses_two_hole_cards() {
+    let mut table = make_omaha_table(2);
+    let cards = |string: &str| -> Vec<Card> { poker::cards!(string).try_collect().unwrap() };
+    let board = cards("Ah Kh Qh 2h 3d");
+    table.board = board.clone();
+    // Player 0 only has one heart so no flush, player 1 makes a straight with 4 and 5
+    for (player, hand) in [("Jh 9s 8s 7c"), ("As 4c 5d 6s")].iter().enumerate() {
+        if let PlayerState::Active(active) = &mut table.players[player].player_state {
+            active.hand = HoleCards::new(&cards(hand));
+        }
+    }
+    let result = table.get_hand_result();
+    assert_eq!(result[0][0].get_id(), 1);
+    assert_eq!(result[1][0].get_id(), 0);
+    // Hold'em would have counted the flush
+    table.config.variant = GameVariant::TexasHoldem;
+    let mut hand = table.players[0];
+    if let PlayerState::Active(active) = &mut hand.player_state {
+        active.hand = HoleCards::new(&active.hand[..2]);
+    }
+    table.players[0] = hand;
+    assert_eq!(table.get_hand_result()[0][0].get_id(), 0);
+}
+
+#[test]
+pub fn test_variant_games_finish() {
+    let mut rng = thread_rng();
+    for variant in [
+        GameVariant::PotLimitOmaha,
+        GameVariant::OmahaHiLo,
+        GameVariant::ShortDeckHoldem,
+        GameVariant::KuhnPoker,
+        GameVariant::LeducHoldem,
+        GameVariant::SevenCardStud,
+    ]
+    .into_iter()
+    .cycle()
+    .take(12)
+    {
+        let mut table = make_variant_table(variant.get_max_players(), variant);
+        for _ in 0..1000000 {
+            if table.is_game_over() {
+                break;
+            }
+            match rng.gen_range(0..4) {
+                0 => table
+                    .take_action(HandAction::Raise(rng.gen_range(1..50)))
+                    .unwrap(),
+                1 => table.take_action(HandAction::Check).unwrap(),
+                2 => table.take_action(HandAction::Call).unwrap(),
+                _ => table.take_action(HandAction::Fold).unwrap(),
+            }
+        }
+        assert!(table.is_game_over());
+        assert!(table.get_results().contains("Rank:  1"));
+    }
+}
+
+fn make_hi_lo_table(board: &str, hands: [&str; 3]) -> Table {
+    let mut table = Table::with_config(
+        3,
+        TableConfig {
+            deal_automatically: false,
+            variant: GameVariant::OmahaHiLo,
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    table.deal().unwrap();
+    let cards = |string: &str| -> Vec<Card> { poker::cards!(string).try_collect().unwrap() };
+    table.board = cards(board);
+    for (player, hand) in hands.iter().enumerate() {
+        if let PlayerState::Active(active) = &mut table.players[player].player_state {
+            active.hand = HoleCards::new(&cards(hand));
+        }
+    }
+    table
+}
+
+/// Checks until the hand is over
+fn check_to_showdown(table: &mut Table) {
+    while table.is_hand_in_progress() {
+        table.take_action(HandAction::Check).unwrap();
+    }
+}
+
+fn get_last_hand_result(table: &Table) -> String {
+    match table.round_actions.last().unwrap() {
+        TableAction::EvaluateHand(result) => result.clone(),
+        _ => panic!("The hand wasn't evaluated"),
+    }
+}
+
+#[test]
+pub fn test_hi_lo_splits_the_pot() {
+    // Player 0 has the best high with kings, player 1 the best low, player 2 neither
+    let mut table = make_hi_lo_table(
+        "Ah 3c 6d Ks Qs",
+        ["Kc Kd 9h 9c", "2d 4s Jc Jd", "Tc Th 8s 8c"],
+    );
+    assert_eq!(table.current_player_index, 1);
+    // A pot sized raise, everyone puts in 4
+    table.take_action(HandAction::Raise(3)).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    check_to_showdown(&mut table);
+    assert_eq!(table.players[0].total_money, DEFAULT_START_MONEY + 2);
+    assert_eq!(table.players[1].total_money, DEFAULT_START_MONEY + 2);
+    assert_eq!(table.players[2].total_money, DEFAULT_START_MONEY - 4);
+    let result = get_last_hand_result(&table);
+    assert!(result.contains("Player 1 has low hand 6-4-3-2-A"));
+    assert!(result.contains("Pot 1 of 12 was split, high won by [0], low won by [1]"));
+}
+
+#[test]
+pub fn test_hi_lo_scoops_without_a_low() {
+    // Only one low card on the board so nobody can make a low
+    let mut table = make_hi_lo_table(
+        "Ks Qs Jd 9c 2h",
+        ["Kc Kd 9h 9s", "2d 4s Jc 3d", "Tc Th 5s 5c"],
+    );
+    table.take_action(HandAction::Raise(3)).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    check_to_showdown(&mut table);
+    assert_eq!(table.players[0].total_money, DEFAULT_START_MONEY + 8);
+    assert!(
+        get_last_hand_result(&table).contains("Pot 1 of 12 was scooped by [0], no low qualified")
+    );
+}
+
+#[test]
+pub fn test_hi_lo_splits_each_side_pot() {
+    let mut table = make_hi_lo_table(
+        "Ah 3c 6d Ks Qs",
+        ["Kc Kd 9h 9c", "2d 4s Jc Jd", "Tc Th 8s 8c"],
+    );
+    // The low hand is all in for 3, so it can only win part of the main pot
+    table.players[1].total_money = 2;
+    table.take_action(HandAction::Raise(2)).unwrap();
+    table.take_action(HandAction::Raise(5)).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    check_to_showdown(&mut table);
+    // The odd chip of the main pot goes to the high hand
+    assert_eq!(
+        table.players[0].total_money,
+        DEFAULT_START_MONEY - 8 + 5 + 10
+    );
+    assert_eq!(table.players[1].total_money, 4);
+    assert_eq!(table.players[2].total_money, DEFAULT_START_MONEY - 8);
+    assert_eq!(table.get_pot_size(), 0);
+    let result = get_last_hand_result(&table);
+    assert!(result.contains("Pot 1 of 9 was split, high won by [0], low won by [1]"));
+    assert!(result.contains("Pot 2 of 10 was scooped by [0], no low qualified"));
+}
+
+#[test]
+pub fn test_short_deck_deals_from_36_cards() {
+    let table = make_variant_table(15, GameVariant::ShortDeckHoldem);
+    let mut cards = table.get_board_cards();
+    for player in &table.players {
+        if let PlayerState::Active(active) = player.player_state {
+            cards.extend(active.hand.iter());
+        }
+    }
+    assert_eq!(cards.iter().collect::<HashSet<_>>().len(), 15 * 2 + 5);
+    assert!(cards.iter().all(|card| card.rank() >= poker::Rank::Six));
+}
+
+#[test]
+pub fn test_short_deck_showdown() {
+    let mut table = make_variant_table(2, GameVariant::ShortDeckHoldem);
+    let cards = |string: &str| -> Vec<Card> { poker::cards!(string).try_collect().unwrap() };
+    table.board = cards("Ah Ad 9h 7h Kc");
+    // Player 0 has a full house and player 1 a flush, which wins in short deck
+    for (player, hand) in ["Ac 9d", "Th 6h"].iter().enumerate() {
+        if let PlayerState::Active(active) = &mut table.players[player].player_state {
+            active.hand = HoleCards::new(&cards(hand));
+        }
+    }
+    assert_eq!(table.get_hand_result()[0][0].get_id(), 1);
+    table.config.variant = GameVariant::TexasHoldem;
+    assert_eq!(table.get_hand_result()[0][0].get_id(), 0);
+}
+
+/// Makes a heads up table of a one card game, the first player to act gets the first hand
+fn make_single_card_table(variant: GameVariant, board: &str, hands: [&str; 2]) -> Table {
+    let mut table = Table::with_config(
+        2,
+        TableConfig {
+            deal_automatically: false,
+            variant,
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    table.deal().unwrap();
+    let cards = |string: &str| -> Vec<Card> { poker::cards!(string).try_collect().unwrap() };
+    table.board = cards(board);
+    let first = table.current_player_index;
+    for (offset, hand) in hands.iter().enumerate() {
+        let player = (first + offset) % 2;
+        if let PlayerState::Active(active) = &mut table.players[player].player_state {
+            active.hand = HoleCards::new(&cards(hand));
+        }
+    }
+    table
+}
+
+#[test]
+pub fn test_kuhn_poker_hand() {
+    let mut table = make_single_card_table(GameVariant::KuhnPoker, "", ["Ks", "Qs"]);
+    let first = table.current_player_index;
+    let json = table.get_state_json_for_current_player();
+    assert_eq!(json["cards"].len(), 1);
+    assert_eq!(json["flop"].to_string(), "[\"None\"]");
+    assert_eq!(json["river"], "None");
+    // Any raise is one bet, and with the one bet made a raise is a call
+    table.take_action(HandAction::Raise(100)).unwrap();
+    table.take_action(HandAction::Raise(100)).unwrap();
+    // The only betting round ends in a showdown
+    assert!(!table.is_hand_in_progress());
+    assert!(!table
+        .round_actions
+        .contains(&TableAction::AdvanceToStage(Flop)));
+    assert!(table.round_actions.contains(&TableAction::TakePlayerAction(
+        1 - first as i8,
+        HandAction::Call
+    )));
+    assert_eq!(table.players[first].total_money, DEFAULT_START_MONEY + 2);
+    assert_eq!(
+        table.players[1 - first].total_money,
+        DEFAULT_START_MONEY - 2
+    );
+}
+
+#[test]
+pub fn test_leduc_holdem_hand() {
+    let mut table = make_single_card_table(GameVariant::LeducHoldem, "Jh", ["Js", "Ks"]);
+    let first = table.current_player_index;
+    assert_eq!(
+        table.get_state_json_for_current_player()["flop"].to_string(),
+        "[\"Hidden\"]"
+    );
+    // Bets are 2 in the first round, and after a bet and a raise the next raise is a call
+    table.take_action(HandAction::Raise(50)).unwrap();
+    table.take_action(HandAction::Raise(1)).unwrap();
+    table.take_action(HandAction::Raise(9)).unwrap();
+    assert_eq!(table.get_pot_size(), 10);
+    assert_eq!(table.table_state, Flop);
+    let json = table.get_state_json_for_current_player();
+    assert_eq!(json["flop"].len(), 1);
+    assert_eq!(json["flop"][0], table.board[0].to_string());
+    assert_eq!(json["turn"], "None");
+    // Bets are 4 in the second and last round
+    table.take_action(HandAction::Raise(1)).unwrap();
+    assert_eq!(table.get_pot_size(), 14);
+    table.take_action(HandAction::Call).unwrap();
+    assert!(!table.is_hand_in_progress());
+    assert!(!table
+        .round_actions
+        .contains(&TableAction::AdvanceToStage(Turn)));
+    // The jack pairs the board and beats the king
+    assert_eq!(table.players[first].total_money, DEFAULT_START_MONEY + 9);
+    assert_eq!(
+        table.players[1 - first].total_money,
+        DEFAULT_START_MONEY - 9
+    );
+}
+
+#[test]
+pub fn test_fixed_limit_ante_never_increases() {
+    for variant in [GameVariant::KuhnPoker, GameVariant::LeducHoldem] {
+        let mut table = Table::with_config(
+            2,
+            TableConfig {
+                variant,
+                ..TableConfig::default()
+            },
+        )
+        .unwrap();
+        // Each fold ends a hand, far past the hands after which other games raise the ante
+        for _ in 0..50 {
+            table.take_action(HandAction::Fold).unwrap();
+            assert_eq!(table.get_ante(), 1, "{variant:?}");
+        }
+        assert_eq!(table.get_hand_number(), 51);
+    }
+}
+
+fn make_stud_table(player_count: usize) -> Table {
+    let mut table = Table::with_config(
+        player_count,
+        TableConfig {
+            deal_automatically: false,
+            variant: GameVariant::SevenCardStud,
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    table.deal().unwrap();
+    table
+}
+
+#[test]
+pub fn test_stud_deal_and_bring_in() {
+    let table = make_stud_table(7);
+    assert_eq!(table.table_state, BetStage::ThirdStreet);
+    // The lowest up card brings it in for the ante
+    let up_cards: Vec<Card> = table
+        .players
+        .iter()
+        .map(|player| match player.player_state {
+            PlayerState::Active(active) => active.hand[2],
+            PlayerState::Folded => panic!("Player wasn't dealt in"),
+        })
+        .collect();
+    let bring_in_seat = (0..7)
+        .min_by_key(|seat| get_bring_in_rank(up_cards[*seat]))
+        .unwrap();
+    for (seat, player) in table.players.iter().enumerate() {
+        let expected_bet = if seat == bring_in_seat { 2 } else { 1 };
+        assert_eq!(player.player_state.get_bet(), Some(expected_bet));
+    }
+    assert_eq!(table.current_player_index, (bring_in_seat + 1) % 7);
+    // Players only see their own cards dealt so far, and everyone's up cards
+    let json = table.get_state_json_for_current_player();
+    assert_eq!(json["cards"].len(), 3);
+    assert_eq!(json["flop"].to_string(), "[\"None\"]");
+    for (seat, up_card) in up_cards.iter().enumerate() {
+        let details = &json["players"][seat]["player_state"]["details"];
+        assert_eq!(details["up_cards"].len(), 1);
+        assert_eq!(details["up_cards"][0], up_card.to_string());
+        assert!(details["hand"].is_null());
+    }
+}
+
+#[test]
+pub fn test_stud_streets() {
+    let mut table = make_stud_table(3);
+    let cards = |string: &str| -> Vec<Card> { poker::cards!(string).try_collect().unwrap() };
+    // Player 0 shows a pair of kings, player 1 has aces and player 2 sixes
+    let hands = [
+        "2c 3d Kh Ks 4c 5d 7h",
+        "Ac 8d Ah 9s Tc Jd 2h",
+        "3c 4d Qh Js 6c 6d 8h",
+    ];
+    for (player, hand) in hands.iter().enumerate() {
+        if let PlayerState::Active(active) = &mut table.players[player].player_state {
+            active.hand = HoleCards::new(&cards(hand));
+        }
+    }
+    // Everyone calls the bring-in
+    while table.table_state == BetStage::ThirdStreet {
+        table.take_action(HandAction::Call).unwrap();
+    }
+    assert_eq!(table.table_state, BetStage::FourthStreet);
+    assert_eq!(
+        *table.round_actions.last().unwrap(),
+        TableAction::AdvanceToStage(BetStage::FourthStreet)
+    );
+    // The best hand showing acts first
+    assert_eq!(table.current_player_index, 0);
+    let json = table.get_state_json_for_current_player();
+    assert_eq!(json["cards"].len(), 4);
+    assert_eq!(
+        json["players"][1]["player_state"]["details"]["up_cards"].to_string(),
+        HoleCards::new(&cards("Ah 9s")).as_json().to_string()
+    );
+    check_to_showdown(&mut table);
+    let stages: Vec<&TableAction> = table
+        .round_actions
+        .iter()
+        .filter(|action| matches!(action, TableAction::AdvanceToStage(_)))
+        .collect();
+    assert_eq!(stages.len(), 4);
+    assert_eq!(table.players[1].total_money, DEFAULT_START_MONEY + 4);
+    assert_eq!(table.players[0].total_money, DEFAULT_START_MONEY - 2);
+    assert_eq!(table.players[2].total_money, DEFAULT_START_MONEY - 2);
+}
+
+fn make_run_it_table(player_count: usize, run_it_times: usize) -> Table {
+    let mut table = Table::with_config(
+        player_count,
+        TableConfig {
+            deal_automatically: false,
+            run_it_times,
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    table.deal().unwrap();
+    table
+}
+
+/// Gives the players these hands in place of the dealt ones, which go back in the deck
+fn set_hands(table: &mut Table, hands: &[&str]) {
+    let cards = |string: &str| -> Vec<Card> { poker::cards!(string).try_collect().unwrap() };
+    table.dealt_hole_cards.clear();
+    for (player, hand) in hands.iter().enumerate() {
+        if let PlayerState::Active(active) = &mut table.players[player].player_state {
+            active.hand = HoleCards::new(&cards(hand));
+            table.dealt_hole_cards.extend(cards(hand));
+        }
+    }
+}
+
+#[test]
+pub fn test_run_it_twice_on_the_turn() {
+    let mut table = make_run_it_table(2, 2);
+    let cards = |string: &str| -> Vec<Card> { poker::cards!(string).try_collect().unwrap() };
+    // Player 0 has four aces by the turn, so no river can save player 1
+    table.board = cards("As Ad Ah 2c 9d");
+    set_hands(&mut table, &["Ac Kd", "3s 4s"]);
+    for player in &mut table.players {
+        player.total_money = 2;
+    }
+    check_to_stage(&mut table, Turn);
+    table.take_action(HandAction::Raise(2)).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    assert!(!table.is_hand_in_progress());
+    let result = get_last_hand_result(&table);
+    assert!(result.contains("The board was run 2 times."));
+    assert!(result.contains("Run 1:"));
+    assert!(result.contains("Run 2:"));
+    assert!(result.contains("Player 0 won 6"));
+    assert_eq!(table.players[0].total_money, 6);
+    assert_eq!(table.players[1].total_money, 0);
+    // The first run keeps the board that was dealt
+    assert_eq!(table.board, cards("As Ad Ah 2c 9d"));
+}
+
+#[test]
+pub fn test_run_it_once_by_default() {
+    let mut table = make_run_it_table(2, 1);
+    for player in &mut table.players {
+        player.total_money = 2;
+    }
+    table.take_action(HandAction::Raise(2)).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    assert_eq!(table.all_in_stage, Some(PreFlop));
+    assert!(!get_last_hand_result(&table).contains("The board was run"));
+}
+
+#[test]
+pub fn test_runs_skip_folded_cards() {
+    let mut table = make_run_it_table(3, 9);
+    let folded_seat = table.current_player_index;
+    let folded_cards = match table.players[folded_seat].player_state {
+        PlayerState::Active(active) => active.hand.to_vec(),
+        PlayerState::Folded => panic!("Player {folded_seat} wasn't dealt in"),
+    };
+    table.take_action(HandAction::Fold).unwrap();
+    table.all_in_stage = Some(PreFlop);
+    // Every card left is dealt into the 8 extra runs but for one, and none of them is a folded card
+    let boards = table.get_run_out_boards();
+    assert_eq!(boards.len(), 9);
+    for board in &boards[1..] {
+        assert!(board.iter().all(|card| !folded_cards.contains(card)));
+    }
+}
+
+#[test]
+pub fn test_runs_split_each_pot() {
+    let mut table = make_run_it_table(3, 2);
+    let cards = |string: &str| -> Vec<Card> { poker::cards!(string).try_collect().unwrap() };
+    set_hands(&mut table, &["As Ad", "Ks Kd", "2c 7d"]);
+    // Player 0 wins the first run and player 1 the second with a set of kings
+    let boards = vec![cards("3h 5c 8s Jd Qc"), cards("3h 5c 8s Jd Kc")];
+    table.pot_winners_by_seat = vec![vec![]; 3];
+    table.showdown_each_board(boards).unwrap();
+    // The 3 chips in the pot are worth 1.5 to each winner, and the odd chip goes to the first of them
+    assert_eq!(table.players[0].total_money, DEFAULT_START_MONEY + 1);
+    assert_eq!(table.players[1].total_money, DEFAULT_START_MONEY);
+    assert_eq!(table.players[2].total_money, DEFAULT_START_MONEY - 1);
+    assert_eq!(table.board, cards("3h 5c 8s Jd Qc"));
+}
+
+#[test]
+pub fn test_all_in_adjusted_results() {
+    let mut table = Table::with_config(
+        2,
+        TableConfig {
+            deal_automatically: false,
+            all_in_adjusted: true,
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    table.deal().unwrap();
+    // Kings hit a set on the turn after getting all in on the flop with 83 of 990 boards winning
+    table.board = poker::cards!("2c 7d 9h Kc 3s").try_collect().unwrap();
+    set_hands(&mut table, &["As Ad", "Ks Kd"]);
+    for player in &mut table.players {
+        player.total_money = 2;
+    }
+    check_to_stage(&mut table, Flop);
+    table.take_action(HandAction::Raise(2)).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    assert_eq!(table.players[0].total_money, 0);
+    assert_eq!(table.players[1].total_money, 6);
+    let expected = 6.0 * 907.0 / 990.0;
+    assert!((table.all_in_adjustments[0] - expected).abs() < 1e-6);
+    assert!((table.all_in_adjustments[1] + expected).abs() < 1e-6);
+    let result = get_last_hand_result(&table);
+    assert!(result.contains("Player 0 had 91.62% equity, expected to win 5.50 and won 0"));
+    assert!(result.contains("Player 1 had 8.38% equity, expected to win 0.50 and won 6"));
+    // Both players lost or won the same chips on average
+    let net_chips = table.players[0].get_net_chips() as f64;
+    assert!(
+        (table.get_all_in_adjusted_net_chips(&table.players[0]) - net_chips - expected).abs()
+            < 1e-6
+    );
+    assert!(table.get_results().contains("All-In Adjusted:"));
+}
+
+/// Checks until the table reaches the betting stage
+fn check_to_stage(table: &mut Table, stage: BetStage) {
+    while table.table_state != stage {
+        table.take_action(HandAction::Check).unwrap();
+    }
+}
+
+#[test]
+pub fn test_player_stats_are_recorded() {
+    let mut table = Table::with_config(
+        2,
+        TableConfig {
+            deal_automatically: false,
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    // The first player to act raises and the other folds
+    table.deal().unwrap();
+    let raiser = table.get_current_player_index();
+    let folder = 1 - raiser;
+    table.take_action(HandAction::Raise(10)).unwrap();
+    table.take_action(HandAction::Fold).unwrap();
+    assert_eq!(table.player_stats[raiser].pfr_hands, 1);
+    assert_eq!(table.player_stats[raiser].vpip_hands, 1);
+    assert_eq!(table.player_stats[folder].hands, 1);
+    assert_eq!(table.player_stats[folder].vpip_hands, 0);
+    assert_eq!(table.player_stats[folder].showdowns, 0);
+    // Both players check the next hand down, only the antes are in so nobody put chips in voluntarily
+    table.deal().unwrap();
+    table.board = poker::cards!("2c 7d 9h Kc 3s").try_collect().unwrap();
+    set_hands(&mut table, &["As Ad", "4s 5d"]);
+    while table.is_hand_in_progress() {
+        table.take_action(HandAction::Check).unwrap();
+    }
+    for stats in &table.player_stats {
+        assert_eq!(stats.hands, 2);
+        assert_eq!(stats.saw_flop_hands, 1);
+        assert_eq!(stats.showdowns, 1);
+    }
+    assert_eq!(table.player_stats[folder].vpip_hands, 0);
+    assert_eq!(table.player_stats[0].showdown_wins, 1);
+    assert_eq!(table.player_stats[1].showdown_wins, 0);
+    let json = table.get_player_stats_json();
+    assert_eq!(json[0]["id"], 0);
+    assert_eq!(json[0]["won_at_showdown"], 100.0);
+    assert_eq!(json[1]["won_at_showdown"], 0.0);
+    let results = table.get_results();
+    assert!(results.contains("Player Stats:\nPlayer:  0, Hands:     2, VPIP:"));
+}
+
+#[test]
+pub fn test_hand_history() {
+    let mut table = Table::with_config(
+        2,
+        TableConfig {
+            deal_automatically: false,
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    table.deal().unwrap();
+    table.board = poker::cards!("2c 7d 9h Kc 3s").try_collect().unwrap();
+    set_hands(&mut table, &["As Ad", "4s 5d"]);
+    let bettor = table.get_current_player_index();
+    // Bets are limited to the pot of the two antes
+    table.take_action(HandAction::Raise(2)).unwrap();
+    table.take_action(HandAction::Call).unwrap();
+    while table.is_hand_in_progress() {
+        table.take_action(HandAction::Check).unwrap();
+    }
+    let history = table.hand_history.as_ref().unwrap().to_string();
+    assert!(history.starts_with("PokerStars Hand #1: Hold'em Pot Limit (0/0) - "));
+    assert!(history.contains("Seat 1: Player 0 (500 in chips)\n"));
+    assert!(history
+        .contains("Player 0: posts the ante 1\nPlayer 1: posts the ante 1\n*** HOLE CARDS ***\n"));
+    assert!(history.contains(&format!(
+        "Player {bettor}: bets 2\nPlayer {}: calls 2\n",
+        1 - bettor
+    )));
+    assert!(history.contains("*** FLOP *** [2c 7d 9h]\n"));
+    assert!(history.contains("*** TURN *** [2c 7d 9h] [Kc]\n"));
+    assert!(history.contains("*** RIVER *** [2c 7d 9h Kc] [3s]\n"));
+    assert!(
+        history.contains("*** SHOW DOWN ***\nPlayer 0: shows [As Ad]\nPlayer 1: shows [4s 5d]\n")
+    );
+    assert!(
+        history.contains("Player 0 collected 6 from pot\n*** SUMMARY ***\nTotal pot 6 | Rake 0\n")
+    );
+    assert!(history.contains("Board [2c 7d 9h Kc 3s]\n"));
+    assert!(history.contains("Seat 1: Player 0 (button) showed [As Ad] and won (6)\n"));
+    assert!(history.ends_with("Seat 2: Player 1 showed [4s 5d] and lost"));
+}
+
+#[test]
+pub fn test_hand_history_file() {
+    let hand_history_file = std::env::temp_dir().join("bot_arena_test_hand_history.txt");
+    let _ = std::fs::remove_file(&hand_history_file);
+    let mut table = Table::with_config(
+        3,
+        TableConfig {
+            deal_automatically: false,
+            hand_history_file: Some(hand_history_file.clone()),
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    for _ in 0..3 {
+        table.deal().unwrap();
+        while table.is_hand_in_progress() {
+            table.take_action(HandAction::Fold).unwrap();
+        }
+    }
+    let histories = std::fs::read_to_string(&hand_history_file).unwrap();
+    std::fs::remove_file(&hand_history_file).unwrap();
+    assert_eq!(histories.matches("PokerStars Hand #").count(), 3);
+    assert_eq!(histories.matches("*** SUMMARY ***").count(), 3);
+    assert!(histories.contains("PokerStars Hand #3: "));
+    // Everyone folds to the last player, who collects the antes
+    assert!(histories.contains(" collected 3 from pot\n"));
+    assert!(histories.contains("folded before Flop"));
+}
+
+#[test]
+pub fn test_snapshot_restores_an_identical_table() {
+    for variant in [GameVariant::TexasHoldem, GameVariant::SevenCardStud] {
+        let mut table = Table::with_config(
+            4,
+            TableConfig {
+                variant,
+                all_in_adjusted: true,
+                ..TableConfig::default()
+            },
+        )
+        .unwrap();
+        // Play into the middle of a hand so there are cards, bets and actions to save
+        for action in [HandAction::Call, HandAction::Raise(5), HandAction::Call] {
+            table.take_action(action).unwrap();
+        }
+        let snapshot = serde_json::to_string(&table).unwrap();
+        let mut restored: Table = serde_json::from_str(&snapshot).unwrap();
+        // Both tables deal the same cards and end the same way from here on
+        while !table.is_game_over() {
+            assert_eq!(
+                restored.get_state_string_for_current_player(),
+                table.get_state_string_for_current_player()
+            );
+            let action = match thread_rng().gen_range(0..3) {
+                0 => HandAction::Fold,
+                1 => HandAction::Call,
+                _ => HandAction::Raise(thread_rng().gen_range(1..30)),
+            };
+            table.take_action(action).unwrap();
+            restored.take_action(action).unwrap();
+        }
+        assert!(restored.is_game_over());
+        assert_eq!(restored.get_results(), table.get_results());
+    }
+}
+
+#[test]
+pub fn test_game_is_written_to_the_database() {
+    let database_file = std::env::temp_dir().join("bot_arena_test_table_storage.db");
+    let _ = std::fs::remove_file(&database_file);
+    let mut table = Table::with_config(
+        3,
+        TableConfig {
+            database_file: Some(database_file.clone()),
+            ..TableConfig::default()
+        },
+    )
+    .unwrap();
+    while !table.is_game_over() {
+        let action = match thread_rng().gen_range(0..3) {
+            0 => HandAction::Fold,
+            1 => HandAction::Call,
+            _ => HandAction::Raise(thread_rng().gen_range(1..600)),
+        };
+        table.take_action(action).unwrap();
+    }
+    let connection = rusqlite::Connection::open(&database_file).unwrap();
+    let count = |query: &str| -> i64 { connection.query_row(query, [], |row| row.get(0)).unwrap() };
+    assert_eq!(
+        count("SELECT COUNT(*) FROM games WHERE results IS NOT NULL"),
+        1
+    );
+    assert_eq!(
+        count("SELECT COUNT(*) FROM entrants WHERE place IS NOT NULL"),
+        3
+    );
+    assert_eq!(count("SELECT COUNT(*) FROM entrants WHERE place = 1"), 1);
+    // Every hand dealt was stored with its deal and how it was evaluated
+    let hands = count("SELECT COUNT(*) FROM hands");
+    assert!(hands > 0);
+    assert_eq!(
+        count("SELECT COUNT(*) FROM hands WHERE pot IS NOT NULL"),
+        hands
+    );
+    assert_eq!(
+        count("SELECT COUNT(*) FROM actions WHERE action = 'deal'"),
+        hands
+    );
+    assert_eq!(
+        count("SELECT COUNT(*) FROM actions WHERE action = 'evaluate'"),
+        hands
+    );
+    assert_eq!(
+        count("SELECT COUNT(*) FROM actions WHERE action = 'eliminate'"),
+        2
+    );
+    // Every showdown was of hands with two hole cards, and someone won each one
+    assert_eq!(
+        count("SELECT COUNT(*) FROM showdowns WHERE length(hole_cards) != 5"),
+        0
+    );
+    assert_eq!(
+        count("SELECT COUNT(DISTINCT hand_id) FROM showdowns WHERE won = 1",),
+        count("SELECT COUNT(DISTINCT hand_id) FROM showdowns"),
+    );
+    // Hands that went to showdown had a full board
+    assert_eq!(
+        count(
+            "SELECT COUNT(*) FROM (SELECT hand_id, COUNT(*) AS cards FROM board_cards
+             WHERE hand_id IN (SELECT hand_id FROM showdowns) GROUP BY hand_id, board) WHERE cards != 5",
+        ),
+        0
+    );
+    // The kind of question the database is for, hands a player shoved before the flop and lost
+    count(
+        "SELECT COUNT(*) FROM actions JOIN showdowns USING (hand_id, seat)
+         WHERE actions.stage = 'pre flop' AND actions.is_all_in AND showdowns.net_chips < 0",
+    );
+    drop(connection);
+    std::fs::remove_file(&database_file).unwrap();
+}
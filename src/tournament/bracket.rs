@@ -0,0 +1,333 @@
+use std::cmp::Reverse;
+use std::time::Duration;
+
+use tokio::net::TcpStream;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::engine::send_results;
+use crate::table::{Table, TableConfig, TableError};
+use crate::tournament::{play_tables_concurrently, seat_at_fewest_tables};
+
+/// Where an entrant sat during one stage of the tournament
+#[derive(Copy, Clone, Debug, Eq, PartialEq)]
+pub struct Seat {
+    pub stage: usize,
+    pub table: usize,
+    pub seat: usize,
+}
+
+/// Everything known about one entrant's run through the tournament
+#[derive(Clone, Debug, Default)]
+pub struct EntrantProgress {
+    /// Every seat the entrant has had, one per stage they played
+    pub seats: Vec<Seat>,
+    /// The rank the entrant finished with at their table in the last stage they played
+    pub last_table_rank: Option<usize>,
+    /// The stage the entrant was knocked out in, None if they are still playing or won
+    pub eliminated_stage: Option<usize>,
+}
+
+/// Splits entrants across qualifying tables, the top finishers of each table advance to the
+/// next stage until everyone left fits at one final table.
+pub struct BracketTournament {
+    /// The most entrants that may sit at one table
+    max_table_size: usize,
+    /// How many entrants from each qualifying table move on to the next stage
+    advance_per_table: usize,
+    /// The current stage, 0 INDEXED
+    stage: usize,
+    /// For the current stage, the entrant sitting at each seat of each table
+    stage_seating: Vec<Vec<usize>>,
+    /// The progress of every entrant, indexed by entrant
+    entrants: Vec<EntrantProgress>,
+    /// True once the final table has been played
+    is_finished: bool,
+}
+
+impl BracketTournament {
+    /// Makes a tournament and seats all the entrants for the first stage
+    pub fn new(
+        entrant_count: usize,
+        max_table_size: usize,
+        advance_per_table: usize,
+    ) -> Result<Self, String> {
+        if entrant_count == 0 {
+            return Err("No entrants to seat.".to_string());
+        }
+        if !(2..=Table::MAX_PLAYERS).contains(&max_table_size) {
+            return Err(format!(
+                "The table size must be between 2 and {}",
+                Table::MAX_PLAYERS
+            ));
+        }
+        // Less than half of each table advancing guarantees every stage shrinks the field
+        if advance_per_table == 0 || advance_per_table * 2 >= max_table_size {
+            return Err(
+                "At least one, but less than half, of each table's players must advance"
+                    .to_string(),
+            );
+        }
+        let mut tournament = BracketTournament {
+            max_table_size,
+            advance_per_table,
+            stage: 0,
+            stage_seating: vec![],
+            entrants: vec![EntrantProgress::default(); entrant_count],
+            is_finished: false,
+        };
+        tournament.seat_stage((0..entrant_count).collect());
+        Ok(tournament)
+    }
+
+    /// Seats the given entrants at as few tables as possible for the current stage
+    fn seat_stage(&mut self, entrants: Vec<usize>) {
+        let seating = seat_at_fewest_tables(entrants, self.max_table_size);
+        for (table, seats) in seating.iter().enumerate() {
+            for (seat, &entrant) in seats.iter().enumerate() {
+                self.entrants[entrant].seats.push(Seat {
+                    stage: self.stage,
+                    table,
+                    seat,
+                });
+            }
+        }
+        self.stage_seating = seating;
+    }
+
+    #[cfg(test)]
+    pub fn get_stage(&self) -> usize {
+        self.stage
+    }
+
+    #[cfg(test)]
+    pub fn get_stage_seating(&self) -> &[Vec<usize>] {
+        &self.stage_seating
+    }
+
+    #[cfg(test)]
+    pub fn get_entrants(&self) -> &[EntrantProgress] {
+        &self.entrants
+    }
+
+    /// Returns true if everyone left is playing at one table
+    pub fn is_final_stage(&self) -> bool {
+        self.stage_seating.len() == 1
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.is_finished
+    }
+
+    /// Makes the tables for the current stage, qualifying tables stop once only the advancing players are left
+    pub fn make_stage_tables(&self) -> Result<Vec<Table>, TableError> {
+        let finish_player_count = if self.is_final_stage() {
+            1
+        } else {
+            self.advance_per_table
+        };
+        self.stage_seating
+            .iter()
+            .map(|seats| {
+                Table::with_config(
+                    seats.len(),
+                    TableConfig {
+                        finish_player_count,
+                        ..TableConfig::default()
+                    },
+                )
+            })
+            .collect()
+    }
+
+    /// Records the results of the finished stage tables and seats the players that advance for the next stage
+    pub fn record_stage_results(&mut self, tables: &[Table]) {
+        let is_final_stage = self.is_final_stage();
+        let mut advancing = vec![];
+        for (seats, table) in self.stage_seating.iter().zip(tables) {
+            for (rank, player) in table.get_ranked_players() {
+                let entrant = seats[player.get_id() as usize];
+                let progress = &mut self.entrants[entrant];
+                progress.last_table_rank = Some(rank);
+                if !player.is_alive() {
+                    progress.eliminated_stage = Some(self.stage);
+                } else if !is_final_stage {
+                    advancing.push(entrant);
+                }
+            }
+        }
+        if is_final_stage {
+            self.is_finished = true;
+            return;
+        }
+        advancing.sort();
+        self.stage += 1;
+        self.seat_stage(advancing);
+    }
+
+    /// Gets every entrant with their overall place, best first. Lasting more stages places higher,
+    /// and entrants knocked out in the same stage are placed by their table rank.
+    pub fn get_standings(&self) -> Vec<(usize, usize)> {
+        let standing_key = |entrant: &usize| {
+            let progress = &self.entrants[*entrant];
+            (
+                Reverse(progress.eliminated_stage.unwrap_or(usize::MAX)),
+                progress.last_table_rank,
+            )
+        };
+        let mut entrants: Vec<usize> = (0..self.entrants.len()).collect();
+        entrants.sort_by_key(standing_key);
+        let mut place = 1;
+        let mut standings = vec![];
+        for (i, entrant) in entrants.iter().enumerate() {
+            // The entrants didn't tie, so increase the place
+            if i > 0 && standing_key(entrant) != standing_key(&entrants[i - 1]) {
+                place = i + 1;
+            }
+            standings.push((place, *entrant));
+        }
+        standings
+    }
+
+    pub fn get_results(&self) -> String {
+        let mut result_string = String::new();
+        for (place, entrant) in self.get_standings() {
+            let progress = &self.entrants[entrant];
+            let eliminated_stage = progress
+                .eliminated_stage
+                .map_or("None".to_string(), |stage| stage.to_string());
+            let seats: Vec<String> = progress
+                .seats
+                .iter()
+                .map(|seat| format!("{}/{}/{}", seat.stage, seat.table, seat.seat))
+                .collect();
+            result_string += format!(
+                "Place:{place:>3}, Entrant:{entrant:>4}, Eliminated Stage:{eliminated_stage:>5}, Seats (stage/table/seat): {}\n",
+                seats.join(" ")
+            )
+            .as_str();
+        }
+        result_string
+    }
+
+    /// Plays every stage of the tournament, entrant i is the connection at index i
+    pub async fn play(
+        &mut self,
+        connections: &mut [WebSocketStream<TcpStream>],
+        read_timeout: Duration,
+    ) -> String {
+        while !self.is_finished() {
+            info!(
+                "Starting stage {} with {} tables",
+                self.stage,
+                self.stage_seating.len()
+            );
+            let mut tables = match self.make_stage_tables() {
+                Ok(tables) => tables,
+                Err(error) => {
+                    error!(
+                        "Couldn't make the tables for stage {}, ending the tournament: {error}",
+                        self.stage
+                    );
+                    break;
+                }
+            };
+            play_tables_concurrently(&mut tables, &self.stage_seating, connections, read_timeout)
+                .await;
+            self.record_stage_results(&tables);
+        }
+        let results = format!("Tournament is over:\n{}", self.get_results());
+        println!("{}", results);
+        send_results(connections, &results).await;
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::HashSet;
+
+    use crate::actions::HandAction;
+    use crate::table::Table;
+    use crate::tournament::bracket::BracketTournament;
+
+    fn play_stage_with_calls(tables: &mut [Table]) {
+        for table in tables {
+            while !table.is_game_over() {
+                table.take_action(HandAction::Call).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn test_bad_settings() {
+        assert!(BracketTournament::new(0, 10, 3).is_err());
+        assert!(BracketTournament::new(30, 1, 0).is_err());
+        assert!(BracketTournament::new(30, Table::MAX_PLAYERS + 1, 3).is_err());
+        assert!(BracketTournament::new(30, 10, 0).is_err());
+        assert!(BracketTournament::new(30, 10, 5).is_err());
+        assert!(BracketTournament::new(30, 10, 4).is_ok());
+    }
+
+    #[test]
+    fn test_first_stage_seating() {
+        let tournament = BracketTournament::new(50, 23, 3).unwrap();
+        let seating = tournament.get_stage_seating();
+        assert_eq!(seating.len(), 3);
+        let sizes: Vec<usize> = seating.iter().map(|x| x.len()).collect();
+        assert_eq!(sizes, vec![17, 17, 16]);
+        // Everyone is seated exactly once
+        let seated: HashSet<usize> = seating.iter().flatten().copied().collect();
+        assert_eq!(seated.len(), 50);
+        for entrant in tournament.get_entrants() {
+            assert_eq!(entrant.seats.len(), 1);
+        }
+        assert!(!tournament.is_final_stage());
+    }
+
+    #[test]
+    fn test_small_field_is_final_table() {
+        let tournament = BracketTournament::new(6, 23, 3).unwrap();
+        assert!(tournament.is_final_stage());
+        let tables = tournament.make_stage_tables().unwrap();
+        assert_eq!(tables.len(), 1);
+        assert_eq!(tables[0].get_player_count(), 6);
+    }
+
+    #[test]
+    fn test_full_tournament() {
+        const ENTRANTS: usize = 40;
+        const ADVANCE: usize = 2;
+        let mut tournament = BracketTournament::new(ENTRANTS, 8, ADVANCE).unwrap();
+        // 40 entrants -> 5 tables of 8 -> at most 10 advance -> at most 2 tables of 5 -> final table
+        assert_eq!(tournament.get_stage_seating().len(), 5);
+        while !tournament.is_finished() {
+            let mut tables = tournament.make_stage_tables().unwrap();
+            play_stage_with_calls(&mut tables);
+            if !tournament.is_final_stage() {
+                for table in &tables {
+                    assert!(table.get_alive_player_count() <= ADVANCE);
+                }
+            }
+            tournament.record_stage_results(&tables);
+        }
+        assert!(tournament.get_stage() <= 2);
+        assert!(tournament.is_finished());
+        let standings = tournament.get_standings();
+        assert_eq!(standings.len(), ENTRANTS);
+        // Exactly one winner, who played every stage
+        assert_eq!(standings[0].0, 1);
+        assert_ne!(standings[1].0, 1);
+        let winner = &tournament.get_entrants()[standings[0].1];
+        assert_eq!(winner.seats.len(), tournament.get_stage() + 1);
+        assert!(winner.eliminated_stage.is_none());
+        // Entrants that went further always place higher
+        for pair in standings.windows(2) {
+            let first = &tournament.get_entrants()[pair[0].1];
+            let second = &tournament.get_entrants()[pair[1].1];
+            assert!(first.seats.len() >= second.seats.len());
+        }
+        let results = tournament.get_results();
+        assert_eq!(results.lines().count(), ENTRANTS);
+        assert!(results.starts_with("Place:  1"));
+    }
+}
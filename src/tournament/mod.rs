@@ -0,0 +1,84 @@
+use std::time::Duration;
+
+use futures_util::future::join_all;
+use tokio::net::TcpStream;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::engine::get_connection_input;
+use crate::table::Table;
+
+pub mod bracket;
+pub mod multi_table;
+
+/// Seats the entrants at as few tables as possible, dealing them around the tables so the
+/// table sizes differ by at most one. Returns the entrant at each seat of each table.
+pub fn seat_at_fewest_tables(entrants: Vec<usize>, max_table_size: usize) -> Vec<Vec<usize>> {
+    let table_count = entrants.len().div_ceil(max_table_size);
+    let mut seating = vec![vec![]; table_count];
+    for (i, entrant) in entrants.into_iter().enumerate() {
+        seating[i % table_count].push(entrant);
+    }
+    seating
+}
+
+/// Plays every table at the same time until none of them has a hand in progress, so tables that
+/// deal automatically are played until they're over and other tables until their current hand is done.
+/// `seating[table][seat]` is the index of the connection sitting at that seat of that table.
+pub async fn play_tables_concurrently(
+    tables: &mut [Table],
+    seating: &[Vec<usize>],
+    connections: &mut [WebSocketStream<TcpStream>],
+    read_timeout: Duration,
+) {
+    // Hand each table only the connections seated at it, so the tables can be played independently
+    let mut unseated_connections: Vec<_> = connections.iter_mut().map(Some).collect();
+    let table_connections: Vec<Vec<_>> = seating
+        .iter()
+        .map(|seats| {
+            seats
+                .iter()
+                .map(|&index| {
+                    unseated_connections[index]
+                        .take()
+                        .expect("A connection can only be seated at one table")
+                })
+                .collect()
+        })
+        .collect();
+    let table_futures =
+        tables
+            .iter_mut()
+            .zip(table_connections)
+            .map(|(table, mut connections)| async move {
+                play_table(table, &mut connections, read_timeout).await
+            });
+    join_all(table_futures).await;
+}
+
+/// Plays a single table until it stops dealing, the connections are indexed by seat
+async fn play_table(
+    table: &mut Table,
+    connections: &mut [&mut WebSocketStream<TcpStream>],
+    read_timeout: Duration,
+) {
+    while table.is_hand_in_progress() {
+        let current_index = table.get_current_player_index();
+        let state = table.get_state_string_for_current_player();
+        let input = get_connection_input(
+            connections[current_index],
+            state,
+            read_timeout,
+            current_index,
+        )
+        .await;
+        if let Err(error) = table.take_action(input) {
+            error!(
+                "Seat {current_index} couldn't take the action {input}, voiding the hand: {error}"
+            );
+            if let Err(error) = table.void_hand() {
+                error!("Couldn't void the hand, the table can't carry on: {error}");
+                break;
+            }
+        }
+    }
+}
@@ -0,0 +1,384 @@
+use std::cmp::Reverse;
+use std::time::Duration;
+
+use tokio::net::TcpStream;
+use tokio_tungstenite::WebSocketStream;
+
+use crate::engine::send_results;
+use crate::player_components::Player;
+use crate::table::{Table, TableConfig, TableError};
+use crate::tournament::{play_tables_concurrently, seat_at_fewest_tables};
+
+/// Everything known about one entrant's run through a multi-table tournament
+#[derive(Clone, Debug, Default)]
+pub struct MultiTableEntrant {
+    /// The round the entrant busted in, None if they are still playing
+    pub eliminated_round: Option<i32>,
+    /// How many times the entrant was moved to another table
+    pub table_moves: usize,
+    /// The money the entrant had when their last hand was dealt, to rank same round busts
+    pub hand_starting_money: i32,
+}
+
+/// One continuous tournament spread over several tables. The tables play hand for hand so that
+/// between hands players can be moved to keep the tables even, and tables can be broken as the field shrinks.
+pub struct MultiTableTournament {
+    /// The most entrants that may sit at one table
+    max_table_size: usize,
+    /// Every table still in play
+    tables: Vec<Table>,
+    /// The entrant sitting at each seat of each table, mirrors the players of each table
+    seating: Vec<Vec<usize>>,
+    /// The progress of every entrant, indexed by entrant
+    entrants: Vec<MultiTableEntrant>,
+    /// How many hands each table has been dealt, every table plays the same number of hands
+    round: i32,
+}
+
+impl MultiTableTournament {
+    /// Makes a tournament with every entrant seated, the first hand is dealt by `prepare_next_round`
+    pub fn new(entrant_count: usize, max_table_size: usize) -> Result<Self, String> {
+        if entrant_count == 0 {
+            return Err("No entrants to seat.".to_string());
+        }
+        // Three seats per table guarantees balanced tables never leave a player alone at a table
+        if !(3..=Table::MAX_PLAYERS).contains(&max_table_size) {
+            return Err(format!(
+                "The table size must be between 3 and {}",
+                Table::MAX_PLAYERS
+            ));
+        }
+        let seating = seat_at_fewest_tables((0..entrant_count).collect(), max_table_size);
+        let tables = seating
+            .iter()
+            .map(|seats| {
+                Table::with_config(
+                    seats.len(),
+                    TableConfig {
+                        deal_automatically: false,
+                        ante_increases: false,
+                        ..TableConfig::default()
+                    },
+                )
+            })
+            .collect::<Result<_, _>>()
+            .map_err(|error| error.to_string())?;
+        Ok(MultiTableTournament {
+            max_table_size,
+            tables,
+            seating,
+            entrants: vec![MultiTableEntrant::default(); entrant_count],
+            round: 0,
+        })
+    }
+
+    /// The ante every table uses for the given round, it grows the same way a single table's does
+    fn get_ante_for_round(&self, round: i32) -> i32 {
+        1 + (round - 1) / (self.max_table_size as i32 * 2) * Table::ANTE_INCREASE_AMOUNT
+    }
+
+    fn get_alive_entrant_count(&self) -> usize {
+        self.seating.iter().map(|seats| seats.len()).sum()
+    }
+
+    /// Returns true once there is at most one entrant left
+    pub fn is_finished(&self) -> bool {
+        self.get_alive_entrant_count() <= 1
+    }
+
+    /// Gets every table ready for the next round: busted players are knocked out, tables are broken
+    /// and balanced, and every table is dealt its next hand with the same ante.
+    pub fn prepare_next_round(&mut self) -> Result<(), TableError> {
+        self.round += 1;
+        let ante = self.get_ante_for_round(self.round);
+        self.eliminate_busted_players(ante)?;
+        self.break_tables()?;
+        self.balance_tables()?;
+        if self.is_finished() {
+            return Ok(());
+        }
+        for table in &mut self.tables {
+            table.set_ante(ante);
+            table.deal()?;
+        }
+        Ok(())
+    }
+
+    /// Knocks out every player that can't pay the next ante, they busted in the round just played
+    fn eliminate_busted_players(&mut self, ante: i32) -> Result<(), TableError> {
+        for (table, seats) in self.tables.iter_mut().zip(self.seating.iter_mut()) {
+            for seat in (0..seats.len()).rev() {
+                if table.get_players()[seat].total_money < ante {
+                    let player = table.remove_player(seat)?;
+                    let entrant = seats.remove(seat);
+                    self.entrants[entrant].eliminated_round = Some(self.round - 1);
+                    self.entrants[entrant].hand_starting_money = player.hand_starting_money;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Breaks the smallest table while everyone left would fit at one less table
+    fn break_tables(&mut self) -> Result<(), TableError> {
+        while self.tables.len() > 1
+            && self.get_alive_entrant_count() <= self.max_table_size * (self.tables.len() - 1)
+        {
+            let smallest = self.get_smallest_table_index();
+            let mut table = self.tables.remove(smallest);
+            let seats = self.seating.remove(smallest);
+            info!("Breaking a table, moving {} players", seats.len());
+            for entrant in seats {
+                let player = table.remove_player(0)?;
+                self.seat_at_smallest_table(player, entrant)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Moves players from the largest table to the smallest until no table has two more players than another
+    fn balance_tables(&mut self) -> Result<(), TableError> {
+        loop {
+            let largest = self.get_largest_table_index();
+            let smallest = self.get_smallest_table_index();
+            if self.seating[largest].len() <= self.seating[smallest].len() + 1 {
+                return Ok(());
+            }
+            // Move the player that would act first next hand
+            let table = &mut self.tables[largest];
+            let seat = (table.get_dealer_button_index() + 1) % table.get_player_count();
+            let player = table.remove_player(seat)?;
+            let entrant = self.seating[largest].remove(seat);
+            self.seat_at_smallest_table(player, entrant)?;
+        }
+    }
+
+    fn seat_at_smallest_table(&mut self, player: Player, entrant: usize) -> Result<(), TableError> {
+        let smallest = self.get_smallest_table_index();
+        self.tables[smallest].add_player(player)?;
+        self.seating[smallest].push(entrant);
+        self.entrants[entrant].table_moves += 1;
+        Ok(())
+    }
+
+    fn get_smallest_table_index(&self) -> usize {
+        (0..self.seating.len())
+            .min_by_key(|&table| self.seating[table].len())
+            .unwrap()
+    }
+
+    fn get_largest_table_index(&self) -> usize {
+        (0..self.seating.len())
+            .max_by_key(|&table| self.seating[table].len())
+            .unwrap()
+    }
+
+    /// Gets every entrant with their overall place, best first. Players still in are placed by
+    /// their chips, everyone else by how late they busted, players busting in the same round tie.
+    pub fn get_standings(&self) -> Vec<(usize, usize)> {
+        let chips = self.get_entrant_chips();
+        let standing_key = |entrant: &usize| {
+            (
+                Reverse(self.entrants[*entrant].eliminated_round.unwrap_or(i32::MAX)),
+                Reverse(chips[*entrant]),
+                Reverse(self.entrants[*entrant].hand_starting_money),
+            )
+        };
+        let mut entrants: Vec<usize> = (0..self.entrants.len()).collect();
+        entrants.sort_by_key(standing_key);
+        let mut place = 1;
+        let mut standings = vec![];
+        for (i, entrant) in entrants.iter().enumerate() {
+            // The entrants didn't tie, so increase the place
+            if i > 0 && standing_key(entrant) != standing_key(&entrants[i - 1]) {
+                place = i + 1;
+            }
+            standings.push((place, *entrant));
+        }
+        standings
+    }
+
+    /// Gets the chips of every entrant still seated, knocked out entrants have none
+    fn get_entrant_chips(&self) -> Vec<i32> {
+        let mut chips = vec![0; self.entrants.len()];
+        for (table, seats) in self.tables.iter().zip(&self.seating) {
+            for (player, &entrant) in table.get_players().iter().zip(seats) {
+                chips[entrant] = player.total_money;
+            }
+        }
+        chips
+    }
+
+    pub fn get_results(&self) -> String {
+        let chips = self.get_entrant_chips();
+        let mut result_string = String::new();
+        for (place, entrant) in self.get_standings() {
+            let progress = &self.entrants[entrant];
+            let eliminated_round = progress
+                .eliminated_round
+                .map_or("None".to_string(), |round| round.to_string());
+            result_string += format!(
+                "Place:{place:>3}, Entrant:{entrant:>4}, Eliminated Round:{eliminated_round:>6}, Chips:{:>6}, Table Moves:{:>3}\n",
+                chips[entrant], progress.table_moves
+            )
+            .as_str();
+        }
+        result_string
+    }
+
+    /// Plays the whole tournament, entrant i is the connection at index i
+    pub async fn play(
+        &mut self,
+        connections: &mut [WebSocketStream<TcpStream>],
+        read_timeout: Duration,
+    ) -> String {
+        loop {
+            if let Err(error) = self.prepare_next_round() {
+                error!(
+                    "Couldn't prepare round {}, ending the tournament: {error}",
+                    self.round
+                );
+                break;
+            }
+            if self.is_finished() {
+                break;
+            }
+            debug!(
+                "Playing round {} at {} tables",
+                self.round,
+                self.tables.len()
+            );
+            play_tables_concurrently(&mut self.tables, &self.seating, connections, read_timeout)
+                .await;
+        }
+        let results = format!("Tournament is over:\n{}", self.get_results());
+        println!("{}", results);
+        send_results(connections, &results).await;
+        results
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::{thread_rng, Rng};
+
+    use crate::actions::HandAction;
+    use crate::tournament::multi_table::MultiTableTournament;
+
+    fn play_round(tournament: &mut MultiTableTournament, hand_action: fn() -> HandAction) {
+        for table in &mut tournament.tables {
+            while table.is_hand_in_progress() {
+                table.take_action(hand_action()).unwrap();
+            }
+        }
+    }
+
+    fn check_tables_balanced(tournament: &MultiTableTournament) {
+        let alive = tournament.get_alive_entrant_count();
+        assert_eq!(
+            tournament.tables.len(),
+            alive.div_ceil(tournament.max_table_size)
+        );
+        let sizes: Vec<usize> = tournament.seating.iter().map(|x| x.len()).collect();
+        assert!(sizes.iter().max().unwrap() - sizes.iter().min().unwrap() <= 1);
+        for (table, seats) in tournament.tables.iter().zip(&tournament.seating) {
+            assert_eq!(table.get_player_count(), seats.len());
+            assert_eq!(table.get_ante(), tournament.tables[0].get_ante());
+            for (seat, player) in table.get_players().iter().enumerate() {
+                assert_eq!(player.get_id() as usize, seat);
+                assert!(player.is_alive());
+            }
+        }
+    }
+
+    #[test]
+    fn test_bad_settings() {
+        assert!(MultiTableTournament::new(0, 10).is_err());
+        assert!(MultiTableTournament::new(30, 2).is_err());
+        assert!(MultiTableTournament::new(30, 24).is_err());
+        assert!(MultiTableTournament::new(30, 3).is_ok());
+    }
+
+    #[test]
+    fn test_first_round_is_dealt_everywhere() {
+        let mut tournament = MultiTableTournament::new(30, 7).unwrap();
+        for table in &tournament.tables {
+            assert!(!table.is_hand_in_progress());
+        }
+        tournament.prepare_next_round().unwrap();
+        check_tables_balanced(&tournament);
+        assert_eq!(tournament.tables.len(), 5);
+        for table in &tournament.tables {
+            assert!(table.is_hand_in_progress());
+        }
+    }
+
+    #[test]
+    fn test_balancing_and_breaking() {
+        let mut tournament = MultiTableTournament::new(8, 4).unwrap();
+        tournament.prepare_next_round().unwrap();
+        assert_eq!(tournament.tables.len(), 2);
+        play_round(&mut tournament, || HandAction::Fold);
+        // Three players at the first table bust, leaving 1 and 4 seated
+        let busted: Vec<usize> = tournament.seating[0][0..3].to_vec();
+        for seat in 0..3 {
+            tournament.tables[0].get_players_mut()[seat].total_money = 0;
+        }
+        tournament.prepare_next_round().unwrap();
+        check_tables_balanced(&tournament);
+        let mut sizes: Vec<usize> = tournament.seating.iter().map(|x| x.len()).collect();
+        sizes.sort();
+        assert_eq!(sizes, vec![2, 3]);
+        for entrant in busted {
+            assert_eq!(tournament.entrants[entrant].eliminated_round, Some(1));
+        }
+        assert_eq!(
+            tournament
+                .entrants
+                .iter()
+                .map(|x| x.table_moves)
+                .sum::<usize>(),
+            1
+        );
+        play_round(&mut tournament, || HandAction::Fold);
+        // Two more players bust, so the last three fit at one table
+        tournament.tables[0].get_players_mut()[0].total_money = 0;
+        tournament.tables[1].get_players_mut()[0].total_money = 0;
+        tournament.prepare_next_round().unwrap();
+        check_tables_balanced(&tournament);
+        assert_eq!(tournament.tables.len(), 1);
+        assert_eq!(tournament.seating[0].len(), 3);
+        assert!(tournament.tables[0].is_hand_in_progress());
+    }
+
+    #[test]
+    fn test_full_tournament() {
+        const ENTRANTS: usize = 30;
+        let mut tournament = MultiTableTournament::new(ENTRANTS, 7).unwrap();
+        let mut previous_ante = 0;
+        loop {
+            tournament.prepare_next_round().unwrap();
+            if tournament.is_finished() {
+                break;
+            }
+            check_tables_balanced(&tournament);
+            // The ante never goes down
+            assert!(tournament.tables[0].get_ante() >= previous_ante);
+            previous_ante = tournament.tables[0].get_ante();
+            play_round(&mut tournament, || match thread_rng().gen_range(0..3) {
+                0 => HandAction::Raise(100),
+                1 => HandAction::Call,
+                _ => HandAction::Fold,
+            });
+        }
+        let standings = tournament.get_standings();
+        assert_eq!(standings.len(), ENTRANTS);
+        assert_eq!(standings[0].0, 1);
+        assert!(tournament.entrants[standings[0].1]
+            .eliminated_round
+            .is_none());
+        let results = tournament.get_results();
+        assert_eq!(results.lines().count(), ENTRANTS);
+        assert!(results.starts_with("Place:  1"));
+    }
+}